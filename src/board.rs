@@ -0,0 +1,654 @@
+use crate::engine::Engine;
+use crate::log::Level;
+use crate::pattern::Pattern;
+use crate::region::Region;
+use crate::resources::ResourceField;
+use crate::rle;
+use crate::rng::Rng;
+use crate::rule::Rule;
+use crate::topology::Topology;
+use crate::trail::DyingPolicy;
+use crate::weighted::WeightedRule;
+
+pub const CYCLES_TO_DIE: usize = 8;
+pub const MAX_TRAIL_LENGTH: usize = 64;
+/// How much of a random soup starts alive, unless asked otherwise.
+pub const SOUP_DENSITY: f64 = 0.25;
+
+#[derive(Clone)]
+pub enum CellState {
+    Alive,
+    Dying(usize),
+    Dead,
+    /// A permanent hazard painted by the user. It never changes and may kill
+    /// live cells next to it, see `Board::poison_chance`.
+    Poison,
+}
+
+#[derive(Clone)]
+pub struct Cell {
+    pub state: CellState,
+    /// Live neighbors, or their weighted sum under a weighted rule.
+    pub neighbor_count: usize,
+    pub poison_neighbor_count: usize,
+    /// Frozen cells keep their state, and ignore edits, until unfrozen, but
+    /// still count as neighbors of the cells around them.
+    pub frozen: bool,
+}
+
+#[derive(Clone)]
+pub struct Board {
+    pub generation: usize,
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Cell>,
+    /// Number of cycles a cell spends `Dying` before it is `Dead`.
+    pub trail_length: usize,
+    pub topology: Topology,
+    pub rule: Rule,
+    /// Replaces `rule` when set, with neighbors counting by their weights.
+    pub weighted_rule: Option<WeightedRule>,
+    /// Probability each generation that a live cell next to poison dies. At
+    /// 1.0 (the default) poison always kills. Draws are derived from the
+    /// generation and cell index, so seeded runs and replays reproduce.
+    pub poison_chance: f64,
+    /// Whether dying cells count as live neighbors and can be born again.
+    pub dying_policy: DyingPolicy,
+    /// When set, births also need enough resource under the cell, and live
+    /// cells use it up, see `ResourceField`.
+    pub resources: Option<ResourceField>,
+    pub engine: Engine,
+    /// What the neighbor counts were last computed from, which the
+    /// incremental engine diffs against.
+    pub(crate) counted: Option<Counted>,
+}
+
+/// A snapshot of the cells that affect neighbor counts.
+#[derive(Clone)]
+pub(crate) struct Counted {
+    width: usize,
+    topology: Topology,
+    weights: [usize; 8],
+    kinds: Vec<NeighborKind>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NeighborKind {
+    Alive,
+    Poison,
+    Other,
+}
+
+impl Cell {
+    pub fn dead() -> Self {
+        Cell {
+            state: CellState::Dead,
+            neighbor_count: 0,
+            poison_neighbor_count: 0,
+            frozen: false,
+        }
+    }
+
+    pub fn alive() -> Self {
+        Cell {
+            state: CellState::Alive,
+            neighbor_count: 0,
+            poison_neighbor_count: 0,
+            frozen: false,
+        }
+    }
+
+    pub fn poison() -> Self {
+        Cell {
+            state: CellState::Poison,
+            neighbor_count: 0,
+            poison_neighbor_count: 0,
+            frozen: false,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        matches!(self.state, CellState::Alive)
+    }
+
+    pub fn is_poison(&self) -> bool {
+        matches!(self.state, CellState::Poison)
+    }
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut board = Self::empty(width, height);
+
+        let glider_gun = Pattern::builtin("glider_gun").expect("glider gun is built in");
+        board.stamp(&glider_gun, 1, 1);
+
+        board
+    }
+
+    pub fn empty(width: usize, height: usize) -> Self {
+        Self {
+            generation: 0,
+            width,
+            height,
+            cells: vec![Cell::dead(); width * height],
+            trail_length: CYCLES_TO_DIE,
+            topology: Topology::default(),
+            rule: Rule::default(),
+            weighted_rule: None,
+            poison_chance: 1.0,
+            dying_policy: DyingPolicy::default(),
+            resources: None,
+            engine: Engine::default(),
+            counted: None,
+        }
+    }
+
+    /// Builds a random soup where each cell is alive with the given density.
+    pub fn seeded(width: usize, height: usize, seed: u64, density: f64) -> Self {
+        let mut board = Self::empty(width, height);
+        let mut rng = Rng::new(seed);
+
+        for cell in board.cells.iter_mut() {
+            if rng.next_f64() < density {
+                *cell = Cell::alive();
+            }
+        }
+
+        board
+    }
+
+    /// Places the pattern with its origin at `(x, y)`, clipping anything that
+    /// falls outside the board.
+    pub fn stamp(&mut self, pattern: &Pattern, x: i32, y: i32) {
+        for (dx, dy) in pattern.cells.iter() {
+            if let Some(index) = self.edit_index(x + dx, y + dy) {
+                self.cells[index].state = CellState::Alive;
+            }
+        }
+    }
+
+    /// Changes the board size, keeping every cell at the same coordinates
+    /// and dropping those that no longer fit.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut cells = vec![Cell::dead(); width * height];
+
+        for y in 0..self.height.min(height) {
+            for x in 0..self.width.min(width) {
+                cells[y * width + x] = self.cells[y * self.width + x].clone();
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+
+        if let Some(resources) = &mut self.resources {
+            *resources = ResourceField::new(resources.rule, width * height);
+        }
+    }
+
+    pub fn toggle(&mut self, x: i32, y: i32) {
+        if let Some(index) = self.edit_index(x, y) {
+            self.cells[index].state = match self.cells[index].state {
+                CellState::Alive => CellState::Dead,
+                _ => CellState::Alive,
+            };
+        }
+    }
+
+    /// Paints poison on the cell, or clears it if it is already poison.
+    pub fn toggle_poison(&mut self, x: i32, y: i32) {
+        if let Some(index) = self.edit_index(x, y) {
+            self.cells[index].state = match self.cells[index].state {
+                CellState::Poison => CellState::Dead,
+                _ => CellState::Poison,
+            };
+        }
+    }
+
+    /// Changes the trail length, shortening the trails of cells that are
+    /// already dying so they never exceed it.
+    pub fn set_trail_length(&mut self, trail_length: usize) {
+        self.trail_length = trail_length.min(MAX_TRAIL_LENGTH);
+
+        for cell in self.cells.iter_mut() {
+            if let CellState::Dying(cycles_left) = cell.state {
+                cell.state = CellState::Dying(cycles_left.min(self.trail_length));
+            }
+        }
+    }
+
+    /// Advances the board one generation using the neighbor counts from
+    /// `update_live_neighbor_counts`, and counts the generation.
+    pub fn step(&mut self) {
+        let _span = crate::span!(Level::Trace, "step");
+
+        self.generation += 1;
+
+        let generation = self.generation as u64;
+        let rule = self.rule;
+        let weighted_rule = self.weighted_rule;
+        let is_born = |count| match &weighted_rule {
+            Some(weighted_rule) => weighted_rule.is_born(count),
+            None => rule.is_born(count),
+        };
+        let survives = |count| match &weighted_rule {
+            Some(weighted_rule) => weighted_rule.survives(count),
+            None => rule.survives(count),
+        };
+        let resources = self.resources.as_ref();
+        let reborn = self.dying_policy != DyingPolicy::Decay;
+        let is_born = |index, count| {
+            is_born(count) && resources.is_none_or(|resources| resources.allows_birth(index))
+        };
+
+        for (index, cell) in self.cells.iter_mut().enumerate() {
+            if cell.frozen {
+                continue;
+            }
+
+            match cell.state {
+                CellState::Alive => {
+                    let poisoned = cell.poison_neighbor_count > 0
+                        && Rng::new((generation << 32) ^ index as u64).next_f64()
+                            < self.poison_chance;
+
+                    if !survives(cell.neighbor_count) || poisoned {
+                        cell.state = CellState::Dying(self.trail_length)
+                    }
+                }
+                CellState::Dying(cycles_left) => {
+                    if reborn && is_born(index, cell.neighbor_count) {
+                        cell.state = CellState::Alive
+                    } else if cycles_left == 0 {
+                        cell.state = CellState::Dead
+                    } else {
+                        cell.state = CellState::Dying(cycles_left - 1)
+                    }
+                }
+                CellState::Dead => {
+                    if is_born(index, cell.neighbor_count) {
+                        cell.state = CellState::Alive
+                    }
+                }
+                CellState::Poison => {}
+            }
+        }
+
+        if let Some(resources) = &mut self.resources {
+            resources.update(self.cells.iter().map(Cell::is_alive));
+        }
+    }
+
+    pub fn update_live_neighbor_counts(&mut self) {
+        let _span = crate::span!(Level::Trace, "neighbor counting");
+
+        match self.engine {
+            Engine::Naive => {
+                self.count_all_neighbors();
+                self.counted = None;
+            }
+            Engine::Incremental => self.count_changed_neighbors(),
+        }
+    }
+
+    fn count_all_neighbors(&mut self) {
+        let neighbor_counts: Vec<(usize, usize)> = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(index, _cell)| self.neighbor_counts(index))
+            .collect();
+
+        for (index, cell) in self.cells.iter_mut().enumerate() {
+            (cell.neighbor_count, cell.poison_neighbor_count) = neighbor_counts[index];
+        }
+    }
+
+    /// Recounts the neighbors of cells next to ones that became or stopped
+    /// being alive or poison since the last count, and of those cells
+    /// themselves in case they were replaced whole. Falls back to counting
+    /// everything when there is no usable previous count, e.g. after a
+    /// resize or topology change.
+    fn count_changed_neighbors(&mut self) {
+        let kinds: Vec<NeighborKind> = self
+            .cells
+            .iter()
+            .map(|cell| match cell.state {
+                CellState::Alive => NeighborKind::Alive,
+                CellState::Dying(_) if self.dying_policy == DyingPolicy::Alive => {
+                    NeighborKind::Alive
+                }
+                CellState::Poison => NeighborKind::Poison,
+                _ => NeighborKind::Other,
+            })
+            .collect();
+        let previous = self.counted.take().filter(|counted| {
+            counted.width == self.width
+                && counted.topology == self.topology
+                && counted.weights == self.neighbor_weights()
+                && counted.kinds.len() == kinds.len()
+        });
+
+        match previous {
+            Some(previous) => {
+                let mut affected: Vec<usize> = kinds
+                    .iter()
+                    .zip(previous.kinds.iter())
+                    .enumerate()
+                    .filter(|(_, (kind, previous))| kind != previous)
+                    .flat_map(|(index, _)| {
+                        std::iter::once(Some(index)).chain(self.neighbor_indices(index))
+                    })
+                    .flatten()
+                    .collect();
+
+                affected.sort_unstable();
+                affected.dedup();
+
+                for index in affected {
+                    let counts = self.neighbor_counts(index);
+                    let cell = &mut self.cells[index];
+
+                    (cell.neighbor_count, cell.poison_neighbor_count) = counts;
+                }
+            }
+            None => self.count_all_neighbors(),
+        }
+
+        self.counted = Some(Counted {
+            width: self.width,
+            topology: self.topology,
+            weights: self.neighbor_weights(),
+            kinds,
+        });
+    }
+
+    /// A hash of every cell's state, including trails and poison, that is
+    /// stable across runs, platforms and engines, for checking that two
+    /// simulations stayed in lockstep.
+    pub fn state_hash(&self) -> u64 {
+        let header = [self.width as u64, self.height as u64];
+
+        fnv1a(
+            header
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .chain(self.cells.iter().map(|cell| match cell.state {
+                    CellState::Dead => 0,
+                    CellState::Alive => 1,
+                    CellState::Poison => 2,
+                    CellState::Dying(cycles_left) => 3 + cycles_left.min(MAX_TRAIL_LENGTH) as u8,
+                })),
+        )
+    }
+
+    /// A hash of the shape of the live cells alone, ignoring where they are
+    /// on the board, the board's size and any trails, so a spaceship hashes
+    /// the same after each full period.
+    pub fn canonical_hash(&self) -> u64 {
+        let live_cells = self.live_cells();
+        let origin = live_cells
+            .iter()
+            .fold((i32::MAX, i32::MAX), |(x, y), cell| {
+                (x.min(cell.0), y.min(cell.1))
+            });
+
+        fnv1a(
+            live_cells
+                .iter()
+                .flat_map(|(x, y)| [x - origin.0, y - origin.1])
+                .flat_map(|value| value.to_le_bytes()),
+        )
+    }
+
+    /// Builds a board of the given size from an RLE pattern placed in its
+    /// center, running the rule the pattern names (Conway's Life if none).
+    pub fn from_rle(text: &str, width: usize, height: usize) -> Result<Self, String> {
+        let pattern = rle::decode(text)?;
+        let (pattern_width, pattern_height) = pattern.size();
+        let mut board = Self::empty(width, height);
+
+        if let Some(rule) = &pattern.rule {
+            board.rule = Rule::parse(rule)?;
+        }
+
+        board.stamp(
+            &pattern,
+            (width as i32 - pattern_width as i32) / 2,
+            (height as i32 - pattern_height as i32) / 2,
+        );
+
+        Ok(board)
+    }
+
+    /// The cells the next step would bring to life and kill, found on a
+    /// copy so the board itself doesn't move on.
+    pub fn predict_step(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut next = self.clone();
+
+        next.step_n(1);
+
+        let changed = |alive: bool| {
+            (0..self.cells.len())
+                .filter(|index| {
+                    self.cells[*index].is_alive() != alive && next.cells[*index].is_alive() == alive
+                })
+                .collect()
+        };
+
+        (changed(true), changed(false))
+    }
+
+    /// Advances the board `generations` generations.
+    pub fn step_n(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.update_live_neighbor_counts();
+            self.step();
+        }
+    }
+
+    /// Bytes used by the board and its cells.
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.cells.capacity() * std::mem::size_of::<Cell>()
+            + self
+                .resources
+                .as_ref()
+                .map_or(0, |resources| resources.memory_usage())
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_alive()).count()
+    }
+
+    /// Returns whether each cell is alive, in cell order.
+    pub fn alive_mask(&self) -> Vec<bool> {
+        self.cells.iter().map(|cell| cell.is_alive()).collect()
+    }
+
+    /// Coordinates of every live cell, row by row.
+    pub fn live_cells(&self) -> Vec<(i32, i32)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_alive())
+            .map(|(index, _)| self.index_to_coordinates(index))
+            .collect()
+    }
+
+    /// Returns whether each cell in the region is alive, row by row. Cells
+    /// outside the board count as dead.
+    pub fn alive_in(&self, region: &Region) -> Vec<bool> {
+        let mut alive = Vec::with_capacity(region.width * region.height);
+
+        for dy in 0..region.height as i32 {
+            for dx in 0..region.width as i32 {
+                let (x, y) = (region.x + dx, region.y + dy);
+                let inside = x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32;
+
+                alive.push(inside && self.cells[y as usize * self.width + x as usize].is_alive());
+            }
+        }
+
+        alive
+    }
+
+    /// Copies the region onto a board of its own, starting at generation 0
+    /// with dead edges but the same rules, engine and trail length. Cells
+    /// outside this board come across dead.
+    pub fn extract(&self, region: &Region) -> Board {
+        let mut extracted = Board::empty(region.width, region.height);
+
+        extracted.trail_length = self.trail_length;
+        extracted.rule = self.rule;
+        extracted.weighted_rule = self.weighted_rule;
+        extracted.poison_chance = self.poison_chance;
+        extracted.dying_policy = self.dying_policy;
+        extracted.resources = self
+            .resources
+            .as_ref()
+            .map(|resources| ResourceField::new(resources.rule, region.width * region.height));
+        extracted.engine = self.engine;
+
+        for (index, cell) in extracted.cells.iter_mut().enumerate() {
+            let x = region.x + (index % region.width) as i32;
+            let y = region.y + (index / region.width) as i32;
+
+            if x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32 {
+                *cell = self.cells[y as usize * self.width + x as usize].clone();
+                cell.neighbor_count = 0;
+                cell.poison_neighbor_count = 0;
+            }
+        }
+
+        extracted
+    }
+
+    /// Kills every live or dying cell in the region, leaving poison and
+    /// frozen cells alone.
+    pub fn clear(&mut self, region: &Region) {
+        for index in self.indices_in(region) {
+            let cell = &mut self.cells[index];
+
+            if !cell.is_poison() && !cell.frozen {
+                cell.state = CellState::Dead;
+            }
+        }
+    }
+
+    /// Freezes or unfreezes every cell in the region, clipped to the board.
+    pub fn set_frozen(&mut self, region: &Region, frozen: bool) {
+        for index in self.indices_in(region) {
+            self.cells[index].frozen = frozen;
+        }
+    }
+
+    /// Whether the region has cells on the board and all of them are
+    /// frozen.
+    pub fn is_frozen(&self, region: &Region) -> bool {
+        let indices = self.indices_in(region);
+
+        !indices.is_empty() && indices.iter().all(|index| self.cells[*index].frozen)
+    }
+
+    /// Coordinates of every frozen cell, row by row.
+    pub fn frozen_cells(&self) -> Vec<(i32, i32)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.frozen)
+            .map(|(index, _)| self.index_to_coordinates(index))
+            .collect()
+    }
+
+    /// Indices of the region's cells that are on the board, row by row.
+    fn indices_in(&self, region: &Region) -> Vec<usize> {
+        let xs = region.x.max(0)..(region.x + region.width as i32).min(self.width as i32);
+
+        (region.y.max(0)..(region.y + region.height as i32).min(self.height as i32))
+            .flat_map(|y| {
+                xs.clone()
+                    .map(move |x| y as usize * self.width + x as usize)
+            })
+            .collect()
+    }
+
+    pub fn index_to_coordinates(&self, index: usize) -> (i32, i32) {
+        let x = index.wrapping_rem(self.width) as i32;
+        let y = index.wrapping_div(self.width) as i32;
+
+        (x, y)
+    }
+
+    fn coordinates_to_index(&self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = self.topology.wrap(x, y, self.width, self.height)?;
+
+        Some(y * self.width + x)
+    }
+
+    /// The index of the cell an edit at `(x, y)` changes, or `None` if it
+    /// falls off the board or on a frozen cell.
+    fn edit_index(&self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = self.topology.place(x, y, self.width, self.height)?;
+        let index = y * self.width + x;
+
+        Some(index).filter(|index| !self.cells[*index].frozen)
+    }
+
+    /// Indices of the eight cells around `index`, or `None` for those beyond
+    /// a dead edge.
+    fn neighbor_indices(&self, index: usize) -> [Option<usize>; 8] {
+        let (x, y) = self.index_to_coordinates(index);
+
+        [
+            self.coordinates_to_index(x - 1, y - 1),
+            self.coordinates_to_index(x, y - 1),
+            self.coordinates_to_index(x + 1, y - 1),
+            self.coordinates_to_index(x - 1, y),
+            self.coordinates_to_index(x + 1, y),
+            self.coordinates_to_index(x - 1, y + 1),
+            self.coordinates_to_index(x, y + 1),
+            self.coordinates_to_index(x + 1, y + 1),
+        ]
+    }
+
+    /// How much each neighbor, in the order of `neighbor_indices`, counts
+    /// towards a cell's live neighbor count.
+    fn neighbor_weights(&self) -> [usize; 8] {
+        self.weighted_rule
+            .map_or([1; 8], |weighted_rule| weighted_rule.weights)
+    }
+
+    /// Counts the live neighbors of the cell at `index`, weighted by
+    /// `neighbor_weights`, and its poison neighbors. Dying neighbors count
+    /// as live under `DyingPolicy::Alive`.
+    fn neighbor_counts(&self, index: usize) -> (usize, usize) {
+        let dying_alive = self.dying_policy == DyingPolicy::Alive;
+
+        self.neighbor_indices(index)
+            .iter()
+            .zip(self.neighbor_weights())
+            .filter_map(|(index, weight)| Some((index.as_ref()?, weight)))
+            .fold((0, 0), |(alive, poison), (index, weight)| {
+                match self.cells[*index].state {
+                    CellState::Alive => (alive + weight, poison),
+                    CellState::Dying(_) if dying_alive => (alive + weight, poison),
+                    CellState::Poison => (alive, poison + 1),
+                    _ => (alive, poison),
+                }
+            })
+    }
+}
+
+/// The 64-bit FNV-1a hash, which unlike `DefaultHasher` is guaranteed not to
+/// change between Rust releases.
+fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
@@ -0,0 +1,168 @@
+use crate::log::Level;
+use crate::rng::Rng;
+
+/// A 3D life-like rule in Bays' notation `EₗEᵤFₗFᵤ`: a live cell survives
+/// with between `Eₗ` and `Eᵤ` live neighbors, and a dead cell comes alive
+/// with between `Fₗ` and `Fᵤ`, out of its 26 neighbors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule3d {
+    pub survive_min: usize,
+    pub survive_max: usize,
+    pub birth_min: usize,
+    pub birth_max: usize,
+}
+
+/// A cubic lattice of cells using the 26-cell Moore neighborhood. Cells
+/// outside the lattice are dead.
+pub struct Board3d {
+    pub generation: usize,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub cells: Vec<bool>,
+    pub rule: Rule3d,
+}
+
+impl Rule3d {
+    /// Bays' Life 4555.
+    pub const LIFE_4555: Rule3d = Rule3d {
+        survive_min: 4,
+        survive_max: 5,
+        birth_min: 5,
+        birth_max: 5,
+    };
+
+    /// Parses a four digit rule such as `4555` or `5766`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let digits: Vec<usize> = text
+            .chars()
+            .map(|c| c.to_digit(10).map(|digit| digit as usize))
+            .collect::<Option<Vec<usize>>>()
+            .filter(|digits| digits.len() == 4)
+            .ok_or_else(|| format!("expected a four digit 3D rule: {}", text))?;
+
+        Ok(Rule3d {
+            survive_min: digits[0],
+            survive_max: digits[1],
+            birth_min: digits[2],
+            birth_max: digits[3],
+        })
+    }
+
+    fn next_state(&self, alive: bool, neighbor_count: usize) -> bool {
+        if alive {
+            (self.survive_min..=self.survive_max).contains(&neighbor_count)
+        } else {
+            (self.birth_min..=self.birth_max).contains(&neighbor_count)
+        }
+    }
+}
+
+impl Board3d {
+    pub fn empty(width: usize, height: usize, depth: usize, rule: Rule3d) -> Self {
+        Board3d {
+            generation: 0,
+            width,
+            height,
+            depth,
+            cells: vec![false; width * height * depth],
+            rule,
+        }
+    }
+
+    /// Builds a random soup filling the central half of the lattice on each
+    /// axis, leaving room around it for the pattern to grow.
+    pub fn seeded(
+        width: usize,
+        height: usize,
+        depth: usize,
+        rule: Rule3d,
+        seed: u64,
+        density: f64,
+    ) -> Self {
+        let mut board = Self::empty(width, height, depth, rule);
+        let mut rng = Rng::new(seed);
+
+        for z in depth / 4..depth * 3 / 4 {
+            for y in height / 4..height * 3 / 4 {
+                for x in width / 4..width * 3 / 4 {
+                    if rng.next_f64() < density {
+                        let index = board.index(x, y, z);
+                        board.cells[index] = true;
+                    }
+                }
+            }
+        }
+
+        board
+    }
+
+    pub fn step(&mut self) {
+        let _span = crate::span!(Level::Trace, "3d step");
+
+        let mut next = vec![false; self.cells.len()];
+
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let index = self.index(x, y, z);
+                    let neighbor_count = self.live_neighbor_count(x, y, z);
+
+                    next[index] = self.rule.next_state(self.cells[index], neighbor_count);
+                }
+            }
+        }
+
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    /// Bytes used by the lattice and its cells.
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() + self.cells.capacity() * std::mem::size_of::<bool>()
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|alive| **alive).count()
+    }
+
+    pub fn is_alive(&self, x: usize, y: usize, z: usize) -> bool {
+        self.cells[self.index(x, y, z)]
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.height + y) * self.width + x
+    }
+
+    fn live_neighbor_count(&self, x: usize, y: usize, z: usize) -> usize {
+        let mut count = 0;
+
+        for dz in -1..=1i32 {
+            for dy in -1..=1i32 {
+                for dx in -1..=1i32 {
+                    if dx == 0 && dy == 0 && dz == 0 {
+                        continue;
+                    }
+
+                    let (nx, ny, nz) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+
+                    if nx < 0
+                        || ny < 0
+                        || nz < 0
+                        || nx >= self.width as i32
+                        || ny >= self.height as i32
+                        || nz >= self.depth as i32
+                    {
+                        continue;
+                    }
+
+                    if self.is_alive(nx as usize, ny as usize, nz as usize) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        count
+    }
+}
@@ -0,0 +1,97 @@
+use crate::board::Board;
+
+/// Number of bookmark slots, numbered from 0.
+pub const BOOKMARK_SLOTS: usize = 10;
+
+/// A snapshot of the full board state, trails and all.
+#[derive(Clone)]
+pub struct Bookmark {
+    pub name: String,
+    pub board: Board,
+}
+
+/// In-memory board snapshots for branching experiments from a common
+/// starting point without writing files.
+#[derive(Clone)]
+pub struct Bookmarks {
+    slots: Vec<Option<Bookmark>>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Bookmarks {
+            slots: (0..BOOKMARK_SLOTS).map(|_| None).collect(),
+        }
+    }
+
+    /// Saves a copy of the board in the slot, replacing whatever was there.
+    pub fn save(&mut self, slot: usize, board: &Board) -> Result<(), String> {
+        let name = format!("bookmark {} (generation {})", slot, board.generation);
+
+        crate::info!("saving {}", name);
+
+        *self.slot(slot)? = Some(Bookmark {
+            name,
+            board: board.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Replaces the board with the one saved in the slot. The generation
+    /// counter keeps counting forward, since replays are keyed on it, and
+    /// the engine and config-given weighted rule, poison chance and dying
+    /// policy stay as they are.
+    pub fn restore(&mut self, slot: usize, board: &mut Board) -> Result<(), String> {
+        let bookmark = self
+            .slot(slot)?
+            .as_ref()
+            .ok_or_else(|| format!("bookmark {} is empty", slot))?;
+
+        crate::info!("restoring {}", bookmark.name);
+
+        *board = Board {
+            generation: board.generation,
+            engine: board.engine,
+            weighted_rule: board.weighted_rule,
+            poison_chance: board.poison_chance,
+            dying_policy: board.dying_policy,
+            ..bookmark.board.clone()
+        };
+
+        Ok(())
+    }
+
+    /// The saved bookmarks and their slots.
+    pub fn saved(&self) -> impl Iterator<Item = (usize, &Bookmark)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, bookmark)| Some((slot, bookmark.as_ref()?)))
+    }
+
+    pub fn is_saved(&self, slot: usize) -> bool {
+        matches!(self.slots.get(slot), Some(Some(_)))
+    }
+
+    /// Bytes held by the saved boards.
+    pub fn memory_usage(&self) -> usize {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|bookmark| bookmark.board.memory_usage() + bookmark.name.capacity())
+            .sum()
+    }
+
+    fn slot(&mut self, slot: usize) -> Result<&mut Option<Bookmark>, String> {
+        self.slots
+            .get_mut(slot)
+            .ok_or_else(|| format!("no bookmark slot {}", slot))
+    }
+}
+
+impl Default for Bookmarks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,226 @@
+use crate::rng::Rng;
+
+/// Brush radius used for circles and sprays unless configured otherwise.
+pub const DEFAULT_BRUSH_RADIUS: usize = 5;
+/// Fraction of a spray's circle painted per dab unless configured otherwise.
+pub const DEFAULT_BRUSH_DENSITY: f64 = 0.25;
+/// Radius of a noise burst unless configured otherwise.
+pub const DEFAULT_NOISE_RADIUS: usize = 10;
+/// Fraction of a noise burst's circle brought to life unless configured
+/// otherwise.
+pub const DEFAULT_NOISE_DENSITY: f64 = 0.3;
+
+/// The footprint painted with each click or drag of the mouse.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BrushShape {
+    /// A single cell. Clicking toggles it rather than painting it alive.
+    #[default]
+    Cell,
+    /// A 3x3 square.
+    Square,
+    /// A filled circle of the brush radius.
+    Circle,
+    /// Random cells within a circle of the brush radius, each painted with
+    /// the brush density.
+    Spray,
+}
+
+impl BrushShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrushShape::Cell => "cell",
+            BrushShape::Square => "square",
+            BrushShape::Circle => "circle",
+            BrushShape::Spray => "spray",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            BrushShape::Cell => BrushShape::Square,
+            BrushShape::Square => BrushShape::Circle,
+            BrushShape::Circle => BrushShape::Spray,
+            BrushShape::Spray => BrushShape::Cell,
+        }
+    }
+}
+
+/// What a left click or drag does.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Tool {
+    /// Paints with the brush.
+    #[default]
+    Brush,
+    /// Dragging outlines a rectangle between the press and release cells.
+    Rectangle,
+    /// Dragging fills a rectangle between the press and release cells.
+    FilledRectangle,
+    /// Dragging places a spaceship at the press cell, heading toward the
+    /// release cell.
+    Launcher,
+    /// Dragging selects a rectangle to simulate on its own in a sandbox.
+    Sandbox,
+    /// Dragging selects a rectangle to freeze, or to unfreeze if it is all
+    /// frozen already.
+    Freeze,
+}
+
+impl Tool {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Brush => "brush",
+            Tool::Rectangle => "rectangle",
+            Tool::FilledRectangle => "filled rectangle",
+            Tool::Launcher => "launcher",
+            Tool::Sandbox => "sandbox",
+            Tool::Freeze => "freeze",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Tool::Brush => Tool::Rectangle,
+            Tool::Rectangle => Tool::FilledRectangle,
+            Tool::FilledRectangle => Tool::Launcher,
+            Tool::Launcher => Tool::Sandbox,
+            Tool::Sandbox => Tool::Freeze,
+            Tool::Freeze => Tool::Brush,
+        }
+    }
+}
+
+/// Paints cells under the mouse. Offsets are relative to the cell under
+/// the cursor.
+pub struct Brush {
+    pub shape: BrushShape,
+    pub radius: usize,
+    pub density: f64,
+    rng: Rng,
+    /// Cell last clicked or painted. Dragging within it paints nothing new,
+    /// and shift-click lines start from it.
+    pub anchor: Option<(i32, i32)>,
+}
+
+impl Brush {
+    pub fn new(radius: usize, density: f64, seed: u64) -> Self {
+        Brush {
+            shape: BrushShape::default(),
+            radius,
+            density,
+            rng: Rng::new(seed),
+            anchor: None,
+        }
+    }
+
+    /// Every cell the brush can paint.
+    pub fn footprint(&self) -> Vec<(i32, i32)> {
+        let radius = match self.shape {
+            BrushShape::Cell => 0,
+            BrushShape::Square => 1,
+            BrushShape::Circle | BrushShape::Spray => self.radius as i32,
+        };
+        let round = matches!(self.shape, BrushShape::Circle | BrushShape::Spray);
+
+        square(radius, round)
+    }
+
+    /// Random cells within a circle of `radius`, each picked with
+    /// `density`, regardless of the brush shape. Used to keep a board busy
+    /// by injecting noise around the cursor.
+    pub fn burst(&mut self, radius: usize, density: f64) -> Vec<(i32, i32)> {
+        square(radius as i32, true)
+            .into_iter()
+            .filter(|_| self.rng.next_f64() < density)
+            .collect()
+    }
+
+    /// Starts a stroke at `cell`, returning the cells to paint.
+    pub fn press(&mut self, cell: (i32, i32)) -> Vec<(i32, i32)> {
+        self.anchor = Some(cell);
+        self.dab()
+    }
+
+    /// Continues the stroke to `cell`, returning the cells to paint unless
+    /// the brush is still over the cell it last painted.
+    pub fn drag(&mut self, cell: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        if self.anchor == Some(cell) {
+            return None;
+        }
+
+        Some(self.press(cell))
+    }
+
+    fn dab(&mut self) -> Vec<(i32, i32)> {
+        let footprint = self.footprint();
+
+        match self.shape {
+            BrushShape::Spray => footprint
+                .into_iter()
+                .filter(|_| self.rng.next_f64() < self.density)
+                .collect(),
+            _ => footprint,
+        }
+    }
+}
+
+/// The cells of a square reaching `radius` from the center, or of the
+/// circle inside it if `round`.
+fn square(radius: i32, round: bool) -> Vec<(i32, i32)> {
+    let mut cells = Vec::new();
+
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if !round || dx * dx + dy * dy <= radius * radius {
+                cells.push((dx, dy));
+            }
+        }
+    }
+
+    cells
+}
+
+/// The cells on a straight line from `from` to `to`, relative to `from`,
+/// using Bresenham's algorithm.
+pub fn line(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+    let (step_x, step_y) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let (mut x, mut y) = (0, 0);
+    let mut error = dx + dy;
+    let mut cells = vec![(0, 0)];
+
+    while (x, y) != (to.0 - from.0, to.1 - from.1) {
+        let doubled = 2 * error;
+
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+
+        cells.push((x, y));
+    }
+
+    cells
+}
+
+/// The cells of the rectangle with corners `from` and `to`, relative to
+/// `from`: all of them if `filled`, otherwise just the border.
+pub fn rectangle(from: (i32, i32), to: (i32, i32), filled: bool) -> Vec<(i32, i32)> {
+    let (left, right) = (0.min(to.0 - from.0), 0.max(to.0 - from.0));
+    let (top, bottom) = (0.min(to.1 - from.1), 0.max(to.1 - from.1));
+    let mut cells = Vec::new();
+
+    for y in top..=bottom {
+        for x in left..=right {
+            if filled || x == left || x == right || y == top || y == bottom {
+                cells.push((x, y));
+            }
+        }
+    }
+
+    cells
+}
@@ -0,0 +1,188 @@
+use crate::pattern::Pattern;
+use crate::rle;
+use std::fs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Guns,
+    Spaceships,
+    Oscillators,
+    StillLifes,
+    Methuselahs,
+    /// Patterns loaded from the user's pattern directory.
+    User,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatalogEntry {
+    /// Short identifier used to refer to the pattern, e.g. in replays.
+    pub key: String,
+    pub category: Category,
+    pub pattern: Pattern,
+}
+
+/// A searchable collection of patterns.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Catalog {
+    pub entries: Vec<CatalogEntry>,
+}
+
+/// `(key, category, name, RLE body)` for every pattern built into the
+/// program.
+const EMBEDDED: &[(&str, Category, &str, &str)] = &[
+    (
+        "glider_gun",
+        Category::Guns,
+        "Gosper glider gun",
+        "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    ("glider", Category::Spaceships, "Glider", "bo$2bo$3o!"),
+    (
+        "lwss",
+        Category::Spaceships,
+        "Lightweight spaceship",
+        "bo2bo$o4b$o3bo$4o!",
+    ),
+    (
+        "mwss",
+        Category::Spaceships,
+        "Middleweight spaceship",
+        "3bo2b$bo3bo$o5b$o4bo$5o!",
+    ),
+    (
+        "hwss",
+        Category::Spaceships,
+        "Heavyweight spaceship",
+        "3b2o2b$bo4bo$o6b$o5bo$6o!",
+    ),
+    ("blinker", Category::Oscillators, "Blinker", "3o!"),
+    ("toad", Category::Oscillators, "Toad", "b3o$3o!"),
+    ("beacon", Category::Oscillators, "Beacon", "2o$2o$2b2o$2b2o!"),
+    (
+        "pulsar",
+        Category::Oscillators,
+        "Pulsar",
+        "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    (
+        "pentadecathlon",
+        Category::Oscillators,
+        "Pentadecathlon",
+        "2bo4bo$2ob4ob2o$2bo4bo!",
+    ),
+    ("block", Category::StillLifes, "Block", "2o$2o!"),
+    ("beehive", Category::StillLifes, "Beehive", "b2o$o2bo$b2o!"),
+    ("loaf", Category::StillLifes, "Loaf", "b2o$o2bo$bobo$2bo!"),
+    ("r_pentomino", Category::Methuselahs, "R-pentomino", "b2o$2o$bo!"),
+    ("acorn", Category::Methuselahs, "Acorn", "bo$3bo$2o2b3o!"),
+    ("diehard", Category::Methuselahs, "Diehard", "6bo$2o$bo3b3o!"),
+];
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Guns => "Guns",
+            Category::Spaceships => "Spaceships",
+            Category::Oscillators => "Oscillators",
+            Category::StillLifes => "Still lifes",
+            Category::Methuselahs => "Methuselahs",
+            Category::User => "User patterns",
+        }
+    }
+}
+
+impl Catalog {
+    /// The patterns built into the program.
+    pub fn embedded() -> Self {
+        let entries = EMBEDDED
+            .iter()
+            .map(|(key, category, name, body)| CatalogEntry {
+                key: key.to_string(),
+                category: *category,
+                pattern: Pattern::new(
+                    name,
+                    rle::decode_body(body).expect("embedded pattern is valid RLE"),
+                ),
+            })
+            .collect();
+
+        Catalog { entries }
+    }
+
+    /// Adds every `.rle` file in the directory as a user pattern, keyed by
+    /// its file name.
+    pub fn load_directory(&mut self, directory: &str) -> Result<(), String> {
+        crate::info!("loading patterns from {}", directory);
+
+        let read_error = |error: std::io::Error| {
+            format!("failed to read pattern directory {}: {}", directory, error)
+        };
+        let mut paths: Vec<_> = fs::read_dir(directory)
+            .map_err(read_error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()
+            .map_err(read_error)?;
+
+        paths.sort();
+
+        for path in paths {
+            let is_rle = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("rle"));
+
+            if !is_rle {
+                continue;
+            }
+
+            let path = path.to_string_lossy();
+            let pattern = Pattern::load(&path)?;
+            let key = path
+                .rsplit('/')
+                .next()
+                .unwrap_or(&path)
+                .trim_end_matches(".rle")
+                .to_string();
+
+            self.entries.push(CatalogEntry {
+                key,
+                category: Category::User,
+                pattern,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn find(&self, key: &str) -> Option<&CatalogEntry> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    /// Entries whose key, name, author, comments or category contain the
+    /// query (ignoring case), grouped by category and otherwise in catalog
+    /// order.
+    pub fn search(&self, query: &str) -> Vec<&CatalogEntry> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<&CatalogEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                let pattern = &entry.pattern;
+
+                query.is_empty()
+                    || entry.key.to_lowercase().contains(&query)
+                    || pattern.name.to_lowercase().contains(&query)
+                    || entry.category.name().to_lowercase().contains(&query)
+                    || pattern
+                        .author
+                        .iter()
+                        .chain(pattern.comments.iter())
+                        .any(|text| text.to_lowercase().contains(&query))
+            })
+            .collect();
+
+        matches.sort_by_key(|entry| entry.category);
+
+        matches
+    }
+}
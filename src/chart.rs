@@ -0,0 +1,184 @@
+//! Charts of a run's population over time, rendered to images so they can
+//! be shared without external plotting tools.
+
+use crate::board::Board;
+use crate::brush::line;
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::image::RgbaImage;
+use crate::theme::Theme;
+
+/// Size of a chart in pixels unless asked otherwise.
+pub const DEFAULT_CHART_SIZE: (usize, usize) = (800, 480);
+
+/// Pixels per font dot in labels.
+const TEXT_SIZE: i32 = 2;
+/// Room left around the plot for the title and labels, in pixels. The left
+/// margin grows to fit the population labels.
+const MARGIN_TOP: i32 = 40;
+const MARGIN_RIGHT: i32 = 30;
+const MARGIN_BOTTOM: i32 = 60;
+/// Gap between a label and the axis it labels.
+const LABEL_GAP: i32 = 8;
+/// Gridlines aimed for along each axis; the exact number depends on where
+/// round numbers fall.
+const TICKS: usize = 6;
+
+/// The population of every generation of a run, for charting.
+#[derive(Clone, Debug, Default)]
+pub struct PopulationHistory {
+    samples: Vec<(usize, usize)>,
+}
+
+impl PopulationHistory {
+    pub fn new() -> Self {
+        PopulationHistory::default()
+    }
+
+    /// Adds the board's generation. A generation at or before the last one
+    /// recorded means a different board is being run, so the history starts
+    /// over from it.
+    pub fn record(&mut self, board: &Board) {
+        if self
+            .samples
+            .last()
+            .is_some_and(|(generation, _)| *generation >= board.generation)
+        {
+            self.samples.clear();
+        }
+
+        self.samples.push((board.generation, board.population()));
+    }
+
+    /// `(generation, population)` pairs, oldest first.
+    pub fn samples(&self) -> &[(usize, usize)] {
+        &self.samples
+    }
+
+    /// Bytes used by the samples.
+    pub fn memory_usage(&self) -> usize {
+        self.samples.capacity() * std::mem::size_of::<(usize, usize)>()
+    }
+}
+
+/// Draws the population of every recorded generation, given as
+/// `(generation, population)` pairs in order, as a line chart with labeled
+/// axes and gridlines in the theme's colors. Consecutive samples are joined,
+/// so every rise and fall shows up however many generations share a pixel
+/// column.
+pub fn population_chart(
+    history: &[(usize, usize)],
+    theme: &Theme,
+    (width, height): (usize, usize),
+) -> RgbaImage {
+    let mut image = RgbaImage::new(width, height, theme.background);
+    let grid = theme.background.lerp(theme.alive, 0.15);
+    let axis = theme.background.lerp(theme.alive, 0.7);
+    let first = history.first().map_or(0, |(generation, _)| *generation);
+    let last = history.last().map_or(0, |(generation, _)| *generation);
+    let peak = history.iter().map(|(_, population)| *population).max();
+    let population_step = nice_step(peak.unwrap_or(0).max(1), TICKS);
+    let population_top = peak.unwrap_or(0).div_ceil(population_step).max(1) * population_step;
+    let generation_step = nice_step((last - first).max(1), TICKS);
+    let label_width = text_width(&population_top.to_string(), TEXT_SIZE);
+    let (left, top) = (label_width + 2 * LABEL_GAP, MARGIN_TOP);
+    let (right, bottom) = (width as i32 - MARGIN_RIGHT, height as i32 - MARGIN_BOTTOM);
+    let (plot_width, plot_height) = ((right - left).max(1), (bottom - top).max(1));
+    let to_x = |generation: usize| {
+        left + (generation.saturating_sub(first) as f64 / (last - first).max(1) as f64
+            * plot_width as f64) as i32
+    };
+    let to_y = |population: usize| {
+        bottom - (population as f64 / population_top as f64 * plot_height as f64) as i32
+    };
+
+    for population in (0..=population_top).step_by(population_step) {
+        let (y, label) = (to_y(population), population.to_string());
+
+        image.fill_rect(left as i64, y as i64, plot_width as usize, 1, grid);
+        draw_text(
+            &mut image,
+            (
+                left - LABEL_GAP - text_width(&label, TEXT_SIZE),
+                y - GLYPH_HEIGHT * TEXT_SIZE / 2,
+            ),
+            TEXT_SIZE,
+            &label,
+            axis,
+        );
+    }
+
+    let first_tick = first.div_ceil(generation_step) * generation_step;
+
+    for generation in (first_tick..=last.max(first_tick)).step_by(generation_step) {
+        let (x, label) = (to_x(generation), generation.to_string());
+
+        if x > right {
+            break;
+        }
+
+        image.fill_rect(x as i64, top as i64, 1, plot_height as usize, grid);
+        draw_text(
+            &mut image,
+            (x - text_width(&label, TEXT_SIZE) / 2, bottom + LABEL_GAP),
+            TEXT_SIZE,
+            &label,
+            axis,
+        );
+    }
+
+    image.fill_rect(left as i64, top as i64, 1, plot_height as usize + 1, axis);
+    image.fill_rect(left as i64, bottom as i64, plot_width as usize, 1, axis);
+    draw_text(
+        &mut image,
+        (left, top - LABEL_GAP - GLYPH_HEIGHT * TEXT_SIZE),
+        TEXT_SIZE,
+        "population",
+        axis,
+    );
+    draw_text(
+        &mut image,
+        (
+            left + (plot_width - text_width("generation", TEXT_SIZE)) / 2,
+            height as i32 - LABEL_GAP - GLYPH_HEIGHT * TEXT_SIZE,
+        ),
+        TEXT_SIZE,
+        "generation",
+        axis,
+    );
+
+    let points: Vec<(i32, i32)> = history
+        .iter()
+        .map(|(generation, population)| (to_x(*generation), to_y(*population)))
+        .collect();
+
+    for (index, from) in points.iter().enumerate() {
+        let to = points.get(index + 1).unwrap_or(from);
+
+        for (dx, dy) in line(*from, *to) {
+            image.fill_rect(
+                (from.0 + dx) as i64,
+                (from.1 + dy) as i64,
+                1,
+                1,
+                theme.alive,
+            );
+        }
+    }
+
+    image
+}
+
+/// A round step (1, 2 or 5 times a power of ten) that divides `range` into
+/// about `ticks` parts.
+fn nice_step(range: usize, ticks: usize) -> usize {
+    let raw = (range as f64 / ticks as f64).max(1.0);
+    let magnitude = 10f64.powi(raw.log10().floor() as i32);
+    let multiple = match raw / magnitude {
+        residual if residual <= 1.5 => 1.0,
+        residual if residual <= 3.0 => 2.0,
+        residual if residual <= 7.0 => 5.0,
+        _ => 10.0,
+    };
+
+    (multiple * magnitude) as usize
+}
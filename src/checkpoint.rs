@@ -0,0 +1,136 @@
+use crate::board::Board;
+use crate::config::Smoothing;
+use crate::session::Session;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Generations between checkpoints unless told otherwise.
+pub const DEFAULT_CHECKPOINT_EVERY: usize = 100_000;
+
+/// Name of the file listing a run's checkpoints.
+const INDEX: &str = "index";
+
+/// Periodic snapshots of a long headless run, so an interrupted run can pick
+/// up where it stopped and intermediate states can be looked at later.
+///
+/// A run directory holds one `generation-<n>.session` file per checkpoint,
+/// in the session format (live cells as RLE plus trails and poison), and an
+/// `index` of `<generation> <state hash> <file>` lines in the order they were
+/// written. The hash is checked when resuming, so a damaged snapshot is
+/// reported instead of silently continuing from the wrong state. There is no
+/// HashLife engine, so snapshots hold the whole board rather than a
+/// macrocell tree.
+pub struct Checkpoints {
+    directory: PathBuf,
+    every: usize,
+}
+
+impl Checkpoints {
+    /// Creates the run directory if needed.
+    pub fn create(directory: &str, every: usize) -> Result<Self, String> {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("failed to create {}: {}", directory, error))?;
+
+        Ok(Checkpoints {
+            directory: PathBuf::from(directory),
+            every,
+        })
+    }
+
+    /// Writes a checkpoint if the board is on a multiple of `every`.
+    pub fn save_if_due(&self, board: &Board) -> Result<(), String> {
+        if board.generation == 0 || !board.generation.is_multiple_of(self.every) {
+            return Ok(());
+        }
+
+        self.save(board)
+    }
+
+    /// Writes a last checkpoint at the end of a run, unless `save_if_due`
+    /// just wrote one.
+    pub fn finish(&self, board: &Board) -> Result<(), String> {
+        if board.generation.is_multiple_of(self.every) {
+            return Ok(());
+        }
+
+        self.save(board)
+    }
+
+    /// Writes a checkpoint of the board and adds it to the index.
+    pub fn save(&self, board: &Board) -> Result<(), String> {
+        let file = format!("generation-{}.session", board.generation);
+        let path = self.directory.join(&file);
+        let index = self.directory.join(INDEX);
+        let failed = |error: std::io::Error| format!("failed to write checkpoint: {}", error);
+
+        crate::debug!("writing checkpoint {}", path.display());
+
+        fs::write(&path, snapshot_session().to_text(board)).map_err(failed)?;
+
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(index)
+            .map_err(failed)?;
+
+        writeln!(
+            index,
+            "{} {:016x} {}",
+            board.generation,
+            board.state_hash(),
+            file
+        )
+        .map_err(failed)
+    }
+
+    /// The board from the last checkpoint in `directory`, after checking its
+    /// state hash against the index. Its rule, topology and trail length
+    /// come from the snapshot; other board settings are left at defaults.
+    pub fn load_latest(directory: &str) -> Result<Board, String> {
+        let directory = PathBuf::from(directory);
+        let index_path = directory.join(INDEX);
+        let index = fs::read_to_string(&index_path).map_err(|error| {
+            format!(
+                "failed to read checkpoint index {}: {}",
+                index_path.display(),
+                error
+            )
+        })?;
+        let line = index
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+            .ok_or_else(|| format!("no checkpoints in {}", directory.display()))?;
+        let invalid = || format!("invalid checkpoint index line: {}", line);
+        let (hash, file) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [_, hash, file] => (
+                u64::from_str_radix(hash, 16).map_err(|_| invalid())?,
+                file.to_string(),
+            ),
+            _ => return Err(invalid()),
+        };
+        let path = directory.join(&file);
+        let text = fs::read_to_string(&path)
+            .map_err(|error| format!("failed to read checkpoint {}: {}", path.display(), error))?;
+        let (board, _) =
+            Session::parse(&text).map_err(|error| format!("checkpoint {}: {}", file, error))?;
+
+        if board.state_hash() != hash {
+            return Err(format!(
+                "checkpoint {} doesn't match the hash in its index",
+                file
+            ));
+        }
+
+        Ok(board)
+    }
+}
+
+/// The session settings written with every snapshot; runs have no camera.
+fn snapshot_session() -> Session {
+    Session {
+        camera: (0, 0, 1.0),
+        fps: 1,
+        smoothing: Smoothing::Off,
+    }
+}
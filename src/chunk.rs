@@ -0,0 +1,98 @@
+//! A coarse spatial index of the board's occupied cells, so work that only
+//! cares about cells that aren't dead, like drawing, skips the empty space
+//! of huge sparse boards instead of visiting every cell in view.
+
+use crate::board::{Board, Cell, CellState};
+use std::ops::Range;
+
+/// Side length of a chunk, in cells.
+pub const CHUNK_SIZE: usize = 32;
+
+/// How many occupied cells, ones that are alive, dying, poison or frozen,
+/// are in each `CHUNK_SIZE` square of a board.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkIndex {
+    width: usize,
+    height: usize,
+    generation: usize,
+    /// Chunks across the board.
+    columns: usize,
+    counts: Vec<u32>,
+}
+
+impl ChunkIndex {
+    pub fn new(board: &Board) -> Self {
+        let columns = board.width.div_ceil(CHUNK_SIZE);
+        let rows = board.height.div_ceil(CHUNK_SIZE);
+        let mut counts = vec![0; columns * rows];
+
+        for (index, cell) in board.cells.iter().enumerate() {
+            if is_occupied(cell) {
+                let (x, y) = (index % board.width, index / board.width);
+
+                counts[(y / CHUNK_SIZE) * columns + x / CHUNK_SIZE] += 1;
+            }
+        }
+
+        ChunkIndex {
+            width: board.width,
+            height: board.height,
+            generation: board.generation,
+            columns,
+            counts,
+        }
+    }
+
+    /// Whether the index was built from the board's generation at its size.
+    /// Edits that keep both, like painting while paused, aren't noticed, so
+    /// callers rebuild after those.
+    pub fn is_current(&self, board: &Board) -> bool {
+        (self.width, self.height, self.generation) == (board.width, board.height, board.generation)
+    }
+
+    /// Indices of the occupied cells in the given columns and rows, chunk by
+    /// chunk, visiting only chunks that have any.
+    pub fn occupied_in(
+        &self,
+        board: &Board,
+        (columns, rows): (Range<usize>, Range<usize>),
+    ) -> Vec<usize> {
+        let (columns, rows) = (
+            columns.start..columns.end.min(self.width),
+            rows.start..rows.end.min(self.height),
+        );
+        let mut occupied = Vec::new();
+
+        for chunk_y in rows.start / CHUNK_SIZE..rows.end.div_ceil(CHUNK_SIZE) {
+            for chunk_x in columns.start / CHUNK_SIZE..columns.end.div_ceil(CHUNK_SIZE) {
+                if self.counts[chunk_y * self.columns + chunk_x] == 0 {
+                    continue;
+                }
+
+                let ys = (chunk_y * CHUNK_SIZE).max(rows.start)
+                    ..((chunk_y + 1) * CHUNK_SIZE).min(rows.end);
+                let xs = (chunk_x * CHUNK_SIZE).max(columns.start)
+                    ..((chunk_x + 1) * CHUNK_SIZE).min(columns.end);
+
+                for y in ys {
+                    occupied.extend(
+                        xs.clone()
+                            .map(|x| y * self.width + x)
+                            .filter(|index| is_occupied(&board.cells[*index])),
+                    );
+                }
+            }
+        }
+
+        occupied
+    }
+
+    /// Bytes used by the chunk counts.
+    pub fn memory_usage(&self) -> usize {
+        self.counts.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
+fn is_occupied(cell: &Cell) -> bool {
+    cell.frozen || !matches!(cell.state, CellState::Dead)
+}
@@ -0,0 +1,528 @@
+use game_of_life::checkpoint::DEFAULT_CHECKPOINT_EVERY;
+use game_of_life::log::Level;
+use game_of_life::png;
+use game_of_life::session::DEFAULT_SESSION_PATH;
+use game_of_life::workspace::DEFAULT_WORKSPACE_DIR;
+use game_of_life::{Config, Engine, Pattern, Placement, Region, Rule, Rule3d, Script, Topology};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+const WIDTH: usize = 800;
+const HEIGHT: usize = 800;
+const DEFAULT_HEADLESS_GENERATIONS: usize = 1000;
+const LATTICE_SIZE: usize = 64;
+const DEFAULT_MIN_RATE: f64 = 10.0;
+const DEFAULT_ITERATIONS: usize = 20;
+/// Rules `rule-search` samples unless `--samples` is given.
+const DEFAULT_SAMPLES: usize = 100;
+/// Generations each collision runs for unless `--generations` is given.
+const DEFAULT_COLLIDE_GENERATIONS: usize = 160;
+const DEFAULT_TIMELAPSE_DIR: &str = "timelapse";
+/// Pixels per cell in `--render-out` images unless `--cell-size` is given.
+const DEFAULT_CELL_SIZE: usize = 4;
+/// Generations between the state hashes `--verify` compares unless
+/// `--verify-every` is given.
+const DEFAULT_VERIFY_EVERY: usize = 100;
+/// Luminance below which `--from-image` pixels become live cells.
+const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// What the program was asked to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Run the simulation, in a window or headlessly.
+    Run,
+    /// Search for a predecessor of a region of the starting board.
+    Predecessor,
+    /// Ramp board size and soup density until the simulation slows down.
+    Stress,
+    /// Search for interesting rules with a genetic algorithm.
+    Evolve,
+    /// Collide two patterns at a range of offsets and phases.
+    Collide,
+    /// Run two engines side by side until their generations differ.
+    Diverge,
+    /// Rank B/S rules by how interesting they are on standard soups.
+    RuleSearch,
+}
+
+/// Which window frontend shows the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frontend {
+    /// The full frontend, drawn with SDL2, behind the `gui` feature.
+    Sdl,
+    /// A basic one speaking X11 directly, behind the `x11` feature.
+    X11,
+}
+
+impl Frontend {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "sdl" => Ok(Frontend::Sdl),
+            "x11" => Ok(Frontend::X11),
+            _ => Err(format!("unknown frontend: {}", text)),
+        }
+    }
+}
+
+pub struct Options {
+    pub command: Command,
+    pub headless: bool,
+    pub frontend: Frontend,
+    pub generations: usize,
+    pub board_width: usize,
+    pub board_height: usize,
+    pub window_width: usize,
+    pub window_height: usize,
+    pub seed: Option<u64>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub config: Config,
+    pub topology: Topology,
+    pub engine: Engine,
+    /// Engines `diverge` compares.
+    pub engines: (Engine, Engine),
+    /// Run the board twice and check both runs reach the same states.
+    pub verify: bool,
+    /// Engine of the second `--verify` run, `engine` if not given.
+    pub verify_engine: Option<Engine>,
+    /// Generations between the state hashes `--verify` compares.
+    pub verify_every: usize,
+    /// File to write each generation's state hash to.
+    pub hash_log: Option<String>,
+    /// Shell command each generation is streamed to.
+    pub pipe_command: Option<String>,
+    /// Generations between full boards sent down the pipe.
+    pub pipe_full_every: Option<usize>,
+    pub dimensions: usize,
+    pub lattice: (usize, usize, usize),
+    /// Overrides the rule named by the `--pattern` file, if any.
+    pub rule: Option<Rule>,
+    pub rule3d: Rule3d,
+    pub region: Option<Region>,
+    pub save_rle: Option<String>,
+    pub save_svg: Option<String>,
+    pub save_pbm: Option<String>,
+    pub save_pgm: Option<String>,
+    /// Whether SVG exports include the dying trail.
+    pub svg_trail: bool,
+    /// PNG file the final board is rendered to.
+    pub render_out: Option<String>,
+    /// Also render every this many generations, next to `render_out`.
+    pub render_every: Option<usize>,
+    /// Pixels per cell in `render_out` images.
+    pub cell_size: usize,
+    /// Render every this many generations to a PNG in `timelapse_dir`.
+    pub timelapse_every: Option<usize>,
+    pub timelapse_dir: String,
+    /// Size of the time-lapse frames in pixels.
+    pub timelapse_size: (usize, usize),
+    /// CSV file to write per-generation statistics to.
+    pub stats: Option<String>,
+    /// PNG file to chart the population history in at the end of the run.
+    pub population_chart: Option<String>,
+    /// CSV file a summary of each run is appended to.
+    pub runs_log: Option<String>,
+    /// The first `--pattern`, whose rule and provenance the run takes on.
+    pub pattern: Option<Pattern>,
+    /// Every `--pattern`, in order, for the starting board.
+    pub placements: Vec<Placement>,
+    pub script: Option<Script>,
+    pub log_level: Level,
+    pub log_file: Option<String>,
+    pub resume: bool,
+    /// Loop through the demo tour instead of running one board.
+    pub demo: bool,
+    pub session: String,
+    /// Workspace to start from.
+    pub workspace: Option<String>,
+    /// Directory workspaces are saved in and listed from.
+    pub workspace_dir: String,
+    /// Run directory headless checkpoints are written to and resumed from.
+    pub checkpoint_dir: Option<String>,
+    /// Generations between checkpoints.
+    pub checkpoint_every: usize,
+    /// Whether to continue from the last checkpoint instead of starting over.
+    pub resume_run: bool,
+    /// Generations per second below which `stress` stops ramping.
+    pub min_rate: f64,
+    /// Generations of rules `evolve` breeds.
+    pub iterations: usize,
+    /// Rules `rule-search` samples, or every rule if `None`.
+    pub samples: Option<usize>,
+    /// Pattern `collide` fires at `--pattern`.
+    pub with: Option<Pattern>,
+    /// Horizontal and vertical offsets of `with` that `collide` tries.
+    pub offsets: Option<(RangeInclusive<i32>, RangeInclusive<i32>)>,
+    /// Generations `with` is run before it is placed.
+    pub phases: RangeInclusive<usize>,
+}
+
+impl Options {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            command: Command::Run,
+            headless: !cfg!(any(feature = "gui", feature = "x11")),
+            frontend: if cfg!(feature = "gui") {
+                Frontend::Sdl
+            } else {
+                Frontend::X11
+            },
+            generations: DEFAULT_HEADLESS_GENERATIONS,
+            board_width: WIDTH,
+            board_height: HEIGHT,
+            window_width: WIDTH,
+            window_height: HEIGHT,
+            seed: None,
+            record: None,
+            replay: None,
+            config: Config::default(),
+            topology: Topology::default(),
+            engine: Engine::default(),
+            engines: (Engine::Naive, Engine::Incremental),
+            verify: false,
+            verify_engine: None,
+            verify_every: DEFAULT_VERIFY_EVERY,
+            hash_log: None,
+            pipe_command: None,
+            pipe_full_every: None,
+            dimensions: 2,
+            lattice: (LATTICE_SIZE, LATTICE_SIZE, LATTICE_SIZE),
+            rule: None,
+            rule3d: Rule3d::LIFE_4555,
+            region: None,
+            save_rle: None,
+            save_svg: None,
+            save_pbm: None,
+            save_pgm: None,
+            svg_trail: false,
+            render_out: None,
+            render_every: None,
+            cell_size: DEFAULT_CELL_SIZE,
+            timelapse_every: None,
+            timelapse_dir: DEFAULT_TIMELAPSE_DIR.to_string(),
+            timelapse_size: (WIDTH, HEIGHT),
+            stats: None,
+            population_chart: None,
+            runs_log: None,
+            pattern: None,
+            placements: Vec::new(),
+            script: None,
+            log_level: Level::default(),
+            log_file: None,
+            resume: false,
+            demo: false,
+            session: DEFAULT_SESSION_PATH.to_string(),
+            workspace: None,
+            workspace_dir: DEFAULT_WORKSPACE_DIR.to_string(),
+            checkpoint_dir: None,
+            checkpoint_every: DEFAULT_CHECKPOINT_EVERY,
+            resume_run: false,
+            min_rate: DEFAULT_MIN_RATE,
+            iterations: DEFAULT_ITERATIONS,
+            samples: Some(DEFAULT_SAMPLES),
+            with: None,
+            offsets: None,
+            phases: 0..=0,
+        };
+        let mut args = args.skip(1).peekable();
+        let mut image = None;
+        let mut threshold = DEFAULT_THRESHOLD;
+        let mut dither = false;
+        let mut generations = None;
+
+        if let Some(command) = args.next_if(|arg| !arg.starts_with("--")) {
+            options.command = match command.as_str() {
+                "run" => Command::Run,
+                "predecessor" => Command::Predecessor,
+                "stress" => Command::Stress,
+                "evolve" => Command::Evolve,
+                "collide" => Command::Collide,
+                "diverge" => Command::Diverge,
+                "rule-search" => Command::RuleSearch,
+                _ => return Err(format!("unknown command: {}", command)),
+            };
+        }
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} requires a value", arg));
+
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--frontend" => options.frontend = Frontend::parse(&value()?)?,
+                "--generations" => generations = Some(parse_number(&arg, &value()?)?),
+                "--seed" => options.seed = Some(parse_number(&arg, &value()?)?),
+                "--record" => options.record = Some(value()?),
+                "--replay" => options.replay = Some(value()?),
+                "--board" => {
+                    let (width, height) = parse_size(&arg, &value()?)?;
+                    options.board_width = width;
+                    options.board_height = height;
+                }
+                "--window" => {
+                    let (width, height) = parse_size(&arg, &value()?)?;
+                    options.window_width = width;
+                    options.window_height = height;
+                }
+                "--topology" => options.topology = Topology::parse(&value()?)?,
+                "--engine" => options.engine = Engine::parse(&value()?)?,
+                "--engines" => {
+                    let value = value()?;
+                    let (first, second) = value.split_once(',').ok_or_else(|| {
+                        format!("expected <engine>,<engine> for {}: {}", arg, value)
+                    })?;
+
+                    options.engines = (Engine::parse(first)?, Engine::parse(second)?);
+                }
+                "--verify" => options.verify = true,
+                "--verify-engine" => options.verify_engine = Some(Engine::parse(&value()?)?),
+                "--verify-every" => {
+                    let every = parse_number(&arg, &value()?)?;
+
+                    if every == 0 {
+                        return Err("--verify-every must be at least 1".to_string());
+                    }
+
+                    options.verify_every = every;
+                }
+                "--hash-log" => options.hash_log = Some(value()?),
+                "--pipe-command" => options.pipe_command = Some(value()?),
+                "--pipe-full-every" => {
+                    let every: usize = parse_number(&arg, &value()?)?;
+
+                    if every == 0 {
+                        return Err("--pipe-full-every must be at least 1".to_string());
+                    }
+
+                    options.pipe_full_every = Some(every);
+                }
+                "--dimensions" => {
+                    options.dimensions = match value()?.as_str() {
+                        "2" => 2,
+                        "3" => 3,
+                        other => return Err(format!("--dimensions must be 2 or 3: {}", other)),
+                    }
+                }
+                "--lattice" => options.lattice = parse_lattice(&arg, &value()?)?,
+                "--rule" => options.rule = Some(Rule::parse(&value()?)?),
+                "--rule3d" => options.rule3d = Rule3d::parse(&value()?)?,
+                "--region" => options.region = Some(Region::parse(&value()?)?),
+                "--save-rle" => options.save_rle = Some(value()?),
+                "--save-svg" => options.save_svg = Some(value()?),
+                "--svg-trail" => options.svg_trail = true,
+                "--save-pbm" => options.save_pbm = Some(value()?),
+                "--save-pgm" => options.save_pgm = Some(value()?),
+                "--render-out" => options.render_out = Some(value()?),
+                "--render-every" => {
+                    let every: usize = parse_number(&arg, &value()?)?;
+
+                    if every == 0 {
+                        return Err("--render-every must be at least 1".to_string());
+                    }
+
+                    options.render_every = Some(every);
+                }
+                "--cell-size" => {
+                    let cell_size: usize = parse_number(&arg, &value()?)?;
+
+                    if cell_size == 0 {
+                        return Err("--cell-size must be at least 1".to_string());
+                    }
+
+                    options.cell_size = cell_size;
+                }
+                "--timelapse-every" => {
+                    let every: usize = parse_number(&arg, &value()?)?;
+
+                    if every == 0 {
+                        return Err("--timelapse-every must be at least 1".to_string());
+                    }
+
+                    options.timelapse_every = Some(every);
+                }
+                "--timelapse-dir" => options.timelapse_dir = value()?,
+                "--timelapse-size" => options.timelapse_size = parse_size(&arg, &value()?)?,
+                "--stats" => options.stats = Some(value()?),
+                "--population-chart" => options.population_chart = Some(value()?),
+                "--runs-log" => options.runs_log = Some(value()?),
+                "--pattern" => options.placements.push(Placement::parse(&value()?)?),
+                "--from-image" => image = Some(value()?),
+                "--threshold" => {
+                    threshold = parse_number(&arg, &value()?)?;
+
+                    if !(0.0..=1.0).contains(&threshold) {
+                        return Err(format!("--threshold must be in 0..1: {}", threshold));
+                    }
+                }
+                "--dither" => dither = true,
+                "--script-actions" => options.script = Some(Script::load(&value()?)?),
+                "--config" => options.config = Config::load(&value()?)?,
+                "--log-level" => options.log_level = Level::parse(&value()?)?,
+                "--log-file" => options.log_file = Some(value()?),
+                "--resume" => options.resume = true,
+                "--demo" => options.demo = true,
+                "--session" => options.session = value()?,
+                "--workspace" => options.workspace = Some(value()?),
+                "--workspace-dir" => options.workspace_dir = value()?,
+                "--checkpoint-every" => {
+                    let every = parse_number(&arg, &value()?)?;
+
+                    if every == 0 {
+                        return Err("--checkpoint-every must be at least 1".to_string());
+                    }
+
+                    options.checkpoint_every = every;
+                }
+                "--checkpoint-dir" => options.checkpoint_dir = Some(value()?),
+                "--resume-run" => options.resume_run = true,
+                "--min-rate" => {
+                    options.min_rate = parse_number(&arg, &value()?)?;
+
+                    // `stress` grows the board until it runs slower than
+                    // this, which it never would for zero.
+                    if options.min_rate <= 0.0 || options.min_rate.is_nan() {
+                        return Err(format!("--min-rate must be positive: {}", options.min_rate));
+                    }
+                }
+                "--iterations" => options.iterations = parse_number(&arg, &value()?)?,
+                "--samples" => {
+                    options.samples = match value()?.as_str() {
+                        "all" => None,
+                        samples => Some(parse_number(&arg, samples)?),
+                    }
+                }
+                "--with" => options.with = Some(Pattern::load(&value()?)?),
+                "--offset" => {
+                    let value = value()?;
+                    let (x, y) = value.split_once(',').ok_or_else(|| {
+                        format!("expected <x range>,<y range> for {}: {}", arg, value)
+                    })?;
+
+                    options.offsets = Some((parse_range(&arg, x)?, parse_range(&arg, y)?));
+                }
+                "--phase" => options.phases = parse_range(&arg, &value()?)?,
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        options.generations = match (generations, &options.command) {
+            (Some(generations), _) => generations,
+            (None, Command::Collide) => DEFAULT_COLLIDE_GENERATIONS,
+            (None, _) => DEFAULT_HEADLESS_GENERATIONS,
+        };
+
+        if let Some(path) = image {
+            let name = Path::new(&path)
+                .file_stem()
+                .map_or(path.clone(), |stem| stem.to_string_lossy().to_string());
+
+            // The picture joins any `--pattern`s as one more placement.
+            options.placements.push(Placement {
+                pattern: Pattern::new(&name, png::load(&path)?.dark_pixels(threshold, dither)),
+                position: None,
+            });
+        }
+
+        options.pattern = options
+            .placements
+            .first()
+            .map(|placement| placement.pattern.clone());
+
+        if options.resume && (options.record.is_some() || options.replay.is_some()) {
+            return Err("--resume cannot be combined with --record or --replay".to_string());
+        }
+
+        if options.verify && options.dimensions == 3 {
+            return Err("--verify only checks 2D boards".to_string());
+        }
+
+        if options.render_every.is_some() && options.render_out.is_none() {
+            return Err("--render-every needs a --render-out file".to_string());
+        }
+
+        if options.demo && options.headless {
+            return Err("--demo needs the window; it can't run with --headless".to_string());
+        }
+
+        if options.workspace.is_some()
+            && (options.resume || options.record.is_some() || options.replay.is_some())
+        {
+            return Err(
+                "--workspace cannot be combined with --resume, --record or --replay".to_string(),
+            );
+        }
+
+        if (options.resume_run || options.checkpoint_dir.is_some()) && !options.headless {
+            return Err("checkpoints are only written and resumed with --headless".to_string());
+        }
+
+        if options.resume_run && options.checkpoint_dir.is_none() {
+            return Err("--resume-run needs the --checkpoint-dir to resume from".to_string());
+        }
+
+        if options.resume_run
+            && (options.resume || options.record.is_some() || options.replay.is_some())
+        {
+            return Err(
+                "--resume-run cannot be combined with --resume, --record or --replay".to_string(),
+            );
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", arg, value))
+}
+
+/// Parses a `<width>x<height>` size.
+fn parse_size(arg: &str, value: &str) -> Result<(usize, usize), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("expected <width>x<height> for {}: {}", arg, value))?;
+
+    match (parse_number(arg, width)?, parse_number(arg, height)?) {
+        (0, _) | (_, 0) => Err(format!(
+            "{} needs a nonzero width and height: {}",
+            arg, value
+        )),
+        size => Ok(size),
+    }
+}
+
+/// Parses an inclusive `<start>..<end>` range, or a single number.
+fn parse_range<T: std::str::FromStr + PartialOrd>(
+    arg: &str,
+    value: &str,
+) -> Result<RangeInclusive<T>, String> {
+    let (start, end) = value.split_once("..").unwrap_or((value, value));
+    let range = parse_number(arg, start)?..=parse_number(arg, end)?;
+
+    if range.is_empty() {
+        return Err(format!("empty range for {}: {}", arg, value));
+    }
+
+    Ok(range)
+}
+
+/// Parses a `<width>x<height>x<depth>` size.
+fn parse_lattice(arg: &str, value: &str) -> Result<(usize, usize, usize), String> {
+    let sizes = value
+        .split('x')
+        .map(|size| parse_number(arg, size))
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    match sizes.as_slice() {
+        [_, _, _] if sizes.contains(&0) => Err(format!(
+            "{} needs a nonzero width, height and depth: {}",
+            arg, value
+        )),
+        [width, height, depth] => Ok((*width, *height, *depth)),
+        _ => Err(format!(
+            "expected <width>x<height>x<depth> for {}: {}",
+            arg, value
+        )),
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+/// Keeps two kinds of time for a run. Simulated time advances by the length
+/// of a generation at the chosen speed each time the board steps, so it is
+/// how long the run would take in real time; wall-clock run time is how long
+/// the program has actually been running, pauses included.
+#[derive(Clone, Debug)]
+pub struct SimulationClock {
+    started_at: Instant,
+    simulated: Duration,
+}
+
+impl SimulationClock {
+    pub fn start() -> Self {
+        SimulationClock {
+            started_at: Instant::now(),
+            simulated: Duration::ZERO,
+        }
+    }
+
+    /// Counts one generation lasting `generation_length`.
+    pub fn tick(&mut self, generation_length: Duration) {
+        self.simulated += generation_length;
+    }
+
+    pub fn simulated(&self) -> Duration {
+        self.simulated
+    }
+
+    pub fn run_time(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Formats a duration compactly, e.g. `42.5s`, `3m07s` or `2h05m`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    if seconds < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if seconds < 3600 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60)
+    }
+}
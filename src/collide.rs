@@ -0,0 +1,233 @@
+//! Automates the classic glider synthesis workflow: places one pattern at a
+//! range of offsets and phases relative to another, runs each collision and
+//! sorts out what it produced.
+
+use crate::board::Board;
+use crate::pattern::Pattern;
+use crate::period::{find_periodicity, PeriodDetector};
+use crate::rule::Rule;
+use std::collections::HashMap;
+
+/// Longest period recognized, both for the whole board settling and for the
+/// objects it settles into.
+const MAX_PERIOD: usize = 32;
+/// Live cells this close (in either direction) belong to the same object.
+const OBJECT_SPACING: i32 = 2;
+/// Name given to objects that did not repeat within `MAX_PERIOD`.
+const UNSETTLED: &str = "unsettled debris";
+
+/// What a collision amounted to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Outcome {
+    /// Nothing is left.
+    Annihilation,
+    /// The same objects came out as went in, so the patterns missed or
+    /// passed through each other.
+    NoInteraction,
+    /// Only still lifes and oscillators are left, but not the ones that
+    /// went in.
+    NewObjects,
+    /// At least one spaceship flies away from the wreckage.
+    Escape,
+    /// Something was still evolving when the run ended.
+    Unsettled,
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Annihilation => "annihilation",
+            Outcome::NoInteraction => "no interaction",
+            Outcome::NewObjects => "new objects",
+            Outcome::Escape => "escape",
+            Outcome::Unsettled => "unsettled",
+        }
+    }
+}
+
+/// The result of one collision.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collision {
+    /// Where the second pattern was placed relative to the first.
+    pub offset: (i32, i32),
+    /// Generations the second pattern was run alone before being placed.
+    pub phase: usize,
+    pub outcome: Outcome,
+    /// Names of the objects left at the end, sorted.
+    pub products: Vec<String>,
+}
+
+/// Two patterns to collide and the rule and run length to collide them with.
+pub struct Lab {
+    first: Pattern,
+    second: Pattern,
+    rule: Rule,
+    generations: usize,
+    /// Objects the two patterns consist of, sorted; a collision producing
+    /// the same ones did not interact.
+    inputs: Vec<String>,
+}
+
+impl Lab {
+    pub fn new(first: Pattern, second: Pattern, rule: Rule, generations: usize) -> Self {
+        let mut inputs = objects(&first.cells, rule);
+        inputs.extend(objects(&second.cells, rule));
+        inputs.sort();
+
+        Lab {
+            first,
+            second,
+            rule,
+            generations,
+            inputs,
+        }
+    }
+
+    /// Runs the second pattern for `phase` generations, places it at
+    /// `offset` from the first and runs them together for up to the lab's
+    /// generations, stopping early once the board repeats.
+    pub fn collide(&self, offset: (i32, i32), phase: usize) -> Collision {
+        let second = advance(&self.second.cells, self.rule, phase);
+        let cells: Vec<(i32, i32)> = self
+            .first
+            .cells
+            .iter()
+            .copied()
+            .chain(second.iter().map(|(x, y)| (x + offset.0, y + offset.1)))
+            .collect();
+        let live_cells = run(&cells, self.rule, self.generations, true);
+        let mut products = objects(&live_cells, self.rule);
+
+        products.sort();
+
+        let outcome = if products.is_empty() {
+            Outcome::Annihilation
+        } else if products == self.inputs {
+            Outcome::NoInteraction
+        } else if products.iter().any(|name| name == UNSETTLED) {
+            Outcome::Unsettled
+        } else if products.iter().any(|name| name.ends_with("spaceship")) {
+            Outcome::Escape
+        } else {
+            Outcome::NewObjects
+        };
+
+        Collision {
+            offset,
+            phase,
+            outcome,
+            products,
+        }
+    }
+}
+
+/// The cells after running for `generations`, in the same coordinates.
+fn advance(cells: &[(i32, i32)], rule: Rule, generations: usize) -> Vec<(i32, i32)> {
+    run(cells, rule, generations, false)
+}
+
+/// Runs the cells on an empty board big enough that a c/2 spaceship doesn't
+/// reach the edge, optionally stopping as soon as the board repeats, and
+/// returns the live cells in the original coordinates.
+fn run(
+    cells: &[(i32, i32)],
+    rule: Rule,
+    generations: usize,
+    stop_when_settled: bool,
+) -> Vec<(i32, i32)> {
+    if cells.is_empty() {
+        return Vec::new();
+    }
+
+    let margin = generations as i32 / 2 + OBJECT_SPACING + 1;
+    let (min_x, min_y, max_x, max_y) = cells.iter().fold(
+        (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+        |(min_x, min_y, max_x, max_y), (x, y)| {
+            (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+        },
+    );
+    let origin = (min_x - margin, min_y - margin);
+    let mut board = Board::empty(
+        (max_x - min_x + 1 + 2 * margin) as usize,
+        (max_y - min_y + 1 + 2 * margin) as usize,
+    );
+    let mut detector = PeriodDetector::new(MAX_PERIOD);
+
+    board.rule = rule;
+    board.stamp(
+        &Pattern::new("collision", cells.to_vec()),
+        -origin.0,
+        -origin.1,
+    );
+    detector.observe(&board);
+
+    while board.generation < generations {
+        board.update_live_neighbor_counts();
+        board.step();
+
+        if stop_when_settled && (board.population() == 0 || detector.observe(&board).is_some()) {
+            break;
+        }
+    }
+
+    board
+        .live_cells()
+        .into_iter()
+        .map(|(x, y)| (x + origin.0, y + origin.1))
+        .collect()
+}
+
+/// Splits the live cells into separate objects and names each by how it
+/// behaves on its own.
+fn objects(cells: &[(i32, i32)], rule: Rule) -> Vec<String> {
+    clusters(cells)
+        .iter()
+        .map(
+            |cluster| match find_periodicity(cluster, rule, MAX_PERIOD) {
+                Some(periodicity) => periodicity.name(),
+                None => UNSETTLED.to_string(),
+            },
+        )
+        .collect()
+}
+
+/// Groups cells within `OBJECT_SPACING` of each other.
+pub(crate) fn clusters(cells: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    let index: HashMap<(i32, i32), usize> = cells
+        .iter()
+        .enumerate()
+        .map(|(position, cell)| (*cell, position))
+        .collect();
+    let mut cluster_of = vec![usize::MAX; cells.len()];
+    let mut clusters = Vec::new();
+
+    for start in 0..cells.len() {
+        if cluster_of[start] != usize::MAX {
+            continue;
+        }
+
+        let mut cluster = Vec::new();
+        let mut pending = vec![start];
+        cluster_of[start] = clusters.len();
+
+        while let Some(position) = pending.pop() {
+            let (x, y) = cells[position];
+            cluster.push((x, y));
+
+            for dy in -OBJECT_SPACING..=OBJECT_SPACING {
+                for dx in -OBJECT_SPACING..=OBJECT_SPACING {
+                    if let Some(&neighbor) = index.get(&(x + dx, y + dy)) {
+                        if cluster_of[neighbor] == usize::MAX {
+                            cluster_of[neighbor] = clusters.len();
+                            pending.push(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+
+        clusters.push(cluster);
+    }
+
+    clusters
+}
@@ -0,0 +1,478 @@
+use crate::cli::Options;
+use crate::{initial_board, load_playback, pattern_rule};
+use game_of_life::collide::{Lab, Outcome};
+use game_of_life::evolve::Evolution;
+use game_of_life::predecessor::{find_predecessor, MAX_REGION_CELLS};
+use game_of_life::rule_search::{self, Survey};
+use game_of_life::{info, Board, Board3d, Bookmarks, Engine, Replay};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Rules in each generation of the genetic search.
+const EVOLVE_POPULATION: usize = 24;
+/// How many of the best rules found are listed at the end.
+const EVOLVE_REPORTED: usize = 5;
+
+/// How many of the top ranked rules `rule-search` lists.
+const RULE_SEARCH_REPORTED: usize = 20;
+/// Rules surveyed between progress messages.
+const RULE_SEARCH_PROGRESS_EVERY: usize = 1000;
+
+/// Differing cells listed when two engines diverge.
+const DIVERGE_REPORTED_CELLS: usize = 10;
+
+/// Soup densities tried at each board size.
+const STRESS_DENSITIES: [f64; 3] = [0.1, 0.3, 0.5];
+/// How long each size and density is measured for.
+const STRESS_STAGE_DURATION: Duration = Duration::from_secs(1);
+/// Side length of the first board, in cells; it doubles at each stage.
+const STRESS_START_SIZE: usize = 64;
+const STRESS_START_SIZE_3D: usize = 16;
+
+/// One measured stage of a stress test.
+struct StressResult {
+    size: String,
+    cells: usize,
+    density: f64,
+    generations: usize,
+    rate: f64,
+}
+
+/// Searches for a predecessor of `--region` on the starting board and prints
+/// it, or reports that the region is a Garden of Eden.
+pub fn predecessor(options: &Options) -> Result<(), String> {
+    let region = options
+        .region
+        .ok_or("predecessor requires --region <x>,<y>,<width>x<height>")?;
+
+    if region.width * region.height > MAX_REGION_CELLS {
+        return Err(format!(
+            "region has {} cells; predecessor search is limited to {}",
+            region.width * region.height,
+            MAX_REGION_CELLS
+        ));
+    }
+
+    let board = initial_board(options, options.seed);
+    let predecessor = find_predecessor(&board, &region)?;
+
+    println!("target under {}:", board.rule.name());
+    print_grid(&board.alive_in(&region), region.width);
+
+    match predecessor {
+        Some(predecessor) => {
+            println!("predecessor:");
+            print_grid(&predecessor, region.width + 2);
+        }
+        None => println!("no predecessor exists: the region is a Garden of Eden"),
+    }
+
+    Ok(())
+}
+
+/// Steps random soups of growing size and density, printing each
+/// measurement as it completes, until the generation rate drops below
+/// `--min-rate`. Then prints a summary table.
+pub fn stress(options: &Options) -> Result<(), String> {
+    let seed = options.seed.unwrap_or(0);
+    let mut results = Vec::new();
+    let mut side = if options.dimensions == 3 {
+        STRESS_START_SIZE_3D
+    } else {
+        STRESS_START_SIZE
+    };
+
+    println!(
+        "stress testing the {}D engine until it drops below {} generations/second",
+        options.dimensions, options.min_rate
+    );
+
+    'ramp: loop {
+        for density in STRESS_DENSITIES.iter().copied() {
+            let result = if options.dimensions == 3 {
+                let mut board = Board3d::seeded(side, side, side, options.rule3d, seed, density);
+                measure_stress(format!("{0}x{0}x{0}", side), side.pow(3), density, || {
+                    board.step()
+                })
+            } else {
+                let mut board = Board::seeded(side, side, seed, density);
+                board.topology = options.topology;
+                measure_stress(format!("{0}x{0}", side), side * side, density, || {
+                    board.update_live_neighbor_counts();
+                    board.step();
+                })
+            };
+
+            println!(
+                "  {} at density {:.1}: {:.1} generations/second",
+                result.size, result.density, result.rate
+            );
+
+            let too_slow = result.rate < options.min_rate;
+            results.push(result);
+
+            if too_slow {
+                break 'ramp;
+            }
+        }
+
+        side *= 2;
+    }
+
+    println!();
+    println!(
+        "{:>12} {:>12} {:>8} {:>12} {:>14} {:>16}",
+        "board", "cells", "density", "generations", "gens/second", "cells/second"
+    );
+
+    for result in results.iter() {
+        println!(
+            "{:>12} {:>12} {:>8.1} {:>12} {:>14.1} {:>16.0}",
+            result.size,
+            result.cells,
+            result.density,
+            result.generations,
+            result.rate,
+            result.rate * result.cells as f64
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs `step` repeatedly for `STRESS_STAGE_DURATION`, always completing at
+/// least one generation so huge boards still report a rate.
+fn measure_stress(
+    size: String,
+    cells: usize,
+    density: f64,
+    mut step: impl FnMut(),
+) -> StressResult {
+    let started_at = Instant::now();
+    let mut generations = 0;
+
+    while generations == 0 || started_at.elapsed() < STRESS_STAGE_DURATION {
+        step();
+        generations += 1;
+    }
+
+    StressResult {
+        size,
+        cells,
+        density,
+        generations,
+        rate: generations as f64 / started_at.elapsed().as_secs_f64(),
+    }
+}
+
+/// Evolves rulestrings for `--iterations` rounds, printing the best of
+/// each round and then the most interesting rules found overall.
+pub fn evolve(options: &Options) -> Result<(), String> {
+    let mut evolution = Evolution::new(options.seed.unwrap_or(0), EVOLVE_POPULATION);
+
+    for iteration in 1..=options.iterations {
+        let candidates = evolution.iterate();
+        let mean = candidates
+            .iter()
+            .map(|candidate| candidate.fitness)
+            .sum::<f64>()
+            / candidates.len() as f64;
+
+        println!(
+            "iteration {}: best {} ({:.3}), mean {:.3}",
+            iteration,
+            candidates[0].rule.name(),
+            candidates[0].fitness,
+            mean
+        );
+    }
+
+    println!();
+    println!("most interesting rules:");
+
+    for candidate in evolution.hall_of_fame.iter().take(EVOLVE_REPORTED) {
+        println!("  {:<20} {:.3}", candidate.rule.name(), candidate.fitness);
+    }
+
+    Ok(())
+}
+
+/// Surveys every B/S rule or a `--samples` sized sample of them on the
+/// standard soups and prints the most interesting, best first.
+pub fn rule_search(options: &Options) -> Result<(), String> {
+    let rules = rule_search::rules(options.samples, options.seed.unwrap_or(0));
+    let mut surveys: Vec<Survey> = Vec::with_capacity(rules.len());
+
+    println!("surveying {} rules", rules.len());
+
+    for rule in rules {
+        surveys.push(rule_search::survey(rule));
+
+        if surveys.len().is_multiple_of(RULE_SEARCH_PROGRESS_EVERY) {
+            info!("surveyed {} rules", surveys.len());
+        }
+    }
+
+    surveys.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    println!();
+    println!(
+        "{:<4} {:<20} {:>6} {:>10} {:>8} {:>11} {:>8} {:>8}",
+        "rank", "rule", "score", "transient", "bounded", "spaceships", "density", "entropy"
+    );
+
+    for (rank, survey) in surveys.iter().take(RULE_SEARCH_REPORTED).enumerate() {
+        println!(
+            "{:<4} {:<20} {:>6.3} {:>10.0} {:>8.2} {:>11.2} {:>8.3} {:>8.3}",
+            rank + 1,
+            survey.rule.name(),
+            survey.score,
+            survey.transient,
+            survey.bounded,
+            survey.spaceships,
+            survey.density,
+            survey.entropy
+        );
+    }
+
+    Ok(())
+}
+
+fn print_grid(cells: &[bool], width: usize) {
+    for row in cells.chunks(width) {
+        let line: String = row
+            .iter()
+            .map(|alive| if *alive { 'o' } else { '.' })
+            .collect();
+
+        println!("{}", line);
+    }
+}
+
+/// Fires `--with` at `--pattern` from every offset and phase in range,
+/// printing each outcome as it is found and a tally at the end.
+pub fn collide(options: &Options) -> Result<(), String> {
+    let (first, second) = match (&options.pattern, &options.with) {
+        (Some(first), Some(second)) => (first.clone(), second.clone()),
+        _ => return Err("collide requires --pattern <file> and --with <file>".to_string()),
+    };
+    let (x_offsets, y_offsets) = options
+        .offsets
+        .clone()
+        .ok_or("collide requires --offset <x0>..<x1>,<y0>..<y1>")?;
+    let rule = options
+        .rule
+        .unwrap_or_else(|| pattern_rule(options.pattern.as_ref()));
+    let lab = Lab::new(first, second, rule, options.generations);
+    let mut tally: BTreeMap<Outcome, usize> = BTreeMap::new();
+
+    println!(
+        "colliding {} with {} under {} for up to {} generations",
+        options.with.as_ref().map_or("", |pattern| &pattern.name),
+        options.pattern.as_ref().map_or("", |pattern| &pattern.name),
+        rule.name(),
+        options.generations
+    );
+
+    for phase in options.phases.clone() {
+        for dy in y_offsets.clone() {
+            for dx in x_offsets.clone() {
+                let collision = lab.collide((dx, dy), phase);
+
+                println!(
+                    "{:>5} {:>5} {:>5}  {:<14} {}",
+                    dx,
+                    dy,
+                    phase,
+                    collision.outcome.name(),
+                    summarize_products(&collision.products)
+                );
+
+                *tally.entry(collision.outcome).or_default() += 1;
+            }
+        }
+    }
+
+    println!();
+
+    for (outcome, count) in tally.iter() {
+        println!("{:>14}: {}", outcome.name(), count);
+    }
+
+    Ok(())
+}
+
+/// Counts repeated object names, e.g. `2 still life, 1 p2 oscillator`.
+fn summarize_products(products: &[String]) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+
+    for product in products.iter() {
+        *counts.entry(product).or_default() += 1;
+    }
+
+    counts
+        .iter()
+        .map(|(name, count)| format!("{} {}", count, name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Steps the starting board on both `--engines` in lockstep, comparing state
+/// hashes every generation, and reports the first generation where they
+/// differ along with some of the cells that do.
+pub fn diverge(options: &Options) -> Result<(), String> {
+    let (first_engine, second_engine) = options.engines;
+    let mut first = initial_board(options, options.seed);
+    let mut second = first.clone();
+
+    first.engine = first_engine;
+    second.engine = second_engine;
+
+    println!(
+        "comparing the {} and {} engines for {} generations",
+        first_engine.name(),
+        second_engine.name(),
+        options.generations
+    );
+
+    for _ in 0..options.generations {
+        for board in [&mut first, &mut second] {
+            board.update_live_neighbor_counts();
+            board.step();
+        }
+
+        let (first_hash, second_hash) = (first.state_hash(), second.state_hash());
+
+        if first_hash != second_hash {
+            let differing: Vec<(i32, i32)> = (0..first.cells.len())
+                .filter(|index| first.cells[*index].is_alive() != second.cells[*index].is_alive())
+                .map(|index| first.index_to_coordinates(index))
+                .collect();
+
+            println!(
+                "generation {}: {} hash {:016x}, {} hash {:016x}",
+                first.generation,
+                first_engine.name(),
+                first_hash,
+                second_engine.name(),
+                second_hash
+            );
+            println!(
+                "{} cells differ in liveness, first at {:?}",
+                differing.len(),
+                &differing[..differing.len().min(DIVERGE_REPORTED_CELLS)]
+            );
+
+            return Err(format!(
+                "engines diverged at generation {}",
+                first.generation
+            ));
+        }
+    }
+
+    println!(
+        "no divergence in {} generations, final hash {:016x}",
+        options.generations,
+        first.state_hash()
+    );
+
+    Ok(())
+}
+
+/// Runs the starting board twice from scratch, on `--engine` and then on
+/// `--verify-engine`, applying any `--replay` inputs, and checks the state
+/// hashes the runs recorded every `--verify-every` generations and at the
+/// end agree. Unlike `diverge`, the runs share nothing, so state leaking
+/// between generations or from outside the board shows up too.
+pub fn verify(options: &Options) -> Result<(), String> {
+    let (playback, seed) = load_playback(options)?;
+    let engines = (
+        options.engine,
+        options.verify_engine.unwrap_or(options.engine),
+    );
+
+    println!(
+        "verifying {} generations on the {} and {} engines, comparing every {}",
+        options.generations,
+        engines.0.name(),
+        engines.1.name(),
+        options.verify_every
+    );
+
+    let first = verify_run(options, engines.0, seed, playback.as_ref())?;
+    let second = verify_run(options, engines.1, seed, playback.as_ref())?;
+    let mut mismatches = 0;
+    let mut first_mismatch = None;
+
+    for ((generation, first_hash), (_, second_hash)) in first.iter().zip(second.iter()) {
+        let verdict = if first_hash == second_hash {
+            "match"
+        } else {
+            mismatches += 1;
+            first_mismatch.get_or_insert(*generation);
+            "MISMATCH"
+        };
+
+        println!(
+            "generation {:>8}: {:016x} {:016x} {}",
+            generation, first_hash, second_hash, verdict
+        );
+    }
+
+    match first_mismatch {
+        Some(generation) => Err(format!(
+            "{} of {} checkpoints differ, first at generation {}",
+            mismatches,
+            first.len(),
+            generation
+        )),
+        None => {
+            println!("all {} checkpoints match", first.len());
+            Ok(())
+        }
+    }
+}
+
+/// One `verify` run's `(generation, state hash)` checkpoints.
+fn verify_run(
+    options: &Options,
+    engine: Engine,
+    seed: Option<u64>,
+    playback: Option<&Replay>,
+) -> Result<Vec<(usize, u64)>, String> {
+    let mut board = initial_board(options, seed);
+    let mut bookmarks = Bookmarks::new();
+    let mut checkpoints = Vec::new();
+    let started_at = Instant::now();
+
+    if let Some(replay) = playback {
+        replay.settings.apply(&mut board)?;
+    }
+
+    board.engine = engine;
+
+    loop {
+        if let Some(replay) = playback {
+            for event in replay.events_at(board.generation) {
+                event.input.apply(&mut board, &mut bookmarks)?;
+            }
+        }
+
+        let done = board.generation >= options.generations;
+
+        if done || board.generation.is_multiple_of(options.verify_every) {
+            checkpoints.push((board.generation, board.state_hash()));
+        }
+
+        if done {
+            break;
+        }
+
+        board.update_live_neighbor_counts();
+        board.step();
+    }
+
+    info!("{} run took {:.2?}", engine.name(), started_at.elapsed());
+
+    Ok(checkpoints)
+}
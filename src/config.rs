@@ -0,0 +1,289 @@
+use crate::board::CYCLES_TO_DIE;
+use crate::brush::{
+    DEFAULT_BRUSH_DENSITY, DEFAULT_BRUSH_RADIUS, DEFAULT_NOISE_DENSITY, DEFAULT_NOISE_RADIUS,
+};
+use crate::memory;
+use crate::png;
+use crate::resources::{ResourceRule, ResourceScope};
+use crate::theme::{parse_gradient, Rgb, Theme};
+use crate::trail::{DyingPolicy, FadeCurve};
+use crate::weighted::WeightedRule;
+use std::fs;
+
+/// How births and deaths are animated when several frames are rendered per
+/// generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Smoothing {
+    #[default]
+    Off,
+    Fade,
+    Scale,
+}
+
+impl Smoothing {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "off" => Ok(Smoothing::Off),
+            "fade" => Ok(Smoothing::Fade),
+            "scale" => Ok(Smoothing::Scale),
+            _ => Err(format!("unknown smoothing: {}", text)),
+        }
+    }
+
+    /// The name `parse` accepts for this mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Smoothing::Off => "off",
+            Smoothing::Fade => "fade",
+            Smoothing::Scale => "scale",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Smoothing::Off => Smoothing::Fade,
+            Smoothing::Fade => Smoothing::Scale,
+            Smoothing::Scale => Smoothing::Off,
+        }
+    }
+}
+
+/// Settings read from a `key = value` config file. Blank lines and lines
+/// starting with `#` are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub trail_length: usize,
+    pub trail_curve: FadeCurve,
+    pub theme: Theme,
+    pub smoothing: Smoothing,
+    /// Directory of `.rle` files to add to the pattern catalog.
+    pub pattern_dir: Option<String>,
+    /// Bytes the simulation may use before caches are trimmed.
+    pub memory_budget: Option<usize>,
+    /// Probability each generation that poison kills a live neighbor.
+    pub poison_chance: f64,
+    /// Whether dying cells count as live neighbors and can be born again.
+    pub dying_policy: DyingPolicy,
+    /// Radius of the circle and spray brushes, in cells.
+    pub brush_radius: usize,
+    /// Fraction of the spray brush's circle painted per dab.
+    pub brush_density: f64,
+    /// Radius of the noise bursts injected at the cursor, in cells.
+    pub noise_radius: usize,
+    /// Fraction of a noise burst's circle brought to life.
+    pub noise_density: f64,
+    /// How much of the cell history tint fades each generation a cell is
+    /// dead, from 0.0 (never fades) to 1.0.
+    pub history_decay: f32,
+    /// Weighted life rule to run instead of the B/S rule.
+    pub weighted_rule: Option<WeightedRule>,
+    /// Resource dynamics to run the board with, see `ResourceField`.
+    pub resource_rule: Option<ResourceRule>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            trail_length: CYCLES_TO_DIE,
+            trail_curve: FadeCurve::default(),
+            theme: Theme::default(),
+            smoothing: Smoothing::default(),
+            pattern_dir: None,
+            memory_budget: None,
+            poison_chance: 1.0,
+            dying_policy: DyingPolicy::default(),
+            brush_radius: DEFAULT_BRUSH_RADIUS,
+            brush_density: DEFAULT_BRUSH_DENSITY,
+            noise_radius: DEFAULT_NOISE_RADIUS,
+            noise_density: DEFAULT_NOISE_DENSITY,
+            history_decay: 0.0,
+            weighted_rule: None,
+            resource_rule: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Config::default();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let context = |error: String| format!("line {}: {}", line_number + 1, error);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| context(format!("expected key = value: {}", line)))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "trail_length" => {
+                    config.trail_length = value
+                        .parse()
+                        .map_err(|_| context(format!("invalid trail length: {}", value)))?
+                }
+                "trail_curve" => config.trail_curve = FadeCurve::parse(value).map_err(context)?,
+                "smoothing" => config.smoothing = Smoothing::parse(value).map_err(context)?,
+                "poison_chance" => {
+                    config.poison_chance = value
+                        .parse()
+                        .ok()
+                        .filter(|chance| (0.0..=1.0).contains(chance))
+                        .ok_or_else(|| {
+                            context(format!("poison chance must be in 0..1: {}", value))
+                        })?
+                }
+                "dying_policy" => {
+                    config.dying_policy = DyingPolicy::parse(value).map_err(context)?
+                }
+                "brush_radius" => {
+                    config.brush_radius = value
+                        .parse()
+                        .map_err(|_| context(format!("invalid brush radius: {}", value)))?
+                }
+                "brush_density" => {
+                    config.brush_density = value
+                        .parse()
+                        .ok()
+                        .filter(|density| (0.0..=1.0).contains(density))
+                        .ok_or_else(|| {
+                            context(format!("brush density must be in 0..1: {}", value))
+                        })?
+                }
+                "noise_radius" => {
+                    config.noise_radius = value
+                        .parse()
+                        .map_err(|_| context(format!("invalid noise radius: {}", value)))?
+                }
+                "noise_density" => {
+                    config.noise_density = value
+                        .parse()
+                        .ok()
+                        .filter(|density| (0.0..=1.0).contains(density))
+                        .ok_or_else(|| {
+                            context(format!("noise density must be in 0..1: {}", value))
+                        })?
+                }
+                "weighted.weights" => {
+                    config
+                        .weighted_rule
+                        .get_or_insert_with(WeightedRule::default)
+                        .weights = WeightedRule::parse_weights(value).map_err(context)?
+                }
+                "weighted.birth" => {
+                    config
+                        .weighted_rule
+                        .get_or_insert_with(WeightedRule::default)
+                        .birth = WeightedRule::parse_sums(value).map_err(context)?
+                }
+                "weighted.survive" => {
+                    config
+                        .weighted_rule
+                        .get_or_insert_with(WeightedRule::default)
+                        .survive = WeightedRule::parse_sums(value).map_err(context)?
+                }
+                "resources.scope" => {
+                    config
+                        .resource_rule
+                        .get_or_insert_with(ResourceRule::default)
+                        .scope = ResourceScope::parse(value).map_err(context)?
+                }
+                "resources.consumption" => {
+                    config
+                        .resource_rule
+                        .get_or_insert_with(ResourceRule::default)
+                        .consumption =
+                        parse_fraction(value, "resource consumption").map_err(context)?
+                }
+                "resources.regeneration" => {
+                    config
+                        .resource_rule
+                        .get_or_insert_with(ResourceRule::default)
+                        .regeneration =
+                        parse_fraction(value, "resource regeneration").map_err(context)?
+                }
+                "resources.birth_threshold" => {
+                    config
+                        .resource_rule
+                        .get_or_insert_with(ResourceRule::default)
+                        .birth_threshold =
+                        parse_fraction(value, "resource birth threshold").map_err(context)?
+                }
+                "history_decay" => {
+                    config.history_decay = value
+                        .parse()
+                        .ok()
+                        .filter(|decay| (0.0..=1.0).contains(decay))
+                        .ok_or_else(|| {
+                            context(format!("history decay must be in 0..1: {}", value))
+                        })?
+                }
+                "memory_budget" => {
+                    config.memory_budget = Some(memory::parse_bytes(value).map_err(context)?)
+                }
+                "pattern_dir" => config.pattern_dir = Some(value.to_string()),
+                "theme" => config.theme = Theme::preset(value).map_err(context)?,
+                "theme.background" => {
+                    config.theme.background = Rgb::parse(value).map_err(context)?
+                }
+                "theme.alive" => config.theme.alive = Rgb::parse(value).map_err(context)?,
+                "theme.poison" => config.theme.poison = Rgb::parse(value).map_err(context)?,
+                "theme.dying_gradient" => {
+                    config.theme.dying_gradient = Some(parse_gradient(value).map_err(context)?)
+                }
+                "theme.alive_sprite" => {
+                    config.theme.alive_sprite = Some(png::load(value).map_err(context)?)
+                }
+                "theme.dying_sprites" => {
+                    config.theme.dying_sprites = value
+                        .split_whitespace()
+                        .map(png::load)
+                        .collect::<Result<_, _>>()
+                        .map_err(context)?;
+                    config.theme.dying_hatch = false;
+                }
+                "theme.dying_hatch" => {
+                    let hatch: bool = value.parse().map_err(|_| {
+                        context(format!("expected true or false for hatching: {}", value))
+                    })?;
+
+                    if config.theme.dying_hatch && !hatch {
+                        config.theme.dying_sprites.clear();
+                    }
+
+                    config.theme.dying_hatch = hatch;
+                }
+                other => return Err(context(format!("unknown setting: {}", other))),
+            }
+        }
+
+        // Hatching takes the theme's colors, which may be set after it.
+        if config.theme.dying_hatch {
+            config.theme.hatch_dying();
+        }
+
+        Ok(config)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        crate::info!("loading config {}", path);
+
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read config {}: {}", path, error))?;
+
+        Self::parse(&text)
+    }
+}
+
+/// Parses a value that must lie in 0..1, naming it in the error.
+fn parse_fraction(value: &str, what: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|fraction| (0.0..=1.0).contains(fraction))
+        .ok_or_else(|| format!("{} must be in 0..1: {}", what, value))
+}
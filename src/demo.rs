@@ -0,0 +1,102 @@
+use crate::board::Board;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+
+/// What a demo scene starts from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DemoStart {
+    /// A built-in pattern, by catalog key, centered on the board.
+    Pattern(&'static str),
+    /// A random soup of the given density.
+    Soup(f64),
+}
+
+/// One stop of the `--demo` tour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DemoScene {
+    pub caption: &'static str,
+    pub start: DemoStart,
+    /// Rule in B/S notation.
+    pub rule: &'static str,
+    /// Generations the scene runs for before the next one.
+    pub generations: usize,
+    /// Camera zoom at the start and end of the scene; the camera moves
+    /// smoothly from one to the other, centered on the board.
+    pub zoom: (f32, f32),
+}
+
+/// The tour `--demo` loops through.
+pub const DEMO_SCENES: &[DemoScene] = &[
+    DemoScene {
+        caption: "Gosper glider gun: the first pattern found to grow forever",
+        start: DemoStart::Pattern("glider_gun"),
+        rule: "B3/S23",
+        generations: 360,
+        zoom: (2.0, 0.8),
+    },
+    DemoScene {
+        caption: "Pulsar: the most common period 3 oscillator",
+        start: DemoStart::Pattern("pulsar"),
+        rule: "B3/S23",
+        generations: 90,
+        zoom: (3.0, 4.0),
+    },
+    DemoScene {
+        caption: "Seeds (B2/S): every live cell dies, yet a sparse soup explodes",
+        start: DemoStart::Soup(0.02),
+        rule: "B2/S",
+        generations: 150,
+        zoom: (0.5, 0.3),
+    },
+    DemoScene {
+        caption: "Acorn: seven cells that take 5206 generations to settle",
+        start: DemoStart::Pattern("acorn"),
+        rule: "B3/S23",
+        generations: 600,
+        zoom: (3.0, 0.5),
+    },
+    DemoScene {
+        caption: "HighLife (B36/S23): soups breed self-replicating patterns",
+        start: DemoStart::Soup(0.3),
+        rule: "B36/S23",
+        generations: 300,
+        zoom: (0.4, 0.6),
+    },
+];
+
+impl DemoScene {
+    /// The scene's starting board, with soups drawn from `seed`.
+    pub fn board(&self, width: usize, height: usize, seed: u64) -> Board {
+        let mut board = match self.start {
+            DemoStart::Pattern(key) => {
+                let pattern = Pattern::builtin(key).expect("demo patterns are built in");
+                let (pattern_width, pattern_height) = pattern.size();
+                let mut board = Board::empty(width, height);
+
+                board.stamp(
+                    &pattern,
+                    (width as i32 - pattern_width as i32) / 2,
+                    (height as i32 - pattern_height as i32) / 2,
+                );
+
+                board
+            }
+            DemoStart::Soup(density) => Board::seeded(width, height, seed, density),
+        };
+
+        board.rule = Rule::parse(self.rule).expect("demo rules are valid");
+
+        board
+    }
+
+    /// Camera zoom `progress` (0.0 to 1.0) of the way through the scene.
+    pub fn zoom_at(&self, progress: f32) -> f32 {
+        let (start, end) = self.zoom;
+        let progress = progress.clamp(0.0, 1.0);
+
+        // Ease in and out so the camera doesn't lurch at scene changes.
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+
+        start + (end - start) * eased
+    }
+}
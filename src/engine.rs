@@ -0,0 +1,29 @@
+/// How a board updates its neighbor counts before each step. Every engine
+/// must produce identical generations; the `diverge` command checks that.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Engine {
+    /// Recounts every cell's neighbors each generation.
+    #[default]
+    Naive,
+    /// Recounts only the neighbors of cells that changed since the last
+    /// count, which is much faster on sparse boards.
+    Incremental,
+}
+
+impl Engine {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "naive" => Ok(Engine::Naive),
+            "incremental" => Ok(Engine::Incremental),
+            _ => Err(format!("unknown engine: {}", text)),
+        }
+    }
+
+    /// The name `parse` accepts for this engine.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::Naive => "naive",
+            Engine::Incremental => "incremental",
+        }
+    }
+}
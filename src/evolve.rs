@@ -0,0 +1,179 @@
+//! An experimental genetic search for interesting life-like rules.
+//!
+//! Each rule is run on a few small random soups and scored by how long the
+//! soups stay active before settling into a repeating state or dying out,
+//! discounted when they fill the board with chaos. The fittest rules survive
+//! to the next iteration and the rest of the population is bred from them by
+//! crossover and mutation.
+
+use crate::board::Board;
+use crate::period::PeriodDetector;
+use crate::rng::Rng;
+use crate::rule::Rule;
+
+/// Side length of the soups each rule is scored on, in cells.
+const SOUP_SIZE: usize = 64;
+const SOUP_DENSITY: f64 = 0.3;
+/// Soups run per rule; the fitness is their average.
+const SOUPS_PER_RULE: u64 = 3;
+/// Longest a soup is run for. Soups still active by then get full marks
+/// for longevity.
+const MAX_GENERATIONS: usize = 256;
+/// Longest period recognized as a soup having settled.
+const MAX_PERIOD: usize = 16;
+/// Final density above which a soup is considered chaotic rather than
+/// structured. Settled Life soups sit well below it.
+const CHAOS_DENSITY: f64 = 0.25;
+/// How many of the best rules ever seen are remembered.
+const HALL_OF_FAME_SIZE: usize = 10;
+
+/// A rule and how interesting it scored, from 0 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candidate {
+    pub rule: Rule,
+    pub fitness: f64,
+}
+
+/// A population of rules evolving toward longer-lived, structured soups.
+pub struct Evolution {
+    rng: Rng,
+    seed: u64,
+    population: Vec<Rule>,
+    /// The best distinct rules found so far, fittest first.
+    pub hall_of_fame: Vec<Candidate>,
+}
+
+impl Evolution {
+    /// Starts from a random population. The seed determines both the
+    /// population and the soups, so runs reproduce.
+    pub fn new(seed: u64, population_size: usize) -> Self {
+        let mut rng = Rng::new(seed);
+        let population = (0..population_size.max(2))
+            .map(|_| random_rule(&mut rng))
+            .collect();
+
+        Evolution {
+            rng,
+            seed,
+            population,
+            hall_of_fame: Vec::new(),
+        }
+    }
+
+    /// Scores the current population, records the best rules and breeds the
+    /// next population. Returns the scored population, fittest first.
+    pub fn iterate(&mut self) -> Vec<Candidate> {
+        let seed = self.seed;
+        let mut candidates: Vec<Candidate> = self
+            .population
+            .iter()
+            .map(|rule| Candidate {
+                rule: *rule,
+                fitness: fitness(*rule, seed),
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+        for candidate in candidates.iter() {
+            if !self
+                .hall_of_fame
+                .iter()
+                .any(|known| known.rule == candidate.rule)
+            {
+                self.hall_of_fame.push(*candidate);
+            }
+        }
+
+        self.hall_of_fame
+            .sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        self.hall_of_fame.truncate(HALL_OF_FAME_SIZE);
+
+        let survivors: Vec<Rule> = candidates
+            .iter()
+            .take((candidates.len() / 4).max(2))
+            .map(|candidate| candidate.rule)
+            .collect();
+        let mut next = survivors.clone();
+
+        while next.len() < self.population.len() {
+            let a = survivors[self.rng.next_u64() as usize % survivors.len()];
+            let b = survivors[self.rng.next_u64() as usize % survivors.len()];
+
+            next.push(mutate(crossover(a, b, &mut self.rng), &mut self.rng));
+        }
+
+        self.population = next;
+
+        candidates
+    }
+}
+
+/// Scores how interesting the rule is by averaging `soup_fitness` over a
+/// few soups generated from the seed.
+pub fn fitness(rule: Rule, seed: u64) -> f64 {
+    (0..SOUPS_PER_RULE)
+        .map(|soup| soup_fitness(rule, seed.wrapping_add(soup)))
+        .sum::<f64>()
+        / SOUPS_PER_RULE as f64
+}
+
+/// The fraction of `MAX_GENERATIONS` the soup stays active, scaled down as
+/// its final density approaches `CHAOS_DENSITY`.
+fn soup_fitness(rule: Rule, seed: u64) -> f64 {
+    let mut board = Board::seeded(SOUP_SIZE, SOUP_SIZE, seed, SOUP_DENSITY);
+    let mut detector = PeriodDetector::new(MAX_PERIOD);
+
+    board.rule = rule;
+
+    while board.generation < MAX_GENERATIONS {
+        board.update_live_neighbor_counts();
+        board.step();
+
+        let population = board.population();
+
+        if population == 0 || detector.observe(&board).is_some() {
+            break;
+        }
+    }
+
+    let longevity = board.generation as f64 / MAX_GENERATIONS as f64;
+    let density = board.population() as f64 / board.cells.len() as f64;
+
+    longevity * (1.0 - density / CHAOS_DENSITY).max(0.0)
+}
+
+/// A random rule without B0, which would fill empty space every generation.
+fn random_rule(rng: &mut Rng) -> Rule {
+    Rule {
+        birth: rng.next_u64() as u16 & 0b1_1111_1110,
+        survive: rng.next_u64() as u16 & 0b1_1111_1111,
+    }
+}
+
+/// Takes each birth and survival count from either parent at random.
+fn crossover(a: Rule, b: Rule, rng: &mut Rng) -> Rule {
+    let (birth_mask, survive_mask) = (rng.next_u64() as u16, rng.next_u64() as u16);
+
+    Rule {
+        birth: (a.birth & birth_mask) | (b.birth & !birth_mask),
+        survive: (a.survive & survive_mask) | (b.survive & !survive_mask),
+    }
+}
+
+/// Flips one birth or survival count, never turning on B0.
+fn mutate(rule: Rule, rng: &mut Rng) -> Rule {
+    let count = rng.next_u64() % 17;
+
+    if count < 8 {
+        Rule {
+            birth: rule.birth ^ (1 << (count + 1)),
+            ..rule
+        }
+    } else {
+        Rule {
+            survive: rule.survive ^ (1 << (count - 8)),
+            ..rule
+        }
+    }
+}
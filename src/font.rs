@@ -0,0 +1,343 @@
+//! A tiny built-in 5x7 bitmap font, so overlays and rendered images need no
+//! font files. Lowercase letters are drawn as uppercase.
+
+use crate::image::RgbaImage;
+use crate::theme::Rgb;
+
+pub const GLYPH_WIDTH: i32 = 5;
+pub const GLYPH_HEIGHT: i32 = 7;
+/// Horizontal distance between the starts of consecutive glyphs, in font
+/// dots.
+pub const ADVANCE: i32 = GLYPH_WIDTH + 1;
+/// Vertical distance between lines, in font dots.
+pub const LINE_HEIGHT: i32 = GLYPH_HEIGHT + 3;
+
+/// Rows of each glyph from top to bottom, most significant bit leftmost.
+/// Covers `' '..='`'` followed by `'{'..='~'`.
+const GLYPHS: [[u8; 7]; 69] = [
+    // ' '
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+    ],
+    // '!'
+    [
+        0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100,
+    ],
+    // '"'
+    [
+        0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+    ],
+    // '#'
+    [
+        0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010,
+    ],
+    // '$'
+    [
+        0b00100, 0b01111, 0b10100, 0b01110, 0b00101, 0b11110, 0b00100,
+    ],
+    // '%'
+    [
+        0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011,
+    ],
+    // '&'
+    [
+        0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101,
+    ],
+    // '\''
+    [
+        0b00100, 0b00100, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+    ],
+    // '('
+    [
+        0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010,
+    ],
+    // ')'
+    [
+        0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000,
+    ],
+    // '*'
+    [
+        0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000,
+    ],
+    // '+'
+    [
+        0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000,
+    ],
+    // ','
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000,
+    ],
+    // '-'
+    [
+        0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000,
+    ],
+    // '.'
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
+    ],
+    // '/'
+    [
+        0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000,
+    ],
+    // '0'
+    [
+        0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+    ],
+    // '1'
+    [
+        0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+    ],
+    // '2'
+    [
+        0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111,
+    ],
+    // '3'
+    [
+        0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110,
+    ],
+    // '4'
+    [
+        0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+    ],
+    // '5'
+    [
+        0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+    ],
+    // '6'
+    [
+        0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+    ],
+    // '7'
+    [
+        0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+    ],
+    // '8'
+    [
+        0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+    ],
+    // '9'
+    [
+        0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+    ],
+    // ':'
+    [
+        0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000,
+    ],
+    // ';'
+    [
+        0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000,
+    ],
+    // '<'
+    [
+        0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010,
+    ],
+    // '='
+    [
+        0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000,
+    ],
+    // '>'
+    [
+        0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000,
+    ],
+    // '?'
+    [
+        0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100,
+    ],
+    // '@'
+    [
+        0b01110, 0b10001, 0b00001, 0b01101, 0b10101, 0b10101, 0b01110,
+    ],
+    // 'A'
+    [
+        0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+    ],
+    // 'B'
+    [
+        0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+    ],
+    // 'C'
+    [
+        0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+    ],
+    // 'D'
+    [
+        0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100,
+    ],
+    // 'E'
+    [
+        0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+    ],
+    // 'F'
+    [
+        0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+    ],
+    // 'G'
+    [
+        0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+    ],
+    // 'H'
+    [
+        0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+    ],
+    // 'I'
+    [
+        0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+    ],
+    // 'J'
+    [
+        0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+    ],
+    // 'K'
+    [
+        0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+    ],
+    // 'L'
+    [
+        0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+    ],
+    // 'M'
+    [
+        0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+    ],
+    // 'N'
+    [
+        0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001,
+    ],
+    // 'O'
+    [
+        0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+    ],
+    // 'P'
+    [
+        0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+    ],
+    // 'Q'
+    [
+        0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+    ],
+    // 'R'
+    [
+        0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+    ],
+    // 'S'
+    [
+        0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110,
+    ],
+    // 'T'
+    [
+        0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+    ],
+    // 'U'
+    [
+        0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+    ],
+    // 'V'
+    [
+        0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+    ],
+    // 'W'
+    [
+        0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010,
+    ],
+    // 'X'
+    [
+        0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+    ],
+    // 'Y'
+    [
+        0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100,
+    ],
+    // 'Z'
+    [
+        0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+    ],
+    // '['
+    [
+        0b00111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00111,
+    ],
+    // '\\'
+    [
+        0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000,
+    ],
+    // ']'
+    [
+        0b11100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b11100,
+    ],
+    // '^'
+    [
+        0b00100, 0b01010, 0b10001, 0b00000, 0b00000, 0b00000, 0b00000,
+    ],
+    // '_'
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111,
+    ],
+    // '`'
+    [
+        0b01000, 0b00100, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+    ],
+    // '{'
+    [
+        0b00010, 0b00100, 0b00100, 0b01000, 0b00100, 0b00100, 0b00010,
+    ],
+    // '|'
+    [
+        0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+    ],
+    // '}'
+    [
+        0b01000, 0b00100, 0b00100, 0b00010, 0b00100, 0b00100, 0b01000,
+    ],
+    // '~'
+    [
+        0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000,
+    ],
+];
+
+/// The rows of the glyph for `c`, drawing unknown characters as `?`.
+pub fn glyph(c: char) -> &'static [u8; 7] {
+    let c = c.to_ascii_uppercase();
+
+    match c {
+        ' '..='`' => &GLYPHS[c as usize - ' ' as usize],
+        '{'..='~' => &GLYPHS[65 + c as usize - '{' as usize],
+        _ => &GLYPHS['?' as usize - ' ' as usize],
+    }
+}
+
+/// Width in pixels of the text drawn with `size` pixels per font dot.
+pub fn text_width(text: &str, size: i32) -> i32 {
+    (text.chars().count() as i32 * ADVANCE - 1).max(0) * size
+}
+
+/// The font dots of the text, with its top left corner at the origin.
+pub fn dots(text: &str) -> Vec<(i32, i32)> {
+    let mut dots = Vec::new();
+
+    for (position, c) in text.chars().enumerate() {
+        let left = position as i32 * ADVANCE;
+
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    dots.push((left + column, row as i32));
+                }
+            }
+        }
+    }
+
+    dots
+}
+
+/// Draws the text into the image with its top left corner at `(x, y)` and
+/// `size` pixels per font dot.
+pub fn draw_text(image: &mut RgbaImage, (x, y): (i32, i32), size: i32, text: &str, color: Rgb) {
+    for (column, row) in dots(text) {
+        image.fill_rect(
+            (x + column * size) as i64,
+            (y + row * size) as i64,
+            size as usize,
+            size as usize,
+            color,
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! The parts of a window frontend that don't depend on how its window is
+//! made: `Surface`, for showing software rendered frames and reporting
+//! input, and `Controls`, which turn that input into the same recordable
+//! `Input`s the SDL2 frontend produces. The `x11` frontend is a `Surface`;
+//! another backend, such as winit with pixels, only has to be one too.
+
+use crate::board::{Board, MAX_TRAIL_LENGTH};
+use crate::bookmark::Bookmarks;
+use crate::image::RgbaImage;
+use crate::raster;
+use crate::replay::Input;
+
+/// The fastest speed, in generations per second, the controls go up to.
+pub const MAX_FPS: u32 = 240;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    /// A key that types a character, such as `' '` for the space bar or
+    /// `'1'` for a digit, keypad ones included.
+    Char(char),
+    Escape,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Button {
+    Left,
+    Right,
+}
+
+/// Something that happened to the window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowEvent {
+    Key {
+        key: Key,
+        ctrl: bool,
+    },
+    /// A mouse button was pressed at `(x, y)` pixels into the window.
+    Click {
+        button: Button,
+        x: i32,
+        y: i32,
+    },
+    Resize {
+        width: usize,
+        height: usize,
+    },
+    /// Part of the window needs drawing again.
+    Expose,
+    /// The window was asked to close.
+    Close,
+}
+
+/// A window that shows whole frames drawn in software and reports input.
+pub trait Surface {
+    /// The events since the last call, without waiting for more.
+    fn poll_events(&mut self) -> Result<Vec<WindowEvent>, String>;
+
+    /// Shows a frame, drawn at the window's current size.
+    fn present(&mut self, image: &RgbaImage) -> Result<(), String>;
+
+    fn set_title(&mut self, title: &str) -> Result<(), String>;
+}
+
+/// What the window loop should do about an event.
+#[derive(Debug, PartialEq)]
+pub enum Control {
+    None,
+    Quit,
+    /// Step one generation, though paused.
+    Step,
+    Redraw,
+    /// Apply and record an edit, as the SDL2 frontend would.
+    Input(Input),
+}
+
+/// The state the window controls change directly, and the mapping from
+/// events to what they do. The keys match the SDL2 frontend's: `Space`
+/// pauses, `.` steps while paused, `+` / `-` double and halve the speed,
+/// `[` / `]` shorten and lengthen the trails, digits restore bookmarks and
+/// `Ctrl`+digits save them, left click toggles a cell, right click poisons
+/// it, and `Esc` or `q` quits. While replaying, only the viewing controls
+/// work.
+pub struct Controls {
+    pub paused: bool,
+    pub fps: u32,
+    pub width: usize,
+    pub height: usize,
+    pub replaying: bool,
+}
+
+impl Controls {
+    pub fn new(width: usize, height: usize, fps: u32, replaying: bool) -> Self {
+        Controls {
+            paused: false,
+            fps,
+            width,
+            height,
+            replaying,
+        }
+    }
+
+    /// Decides what an event does, pausing, resizing or changing the speed
+    /// here as it goes; the speed only changes once its `Input` is applied.
+    pub fn handle(&mut self, event: &WindowEvent, board: &Board, bookmarks: &Bookmarks) -> Control {
+        let input = match event {
+            WindowEvent::Close
+            | WindowEvent::Key {
+                key: Key::Escape | Key::Char('q'),
+                ..
+            } => return Control::Quit,
+            WindowEvent::Key {
+                key: Key::Char(' '),
+                ..
+            } => {
+                self.paused = !self.paused;
+                return Control::Redraw;
+            }
+            WindowEvent::Key {
+                key: Key::Char('.'),
+                ..
+            } if self.paused => return Control::Step,
+            WindowEvent::Resize { width, height } => {
+                (self.width, self.height) = (*width, *height);
+                return Control::Redraw;
+            }
+            WindowEvent::Expose => return Control::Redraw,
+            _ if self.replaying => None,
+            WindowEvent::Key {
+                key: Key::Char('+' | '='),
+                ..
+            } => Some(Input::Speed((self.fps * 2).min(MAX_FPS))),
+            WindowEvent::Key {
+                key: Key::Char('-'),
+                ..
+            } => Some(Input::Speed((self.fps / 2).max(1))),
+            WindowEvent::Key {
+                key: Key::Char(bracket @ ('[' | ']')),
+                ..
+            } => {
+                let direction = if *bracket == '[' { -1 } else { 1 };
+                let trail_length = board.trail_length as i64 + direction;
+
+                Some(Input::TrailLength(
+                    trail_length.clamp(0, MAX_TRAIL_LENGTH as i64) as usize,
+                ))
+            }
+            WindowEvent::Key {
+                key: Key::Char(digit @ '0'..='9'),
+                ctrl,
+            } => {
+                let slot = *digit as usize - '0' as usize;
+
+                if *ctrl {
+                    Some(Input::SaveBookmark(slot))
+                } else {
+                    bookmarks
+                        .is_saved(slot)
+                        .then_some(Input::RestoreBookmark(slot))
+                }
+            }
+            WindowEvent::Click { button, x, y } => {
+                raster::fit_cell(board, self.width, self.height, (*x, *y)).map(
+                    |(x, y)| match button {
+                        Button::Left => Input::Toggle { x, y },
+                        Button::Right => Input::Poison { x, y },
+                    },
+                )
+            }
+            WindowEvent::Key { .. } => None,
+        };
+
+        input.map_or(Control::None, Control::Input)
+    }
+}
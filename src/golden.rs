@@ -0,0 +1,82 @@
+//! Patterns with well-known histories, for checking end to end that rule and
+//! engine changes still simulate Life correctly. Each one records the
+//! population the pattern is known to have after some number of
+//! generations on a board big enough that it never touches the edge.
+
+use crate::board::Board;
+use crate::engine::Engine;
+
+/// A pattern and the population it must have at `generation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Golden {
+    pub name: &'static str,
+    /// The pattern as RLE, including the rule it runs under.
+    pub rle: &'static str,
+    /// Side length of the square board it runs on.
+    pub size: usize,
+    pub generation: usize,
+    pub population: usize,
+}
+
+pub const GOLDEN_PATTERNS: &[Golden] = &[
+    Golden {
+        name: "glider keeps its five cells",
+        rle: "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!",
+        size: 16,
+        generation: 8,
+        population: 5,
+    },
+    Golden {
+        name: "pulsar's largest phase",
+        rle: "x = 13, y = 13, rule = B3/S23\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+        size: 32,
+        generation: 2,
+        population: 72,
+    },
+    Golden {
+        name: "pentadecathlon after one period",
+        rle: "x = 10, y = 3, rule = B3/S23\n2bo4bo$2ob4ob2o$2bo4bo!",
+        size: 32,
+        generation: 15,
+        population: 12,
+    },
+    Golden {
+        name: "diehard just before it dies",
+        rle: "x = 8, y = 3, rule = B3/S23\n6bo$2o$bo3b3o!",
+        size: 64,
+        generation: 129,
+        population: 2,
+    },
+    Golden {
+        name: "diehard dies at generation 130",
+        rle: "x = 8, y = 3, rule = B3/S23\n6bo$2o$bo3b3o!",
+        size: 64,
+        generation: 130,
+        population: 0,
+    },
+    Golden {
+        name: "Gosper glider gun after emitting its first glider",
+        rle: "x = 36, y = 9, rule = B3/S23\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+        size: 64,
+        generation: 30,
+        population: 41,
+    },
+    Golden {
+        name: "R-pentomino stabilizes at 116 cells",
+        rle: "x = 3, y = 3, rule = B3/S23\nb2o$2o$bo!",
+        size: 640,
+        generation: 1103,
+        population: 116,
+    },
+];
+
+impl Golden {
+    /// The pattern on its board, ready to step.
+    pub fn board(&self, engine: Engine) -> Result<Board, String> {
+        let mut board = Board::from_rle(self.rle, self.size, self.size)?;
+
+        board.engine = engine;
+
+        Ok(board)
+    }
+}
@@ -0,0 +1,227 @@
+use super::render::to_texture;
+use super::text::{draw_text, LINE_HEIGHT};
+use game_of_life::catalog::CatalogEntry;
+use game_of_life::raster::render_pattern;
+use game_of_life::{Catalog, Category, Config, Pattern};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+const PANEL_WIDTH: u32 = 360;
+const TEXT_SIZE: i32 = 2;
+const MARGIN: i32 = 12;
+const THUMBNAIL_SIZE: u32 = 160;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 230);
+const TEXT: Color = Color::RGB(230, 230, 230);
+const HEADING: Color = Color::RGB(255, 190, 80);
+const HIGHLIGHT: Color = Color::RGB(70, 90, 160);
+
+/// What the panel wants done after handling an event.
+pub enum PanelAction {
+    None,
+    Close,
+    /// The user picked a pattern to stamp.
+    Choose(CatalogEntry),
+}
+
+/// A searchable list of catalog patterns drawn over the left side of the
+/// window, with a thumbnail of the selected one.
+pub struct CatalogPanel {
+    query: String,
+    selected: usize,
+}
+
+enum Line<'a> {
+    Heading(Category),
+    Entry(usize, &'a CatalogEntry),
+}
+
+impl CatalogPanel {
+    pub fn new() -> Self {
+        CatalogPanel {
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event, catalog: &Catalog) -> PanelAction {
+        let match_count = catalog.search(&self.query).len();
+
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } => return PanelAction::Close,
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => self.selected = self.selected.saturating_sub(1),
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => self.selected = (self.selected + 1).min(match_count.saturating_sub(1)),
+            Event::KeyDown {
+                keycode: Some(Keycode::Backspace),
+                ..
+            } => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } => {
+                if let Some(entry) = catalog.search(&self.query).get(self.selected) {
+                    return PanelAction::Choose((*entry).clone());
+                }
+            }
+            Event::TextInput { text, .. } => {
+                self.query.push_str(text);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+
+        PanelAction::None
+    }
+
+    pub fn render(
+        &self,
+        canvas: &mut Canvas<Window>,
+        catalog: &Catalog,
+        config: &Config,
+    ) -> Result<(), String> {
+        let (_, height) = canvas.output_size()?;
+        let matches = catalog.search(&self.query);
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(Rect::new(0, 0, PANEL_WIDTH, height))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        draw_text(
+            canvas,
+            (MARGIN, MARGIN),
+            TEXT_SIZE,
+            &format!("Search: {}_", self.query),
+            TEXT,
+        )?;
+
+        let mut lines = Vec::new();
+        let mut category = None;
+
+        for (index, entry) in matches.iter().enumerate() {
+            if category != Some(entry.category) {
+                category = Some(entry.category);
+                lines.push(Line::Heading(entry.category));
+            }
+
+            lines.push(Line::Entry(index, entry));
+        }
+
+        let list_top = MARGIN + line_height * 2;
+        let list_bottom = height as i32 - THUMBNAIL_SIZE as i32 - MARGIN * 2;
+        let visible_lines = ((list_bottom - list_top) / line_height).max(1) as usize;
+        let selected_line = lines
+            .iter()
+            .position(|line| matches!(line, Line::Entry(index, _) if *index == self.selected))
+            .unwrap_or(0);
+        let first_line = selected_line
+            .saturating_sub(visible_lines / 2)
+            .min(lines.len().saturating_sub(visible_lines));
+
+        for (row, line) in lines
+            .iter()
+            .skip(first_line)
+            .take(visible_lines)
+            .enumerate()
+        {
+            let y = list_top + row as i32 * line_height;
+
+            match line {
+                Line::Heading(category) => {
+                    draw_text(canvas, (MARGIN, y), TEXT_SIZE, category.name(), HEADING)?
+                }
+                Line::Entry(index, entry) => {
+                    if *index == self.selected {
+                        canvas.set_draw_color(HIGHLIGHT);
+                        canvas.fill_rect(Rect::new(
+                            MARGIN / 2,
+                            y - TEXT_SIZE * 2,
+                            PANEL_WIDTH - MARGIN as u32,
+                            line_height as u32,
+                        ))?;
+                    }
+
+                    draw_text(
+                        canvas,
+                        (MARGIN * 2, y),
+                        TEXT_SIZE,
+                        &entry.pattern.name,
+                        TEXT,
+                    )?;
+                }
+            }
+        }
+
+        if let Some(entry) = matches.get(self.selected) {
+            draw_thumbnail(
+                canvas,
+                &entry.pattern,
+                config,
+                Rect::new(
+                    MARGIN,
+                    height as i32 - THUMBNAIL_SIZE as i32 - MARGIN,
+                    THUMBNAIL_SIZE,
+                    THUMBNAIL_SIZE,
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws the pattern in the theme, scaled to fit inside `area`.
+fn draw_thumbnail(
+    canvas: &mut Canvas<Window>,
+    pattern: &Pattern,
+    config: &Config,
+    area: Rect,
+) -> Result<(), String> {
+    let (width, height) = pattern.size();
+    let cell_size = area.width() as usize / width.max(height).max(1);
+    let image = render_pattern(pattern, config, cell_size);
+    let background = config.theme.background;
+
+    canvas.set_draw_color(Color::RGB(background.r, background.g, background.b));
+    canvas.fill_rect(area)?;
+
+    if image.width == 0 || image.height == 0 {
+        return Ok(());
+    }
+
+    let scale = (area.width() as f64 / image.width as f64)
+        .min(area.height() as f64 / image.height as f64)
+        .min(1.0);
+    let texture_creator = canvas.texture_creator();
+    let texture = to_texture(&texture_creator, &image)?;
+
+    canvas.copy(
+        &texture,
+        None,
+        Rect::new(
+            area.x(),
+            area.y(),
+            ((image.width as f64 * scale) as u32).max(1),
+            ((image.height as f64 * scale) as u32).max(1),
+        ),
+    )?;
+
+    Ok(())
+}
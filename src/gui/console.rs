@@ -0,0 +1,143 @@
+use super::text::{draw_text, LINE_HEIGHT};
+use game_of_life::ScriptCommand;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+const TEXT_SIZE: i32 = 2;
+const MARGIN: i32 = 12;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 230);
+const TEXT: Color = Color::RGB(230, 230, 230);
+const ERROR: Color = Color::RGB(255, 110, 110);
+
+/// What the console wants done after handling an event.
+pub enum ConsoleAction {
+    None,
+    Close,
+    Run(ScriptCommand),
+}
+
+/// A command bar along the bottom of the window, opened with `:`. It takes
+/// the same commands as `--script-actions`, like `rule B36/S23` or
+/// `goto 10000`; `Enter` runs one, `Up` / `Down` walk back through earlier
+/// ones and `Esc` closes it.
+pub struct ConsolePanel {
+    text: String,
+    history: Vec<String>,
+    /// How far back through the history `Up` has gone.
+    recalled: Option<usize>,
+    error: Option<String>,
+}
+
+impl ConsolePanel {
+    /// An empty console that remembers the `history` of commands already run.
+    pub fn new(history: Vec<String>) -> Self {
+        ConsolePanel {
+            text: String::new(),
+            history,
+            recalled: None,
+            error: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> ConsoleAction {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } => return ConsoleAction::Close,
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => self.recall(self.recalled.map_or(0, |back| back + 1)),
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => match self.recalled {
+                Some(0) | None => {
+                    self.recalled = None;
+                    self.text.clear();
+                }
+                Some(back) => self.recall(back - 1),
+            },
+            Event::KeyDown {
+                keycode: Some(Keycode::Backspace),
+                ..
+            } => {
+                self.text.pop();
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } if self.text.trim().is_empty() => return ConsoleAction::Close,
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } => match ScriptCommand::parse(&self.text) {
+                Ok(command) => return ConsoleAction::Run(command),
+                Err(error) => self.error = Some(error),
+            },
+            Event::TextInput { text, .. } => {
+                self.text.push_str(text);
+                self.error = None;
+            }
+            _ => {}
+        }
+
+        ConsoleAction::None
+    }
+
+    /// The command being typed, to remember once it has run.
+    pub fn text(&self) -> &str {
+        self.text.trim()
+    }
+
+    /// Shows why the command couldn't be run, keeping it to be fixed.
+    pub fn fail(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+        let (width, height) = canvas.output_size()?;
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+        let lines = if self.error.is_some() { 2 } else { 1 };
+        let bar_height = line_height * lines + MARGIN;
+        let top = height as i32 - bar_height;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(Rect::new(0, top, width, bar_height as u32))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        if let Some(error) = &self.error {
+            draw_text(canvas, (MARGIN, top + MARGIN / 2), TEXT_SIZE, error, ERROR)?;
+        }
+
+        draw_text(
+            canvas,
+            (MARGIN, height as i32 - line_height - MARGIN / 2),
+            TEXT_SIZE,
+            &format!(":{}_", self.text),
+            TEXT,
+        )?;
+
+        Ok(())
+    }
+
+    /// Fills in the command `back` places before the latest one.
+    fn recall(&mut self, back: usize) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let back = back.min(self.history.len() - 1);
+
+        self.recalled = Some(back);
+        self.text = self.history[self.history.len() - 1 - back].clone();
+        self.error = None;
+    }
+}
@@ -0,0 +1,79 @@
+use super::hud;
+use super::viewport::Viewport;
+use crate::cli::Options;
+use game_of_life::demo::{DemoScene, DEMO_SCENES};
+use game_of_life::{info, Board};
+
+/// Drives the `--demo` attract mode: loops through `DEMO_SCENES`, swapping in
+/// each scene's board when the previous one has run its course, moving the
+/// camera and captioning the scene.
+pub struct Demo {
+    scene: usize,
+    /// Generation the current scene started at.
+    started_at: usize,
+    seed: u64,
+}
+
+impl Demo {
+    /// Starts the tour on the first scene.
+    pub fn start(board: &mut Board, options: &Options) -> Self {
+        let mut demo = Demo {
+            scene: DEMO_SCENES.len() - 1,
+            started_at: 0,
+            seed: options.seed.unwrap_or_default(),
+        };
+
+        demo.next(board, options);
+
+        demo
+    }
+
+    /// Moves on to the next scene once the current one is over. Returns
+    /// whether the board was replaced.
+    pub fn update(&mut self, board: &mut Board, options: &Options) -> bool {
+        if board.generation - self.started_at < self.current().generations {
+            return false;
+        }
+
+        self.next(board, options);
+
+        true
+    }
+
+    /// Points the camera at the middle of the board, zoomed for how far
+    /// through the scene it is.
+    pub fn aim(&self, viewport: &mut Viewport, board: &Board) {
+        let scene = self.current();
+        let progress = (board.generation - self.started_at) as f32 / scene.generations as f32;
+
+        viewport.camera.zoom = scene.zoom_at(progress);
+        viewport.center_on((board.width as i32 / 2, board.height as i32 / 2));
+    }
+
+    pub fn draw_caption(&self, viewport: &mut Viewport) -> Result<(), String> {
+        hud::draw_caption(viewport, self.current().caption)
+    }
+
+    fn current(&self) -> &'static DemoScene {
+        &DEMO_SCENES[self.scene]
+    }
+
+    /// Replaces the board with the next scene's. The generation counter
+    /// keeps counting, as it does for bookmarks.
+    fn next(&mut self, board: &mut Board, options: &Options) {
+        self.scene = (self.scene + 1) % DEMO_SCENES.len();
+        self.seed = self.seed.wrapping_add(1);
+
+        let scene = self.current();
+        let mut next = scene.board(options.board_width, options.board_height, self.seed);
+
+        info!("demo: {}", scene.caption);
+
+        next.generation = board.generation;
+        next.engine = options.engine;
+        next.topology = options.topology;
+        next.set_trail_length(options.config.trail_length);
+        self.started_at = board.generation;
+        *board = next;
+    }
+}
@@ -0,0 +1,52 @@
+//! Pasted URLs are downloaded on a thread of their own, so a slow server
+//! doesn't freeze the window while curl waits on it.
+
+use game_of_life::{Input, Pattern};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// A pattern download under way, to be pasted at `cell` when it arrives.
+pub struct Download {
+    url: String,
+    cell: (i32, i32),
+    receiver: Receiver<Result<Pattern, String>>,
+}
+
+impl Download {
+    pub fn start(url: &str, cell: (i32, i32)) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread_url = url.to_string();
+
+        thread::spawn(move || {
+            let _ = sender.send(Pattern::load(&thread_url));
+        });
+
+        Download {
+            url: url.to_string(),
+            cell,
+            receiver,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The paste once the download has finished, or `None` while it is
+    /// still going.
+    pub fn poll(&self) -> Option<Result<Input, String>> {
+        let (x, y) = self.cell;
+
+        match self.receiver.try_recv() {
+            Ok(pattern) => Some(pattern.map(|pattern| Input::Paste {
+                cells: pattern.cells,
+                x,
+                y,
+            })),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(format!("the download of {} stopped", self.url)))
+            }
+        }
+    }
+}
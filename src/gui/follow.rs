@@ -0,0 +1,97 @@
+use super::viewport::Viewport;
+use game_of_life::{Board, Pattern};
+
+/// Cells of room kept around the live cells when zooming out to fit them.
+const MARGIN: usize = 8;
+
+/// Whether and how the camera keeps the live cells in view.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Follow {
+    #[default]
+    Off,
+    /// Centers on the average position of the live cells, which glides
+    /// along with a lone spaceship.
+    Centroid,
+    /// Centers on the live cells' bounding box and zooms out whenever it
+    /// outgrows the window, so growth and escaping debris stay in view.
+    BoundingBox,
+    /// Fits the bounding box to the window, zooming in as well as out, for
+    /// watching a tiny seed grow to fill the screen.
+    Fit,
+}
+
+impl Follow {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Follow::Off => "off",
+            Follow::Centroid => "centroid",
+            Follow::BoundingBox => "bounding box",
+            Follow::Fit => "fit",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Follow::Off => Follow::Centroid,
+            Follow::Centroid => Follow::BoundingBox,
+            Follow::BoundingBox | Follow::Fit => Follow::Off,
+        }
+    }
+
+    /// Pans, and for the bounding box modes zooms, the camera onto the live
+    /// cells. Leaves it alone when following is off or nothing is alive.
+    pub fn aim(self, viewport: &mut Viewport, board: &Board) {
+        if self == Follow::Off {
+            return;
+        }
+
+        let live = Pattern::new("live cells", board.live_cells());
+
+        if live.cells.is_empty() {
+            return;
+        }
+
+        match self {
+            Follow::Off => {}
+            Follow::Fit => fit(viewport, &live),
+            Follow::Centroid => {
+                let count = live.cells.len() as i64;
+                let (sum_x, sum_y) = live.cells.iter().fold((0, 0), |(sum_x, sum_y), (x, y)| {
+                    (sum_x + *x as i64, sum_y + *y as i64)
+                });
+
+                viewport.center_on(((sum_x / count) as i32, (sum_y / count) as i32));
+            }
+            Follow::BoundingBox => {
+                let (left, top) = live.origin();
+                let (width, height) = live.size();
+                let fit = viewport.zoom_to_fit(width + 2 * MARGIN, height + 2 * MARGIN);
+
+                viewport.camera.zoom = viewport.camera.zoom.min(fit);
+                viewport.center_on((left + width as i32 / 2, top + height as i32 / 2));
+            }
+        }
+    }
+}
+
+/// Centers the camera on the board's live cells and zooms to fit them with
+/// a margin. Returns false, leaving the camera alone, if nothing is alive.
+pub fn fit_live_cells(viewport: &mut Viewport, board: &Board) -> bool {
+    let live = Pattern::new("live cells", board.live_cells());
+
+    if live.cells.is_empty() {
+        return false;
+    }
+
+    fit(viewport, &live);
+
+    true
+}
+
+fn fit(viewport: &mut Viewport, live: &Pattern) {
+    let (left, top) = live.origin();
+    let (width, height) = live.size();
+
+    viewport.camera.zoom = viewport.zoom_to_fit(width + 2 * MARGIN, height + 2 * MARGIN);
+    viewport.center_on((left + width as i32 / 2, top + height as i32 / 2));
+}
@@ -0,0 +1,59 @@
+use game_of_life::Board;
+
+/// How strongly a cell that was alive on the last step is tinted, as a
+/// fraction of the way from the background to the live color.
+pub const HISTORY_TINT: f32 = 0.2;
+
+/// Every cell that has been alive, for drawing the full trajectory of
+/// spaceships and debris. It is kept by the renderer and never affects the
+/// simulation: each recorded cell starts at full intensity when alive and,
+/// with a decay set, slowly fades once dead.
+pub struct CellHistory {
+    width: usize,
+    height: usize,
+    intensities: Vec<f32>,
+}
+
+impl CellHistory {
+    pub fn new() -> Self {
+        CellHistory {
+            width: 0,
+            height: 0,
+            intensities: Vec::new(),
+        }
+    }
+
+    /// Adds the board's live cells, fading the rest by `decay`. A board of
+    /// a different size starts the history afresh.
+    pub fn record(&mut self, board: &Board, decay: f32) {
+        if (self.width, self.height) != (board.width, board.height) {
+            self.width = board.width;
+            self.height = board.height;
+            self.intensities = vec![0.0; board.cells.len()];
+        }
+
+        for (intensity, cell) in self.intensities.iter_mut().zip(board.cells.iter()) {
+            *intensity = if cell.is_alive() {
+                1.0
+            } else {
+                (*intensity - decay).max(0.0)
+            };
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.intensities
+            .iter_mut()
+            .for_each(|intensity| *intensity = 0.0);
+    }
+
+    /// How visible the cell's history is, from 0.0 (never alive or faded
+    /// away) to 1.0.
+    pub fn intensity(&self, index: usize) -> f32 {
+        self.intensities.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.intensities.capacity() * std::mem::size_of::<f32>()
+    }
+}
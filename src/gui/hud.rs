@@ -0,0 +1,292 @@
+use super::text::{draw_text, text_width, GLYPH_HEIGHT};
+use super::viewport::Viewport;
+use game_of_life::clock::format_duration;
+use game_of_life::{SimulationClock, StatsHistory};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+/// Cells between ruler ticks.
+const TICK_SPACING: i32 = 10;
+const TEXT_SIZE: i32 = 2;
+const RULER_WIDTH: u32 = 24;
+const TICK_LENGTH: u32 = 8;
+/// Pixels between ticks needed to fit a label such as `-1000`.
+const MIN_LABEL_SPACING: f32 = 72.0;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 200);
+const FOREGROUND: Color = Color::RGB(230, 230, 230);
+const BIRTHS: Color = Color::RGB(80, 220, 80);
+const DEATHS: Color = Color::RGB(240, 80, 80);
+const SPARKLINE_WIDTH: u32 = 240;
+const SPARKLINE_HEIGHT: u32 = 32;
+const MARGIN: i32 = 8;
+
+/// Shows the board coordinates of the cell under the mouse in the bottom
+/// left corner.
+pub fn draw_coordinates(viewport: &mut Viewport, cell: (i32, i32)) -> Result<(), String> {
+    let canvas = &mut viewport.canvas;
+    let (_, height) = canvas.output_size()?;
+    let text = format!("{}, {}", cell.0, cell.1);
+    let padding = TEXT_SIZE * 2;
+    let box_height = (GLYPH_HEIGHT * TEXT_SIZE + padding * 2) as u32;
+    let top = height as i32 - box_height as i32;
+
+    canvas.set_scale(1.0, 1.0)?;
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(BACKGROUND);
+    canvas.fill_rect(Rect::new(
+        0,
+        top,
+        (text_width(&text, TEXT_SIZE) + padding * 2) as u32,
+        box_height,
+    ))?;
+    canvas.set_blend_mode(BlendMode::None);
+    draw_text(
+        canvas,
+        (padding, top + padding),
+        TEXT_SIZE,
+        &text,
+        FOREGROUND,
+    )?;
+
+    Ok(())
+}
+
+/// Shows a caption centered along the top edge.
+pub fn draw_caption(viewport: &mut Viewport, text: &str) -> Result<(), String> {
+    let canvas = &mut viewport.canvas;
+    let (width, _) = canvas.output_size()?;
+    let padding = TEXT_SIZE * 4;
+    let text_width = text_width(text, TEXT_SIZE);
+    let left = (width as i32 - text_width) / 2 - padding;
+
+    canvas.set_scale(1.0, 1.0)?;
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(BACKGROUND);
+    canvas.fill_rect(Rect::new(
+        left,
+        MARGIN,
+        (text_width + padding * 2) as u32,
+        (GLYPH_HEIGHT * TEXT_SIZE + padding * 2) as u32,
+    ))?;
+    canvas.set_blend_mode(BlendMode::None);
+    draw_text(
+        canvas,
+        (left + padding, MARGIN + padding),
+        TEXT_SIZE,
+        text,
+        FOREGROUND,
+    )?;
+
+    Ok(())
+}
+
+/// Shows the generation and population with sparklines of births and deaths
+/// per generation in the bottom right corner. Both sparklines share a scale
+/// so their heights can be compared.
+pub fn draw_stats(
+    viewport: &mut Viewport,
+    history: &StatsHistory,
+    clock: &SimulationClock,
+) -> Result<(), String> {
+    let latest = match history.latest() {
+        Some(latest) => *latest,
+        None => return Ok(()),
+    };
+    let canvas = &mut viewport.canvas;
+    let (width, height) = canvas.output_size()?;
+    let line_height = (GLYPH_HEIGHT + 3) * TEXT_SIZE;
+    let box_width = SPARKLINE_WIDTH + MARGIN as u32 * 2;
+    let box_height = (line_height * 5 + SPARKLINE_HEIGHT as i32 * 2 + MARGIN * 4) as u32;
+    let left = width as i32 - box_width as i32;
+    let top = height as i32 - box_height as i32;
+    let peak = history
+        .samples()
+        .map(|stats| stats.births.max(stats.deaths))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    canvas.set_scale(1.0, 1.0)?;
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(BACKGROUND);
+    canvas.fill_rect(Rect::new(left, top, box_width, box_height))?;
+    canvas.set_blend_mode(BlendMode::None);
+
+    let left = left + MARGIN;
+    let mut y = top + MARGIN;
+
+    draw_text(
+        canvas,
+        (left, y),
+        TEXT_SIZE,
+        &format!("gen {}  pop {}", latest.generation, latest.population),
+        FOREGROUND,
+    )?;
+    y += line_height;
+    draw_text(
+        canvas,
+        (left, y),
+        TEXT_SIZE,
+        &format!(
+            "dens {:.3} chg {:.3} ent {:.2}",
+            latest.density, latest.change_rate, latest.entropy
+        ),
+        FOREGROUND,
+    )?;
+    y += line_height;
+    draw_text(
+        canvas,
+        (left, y),
+        TEXT_SIZE,
+        &format!(
+            "sim {}  run {}",
+            format_duration(clock.simulated()),
+            format_duration(clock.run_time())
+        ),
+        FOREGROUND,
+    )?;
+    y += line_height;
+
+    let births: Vec<usize> = history.samples().map(|stats| stats.births).collect();
+    let deaths: Vec<usize> = history.samples().map(|stats| stats.deaths).collect();
+
+    for (label, count, color, values) in [
+        ("births", latest.births, BIRTHS, &births),
+        ("deaths", latest.deaths, DEATHS, &deaths),
+    ] {
+        draw_text(
+            canvas,
+            (left, y),
+            TEXT_SIZE,
+            &format!("{} {}", label, count),
+            color,
+        )?;
+        y += line_height;
+
+        draw_sparkline(canvas, (left, y), values, peak, color)?;
+        y += SPARKLINE_HEIGHT as i32 + MARGIN;
+    }
+
+    Ok(())
+}
+
+/// Draws values as a line graph with `peak` at the top, newest on the right.
+fn draw_sparkline(
+    canvas: &mut Canvas<Window>,
+    (left, top): (i32, i32),
+    values: &[usize],
+    peak: usize,
+    color: Color,
+) -> Result<(), String> {
+    let step = SPARKLINE_WIDTH as f32 / values.len().saturating_sub(1).max(1) as f32;
+    let points: Vec<Point> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let height = *value as f32 / peak as f32 * SPARKLINE_HEIGHT as f32;
+
+            Point::new(
+                left + (index as f32 * step) as i32,
+                top + SPARKLINE_HEIGHT as i32 - height as i32,
+            )
+        })
+        .collect();
+
+    canvas.set_draw_color(color);
+    canvas.draw_lines(points.as_slice())?;
+
+    Ok(())
+}
+
+/// Draws rulers along the top and left edges with a tick every
+/// `TICK_SPACING` cells, labelled when there is room.
+pub fn draw_ruler(viewport: &mut Viewport) -> Result<(), String> {
+    let scale = viewport.scale();
+    let camera = (viewport.camera.x, viewport.camera.y);
+    let canvas = &mut viewport.canvas;
+    let (width, height) = canvas.output_size()?;
+    let tick_pixels = TICK_SPACING as f32 * scale;
+    let labelled = tick_pixels >= MIN_LABEL_SPACING;
+
+    canvas.set_scale(1.0, 1.0)?;
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(BACKGROUND);
+    canvas.fill_rects(&[
+        Rect::new(0, 0, width, RULER_WIDTH),
+        Rect::new(0, RULER_WIDTH as i32, RULER_WIDTH, height - RULER_WIDTH),
+    ])?;
+    canvas.set_blend_mode(BlendMode::None);
+
+    // Ticks closer than a few pixels apart would merge into a solid bar.
+    if tick_pixels < 4.0 {
+        return Ok(());
+    }
+
+    let ticks = |start: i32, pixels: u32| {
+        let first = start.div_euclid(TICK_SPACING) * TICK_SPACING;
+        let last = start + (pixels as f32 / scale).ceil() as i32;
+
+        (first..=last)
+            .step_by(TICK_SPACING as usize)
+            .map(move |cell| (cell, ((cell - start) as f32 * scale) as i32))
+    };
+
+    for (cell, x) in ticks(camera.0, width) {
+        if x < RULER_WIDTH as i32 {
+            continue;
+        }
+
+        canvas.set_draw_color(FOREGROUND);
+        canvas.fill_rect(Rect::new(
+            x,
+            (RULER_WIDTH - TICK_LENGTH) as i32,
+            1,
+            TICK_LENGTH,
+        ))?;
+
+        if labelled {
+            draw_text(
+                canvas,
+                (x + TEXT_SIZE, TEXT_SIZE * 2),
+                TEXT_SIZE,
+                &cell.to_string(),
+                FOREGROUND,
+            )?;
+        }
+    }
+
+    for (cell, y) in ticks(camera.1, height) {
+        if y < RULER_WIDTH as i32 {
+            continue;
+        }
+
+        canvas.set_draw_color(FOREGROUND);
+        canvas.fill_rect(Rect::new(
+            (RULER_WIDTH - TICK_LENGTH) as i32,
+            y,
+            TICK_LENGTH,
+            1,
+        ))?;
+
+        if labelled {
+            // The left ruler is too narrow for numbers across, so write them
+            // down the ruler one digit per line.
+            for (line, digit) in cell.to_string().chars().enumerate() {
+                draw_text(
+                    canvas,
+                    (
+                        TEXT_SIZE,
+                        y + TEXT_SIZE + line as i32 * (GLYPH_HEIGHT + 1) * TEXT_SIZE,
+                    ),
+                    TEXT_SIZE,
+                    &digit.to_string(),
+                    FOREGROUND,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
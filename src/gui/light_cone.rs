@@ -0,0 +1,74 @@
+use super::text::draw_text;
+use super::viewport::Viewport;
+use game_of_life::{Board, Pattern};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+const COLOR: Color = Color::RGB(255, 160, 0);
+const TEXT_SIZE: i32 = 2;
+
+/// The farthest the starting pattern's influence can have reached. Each
+/// cell only sees its immediate neighbors, so nothing travels faster than
+/// one cell per generation (the speed of light, `c`), and everything outside
+/// the pattern's bounding box grown by a cell per generation must still be
+/// as it was. Spaceships and growth are drawn against this boundary to show
+/// how close they get to the limit.
+pub struct LightCone {
+    /// Leftmost, topmost, rightmost and bottommost cells of the pattern.
+    bounds: (i32, i32, i32, i32),
+    generation: usize,
+}
+
+impl LightCone {
+    /// A light cone starting from the board's live cells, or `None` if there
+    /// are none.
+    pub fn new(board: &Board) -> Option<Self> {
+        let pattern = Pattern::new("light cone", board.live_cells());
+        let (left, top) = pattern.origin();
+        let (width, height) = pattern.size();
+
+        if pattern.cells.is_empty() {
+            return None;
+        }
+
+        Some(LightCone {
+            bounds: (left, top, left + width as i32 - 1, top + height as i32 - 1),
+            generation: board.generation,
+        })
+    }
+
+    /// Outlines the boundary at `generation`, labelled with the number of
+    /// generations it has grown for.
+    pub fn draw(&self, viewport: &mut Viewport, generation: usize) -> Result<(), String> {
+        let elapsed = generation.saturating_sub(self.generation);
+        let reach = elapsed as i32;
+        let (left, top, right, bottom) = self.bounds;
+        let scale = viewport.scale();
+        let camera = (viewport.camera.x, viewport.camera.y);
+        let to_pixels = |cell: i32, camera: i32| ((cell - camera) as f32 * scale) as i32;
+        let (x, y) = (
+            to_pixels(left - reach, camera.0),
+            to_pixels(top - reach, camera.1),
+        );
+        let canvas = &mut viewport.canvas;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_draw_color(COLOR);
+        canvas.draw_rect(Rect::new(
+            x,
+            y,
+            (to_pixels(right + reach + 1, camera.0) - x).max(1) as u32,
+            (to_pixels(bottom + reach + 1, camera.1) - y).max(1) as u32,
+        ))?;
+
+        draw_text(
+            canvas,
+            (x + TEXT_SIZE * 2, y + TEXT_SIZE * 2),
+            TEXT_SIZE,
+            &format!("c +{}", elapsed),
+            COLOR,
+        )?;
+
+        Ok(())
+    }
+}
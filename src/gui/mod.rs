@@ -0,0 +1,1374 @@
+mod catalog_panel;
+mod console;
+mod demo;
+mod download;
+mod follow;
+mod history;
+mod hud;
+mod light_cone;
+mod mode;
+mod profiler;
+mod render;
+mod rule_panel;
+mod sandbox;
+mod settings_panel;
+mod slice;
+mod state;
+mod tabs;
+mod text;
+mod viewport;
+mod workspace_panel;
+
+use crate::cli::Options;
+use crate::headless::MAX_PERIOD;
+use crate::output::{Pipe, RunSummary, StatsFile, Timelapse};
+use crate::{export_rle, initial_board, load_playback, resource_field, save_population_chart};
+use catalog_panel::CatalogPanel;
+use console::ConsolePanel;
+use demo::Demo;
+use download::Download;
+use follow::Follow;
+use game_of_life::brush::{line, rectangle};
+use game_of_life::catalog::CatalogEntry;
+use game_of_life::memory::{format_bytes, MemoryReport};
+use game_of_life::rule::RULE_PRESETS;
+use game_of_life::theme::THEME_PRESETS;
+use game_of_life::{
+    debug, info, net, png, rle, svg, warn, Board, Bookmarks, Brush, BrushShape, Catalog, Category,
+    ChunkIndex, Config, GenerationStats, Input, Launcher, Pattern, PeriodDetector, Periodicity,
+    PopulationHistory, Region, Replay, RgbaImage, Rule, ScriptCommand, Session, SimulationClock,
+    Smoothing, StatsHistory, Theme, Tool, Workspace, MAX_TRAIL_LENGTH,
+};
+use history::CellHistory;
+use light_cone::LightCone;
+use mode::{AppMode, KeyAction, KeyContext};
+use profiler::{FrameTimes, Profiler};
+use render::{draw_prediction, render, View};
+use rule_panel::RulePanel;
+use sandbox::{drag_region, Sandbox};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::VideoSubsystem;
+use settings_panel::{Setting, SettingsPanel};
+use state::{Gui, KeyOutcome};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tabs::Tabs;
+use viewport::{Camera, Viewport};
+use workspace_panel::WorkspacePanel;
+
+pub use slice::run_3d;
+
+const DEFAULT_FPS: u32 = 60;
+pub(crate) use game_of_life::frontend::MAX_FPS;
+/// Frame rate used while smoothing, so animations stay fluid even when the
+/// simulation runs at only a few generations per second.
+const RENDER_FPS: u32 = 60;
+const OVERVIEW_SIZE: u32 = 400;
+/// Fraction of the visible area the arrow keys pan by.
+const PAN_FRACTION: f32 = 0.1;
+const ZOOM_STEP: f32 = 1.25;
+/// Generations of births and deaths shown in the stats sparklines.
+const STATS_HISTORY: usize = 240;
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+/// Square board sizes offered by the settings panel.
+const BOARD_SIZES: [usize; 6] = [100, 200, 400, 800, 1600, 3200];
+
+/// Advances the board one generation, returning which cells were alive
+/// beforehand. Time spent counting neighbors and stepping is added to
+/// `times`.
+fn advance(board: &mut Board, times: &mut FrameTimes) -> Vec<bool> {
+    let previously_alive = board.alive_mask();
+
+    let started_at = Instant::now();
+    board.update_live_neighbor_counts();
+    times.counting += started_at.elapsed();
+
+    let started_at = Instant::now();
+    board.step();
+    times.stepping += started_at.elapsed();
+
+    previously_alive
+}
+
+/// Translates a window event into a recordable input, if it is one.
+fn event_to_input(
+    event: &Event,
+    board: &Board,
+    bookmarks: &Bookmarks,
+    brush: &mut Brush,
+    cell: (i32, i32),
+    fps: u32,
+    config: &Config,
+) -> Option<Input> {
+    let (x, y) = cell;
+
+    match event {
+        Event::MouseButtonDown {
+            mouse_btn: MouseButton::Left,
+            ..
+        } if brush.shape == BrushShape::Cell => {
+            brush.anchor = Some(cell);
+            Some(Input::Toggle { x, y })
+        }
+        Event::MouseButtonDown {
+            mouse_btn: MouseButton::Left,
+            ..
+        } => Some(Input::Paste {
+            cells: brush.press(cell),
+            x,
+            y,
+        }),
+        Event::MouseMotion { mousestate, .. }
+            if mousestate.left() && brush.shape != BrushShape::Cell =>
+        {
+            Some(Input::Paste {
+                cells: brush.drag(cell)?,
+                x,
+                y,
+            })
+        }
+        Event::MouseButtonDown {
+            mouse_btn: MouseButton::Right,
+            ..
+        } => Some(Input::Poison { x, y }),
+        Event::KeyDown {
+            keycode: Some(Keycode::G),
+            ..
+        } => Some(Input::Stamp {
+            pattern: "glider".to_string(),
+            x,
+            y,
+        }),
+        Event::KeyDown {
+            keycode: Some(Keycode::I),
+            ..
+        } => Some(Input::Paste {
+            cells: brush.burst(config.noise_radius, config.noise_density),
+            x,
+            y,
+        }),
+        Event::KeyDown {
+            keycode: Some(Keycode::Equals),
+            ..
+        }
+        | Event::KeyDown {
+            keycode: Some(Keycode::KpPlus),
+            ..
+        } => Some(change_speed(fps, 1)),
+        Event::KeyDown {
+            keycode: Some(Keycode::Minus),
+            ..
+        }
+        | Event::KeyDown {
+            keycode: Some(Keycode::KpMinus),
+            ..
+        } => Some(change_speed(fps, -1)),
+        Event::KeyDown {
+            keycode: Some(keycode),
+            keymod,
+            ..
+        } if bookmark_slot(*keycode).is_some() => {
+            let slot = bookmark_slot(*keycode)?;
+
+            if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD | Mod::LGUIMOD | Mod::RGUIMOD) {
+                Some(Input::SaveBookmark(slot))
+            } else if bookmarks.is_saved(slot) {
+                Some(Input::RestoreBookmark(slot))
+            } else {
+                None
+            }
+        }
+        Event::KeyDown {
+            keycode: Some(Keycode::LeftBracket),
+            ..
+        } => Some(change_trail_length(board, -1)),
+        Event::KeyDown {
+            keycode: Some(Keycode::RightBracket),
+            ..
+        } => Some(change_trail_length(board, 1)),
+        _ => None,
+    }
+}
+
+/// The bookmark slot a number key selects.
+fn bookmark_slot(keycode: Keycode) -> Option<usize> {
+    let digits = [
+        Keycode::Num0,
+        Keycode::Num1,
+        Keycode::Num2,
+        Keycode::Num3,
+        Keycode::Num4,
+        Keycode::Num5,
+        Keycode::Num6,
+        Keycode::Num7,
+        Keycode::Num8,
+        Keycode::Num9,
+    ];
+
+    digits.iter().position(|digit| *digit == keycode)
+}
+
+/// Doubles (`direction` 1) or halves (-1) the speed.
+fn change_speed(fps: u32, direction: i32) -> Input {
+    if direction > 0 {
+        Input::Speed((fps * 2).min(MAX_FPS))
+    } else {
+        Input::Speed((fps / 2).max(1))
+    }
+}
+
+fn change_trail_length(board: &Board, direction: i32) -> Input {
+    let trail_length = board.trail_length as i64 + direction as i64;
+
+    Input::TrailLength(trail_length.clamp(0, MAX_TRAIL_LENGTH as i64) as usize)
+}
+
+/// Steps a setting from the settings panel to its previous or next value.
+/// Changes to the simulation are returned as inputs so they can be
+/// recorded; display settings are changed directly.
+fn adjust_setting(
+    setting: Setting,
+    direction: i32,
+    board: &Board,
+    fps: u32,
+    view: &mut View,
+    config: &mut Config,
+) -> Option<Input> {
+    match setting {
+        Setting::Speed => return Some(change_speed(fps, direction)),
+        Setting::TrailLength => return Some(change_trail_length(board, direction)),
+        Setting::BoardSize => {
+            let size = if direction > 0 {
+                BOARD_SIZES.iter().find(|size| **size > board.width)
+            } else {
+                BOARD_SIZES.iter().rev().find(|size| **size < board.width)
+            };
+
+            return size.map(|size| Input::Resize {
+                width: *size,
+                height: *size,
+            });
+        }
+        Setting::Smoothing => view.smoothing = view.smoothing.next(),
+        Setting::DiffView => view.diff_mode = !view.diff_mode,
+        Setting::Ruler => view.ruler = !view.ruler,
+        Setting::Theme => {
+            let next = match theme_preset(config) {
+                Some(index) => {
+                    (index as i32 + direction).rem_euclid(THEME_PRESETS.len() as i32) as usize
+                }
+                None => 0,
+            };
+
+            config.theme = Theme::preset(THEME_PRESETS[next]).expect("presets are valid");
+        }
+        Setting::Rule => {
+            let next = match rule_preset(board.rule) {
+                Some(index) => {
+                    (index as i32 + direction).rem_euclid(RULE_PRESETS.len() as i32) as usize
+                }
+                None => 0,
+            };
+
+            return Some(Input::Rule(
+                Rule::parse(RULE_PRESETS[next]).expect("presets are valid"),
+            ));
+        }
+        Setting::LoadPattern => {}
+    }
+
+    None
+}
+
+/// Index in `RULE_PRESETS` of the rule, if it is a preset.
+fn rule_preset(rule: Rule) -> Option<usize> {
+    RULE_PRESETS
+        .iter()
+        .position(|name| Rule::parse(name) == Ok(rule))
+}
+
+/// Index in `THEME_PRESETS` of the config's theme, if it is a preset.
+fn theme_preset(config: &Config) -> Option<usize> {
+    THEME_PRESETS
+        .iter()
+        .position(|name| Theme::preset(name).ok().as_ref() == Some(&config.theme))
+}
+
+/// The current value of each setting, in `Setting::ALL` order.
+fn setting_values(board: &Board, fps: u32, view: &View, config: &Config) -> Vec<String> {
+    let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+
+    Setting::ALL
+        .iter()
+        .map(|setting| match setting {
+            Setting::Speed => format!("{} fps", fps),
+            Setting::TrailLength => board.trail_length.to_string(),
+            Setting::Smoothing => view.smoothing.name().to_string(),
+            Setting::DiffView => on_off(view.diff_mode),
+            Setting::Ruler => on_off(view.ruler),
+            Setting::Theme => theme_preset(config)
+                .map_or("custom", |index| THEME_PRESETS[index])
+                .to_string(),
+            Setting::BoardSize => format!("{}x{}", board.width, board.height),
+            Setting::Rule => board.rule.name(),
+            Setting::LoadPattern => String::new(),
+        })
+        .collect()
+}
+
+fn is_paste(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::KeyDown {
+            keycode: Some(Keycode::V),
+            keymod,
+            ..
+        } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD | Mod::LGUIMOD | Mod::RGUIMOD)
+    )
+}
+
+fn is_left_click(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::MouseButtonDown {
+            mouse_btn: MouseButton::Left,
+            ..
+        }
+    )
+}
+
+/// Whether the event presses, releases or drags with the left button.
+fn is_left_button(event: &Event) -> bool {
+    match event {
+        Event::MouseButtonDown { mouse_btn, .. } | Event::MouseButtonUp { mouse_btn, .. } => {
+            *mouse_btn == MouseButton::Left
+        }
+        Event::MouseMotion { mousestate, .. } => mousestate.left(),
+        _ => false,
+    }
+}
+
+/// Builds an input drawing a line from the brush's anchor, the cell last
+/// clicked or painted, to `cell`.
+fn line_input(brush: &mut Brush, cell: (i32, i32)) -> Option<Input> {
+    let from = brush.anchor.replace(cell).unwrap_or(cell);
+
+    Some(Input::Paste {
+        cells: line(from, cell),
+        x: from.0,
+        y: from.1,
+    })
+}
+
+/// The cells a drag from `from` to `to` places with a rectangle or launcher
+/// tool, relative to `from`.
+fn drag_cells(
+    tool: Tool,
+    launcher: &Launcher,
+    from: (i32, i32),
+    to: (i32, i32),
+) -> Vec<(i32, i32)> {
+    match tool {
+        Tool::Launcher => launcher.aim((to.0 - from.0, to.1 - from.1)),
+        _ => rectangle(from, to, tool == Tool::FilledRectangle),
+    }
+}
+
+/// Starts selecting when the left button is pressed and builds an input
+/// placing the tool's cells when the button is released.
+fn drag_input(
+    event: &Event,
+    tool: Tool,
+    launcher: &Launcher,
+    mode: &mut AppMode,
+    cell: (i32, i32),
+) -> Option<Input> {
+    match event {
+        Event::MouseButtonDown { .. } => {
+            mode.select(cell);
+            None
+        }
+        Event::MouseButtonUp { .. } => {
+            let from = match mode {
+                AppMode::Selecting { from, .. } => *from,
+                _ => return None,
+            };
+
+            mode.finish();
+
+            Some(Input::Paste {
+                cells: drag_cells(tool, launcher, from, cell),
+                x: from.0,
+                y: from.1,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Starts selecting when the left button is pressed and returns the region
+/// selected when it is released, e.g. to sandbox or freeze.
+fn region_drag(event: &Event, mode: &mut AppMode, cell: (i32, i32)) -> Option<Region> {
+    match event {
+        Event::MouseButtonDown { .. } => {
+            mode.select(cell);
+            None
+        }
+        Event::MouseButtonUp { .. } => {
+            let from = match mode {
+                AppMode::Selecting { from, .. } => *from,
+                _ => return None,
+            };
+
+            mode.finish();
+
+            Some(drag_region(from, cell))
+        }
+        _ => None,
+    }
+}
+
+/// Builds an input placing the pattern on the clipboard at `cell`. The
+/// clipboard may hold RLE text, or a URL to fetch it from, which starts a
+/// `download` that is pasted when it arrives. Problems are reported on
+/// stderr rather than ending the session.
+fn paste_input(
+    video_subsystem: &VideoSubsystem,
+    cell: (i32, i32),
+    download: &mut Option<Download>,
+) -> Option<Input> {
+    let text = video_subsystem.clipboard().clipboard_text().ok()?;
+    let text = text.trim();
+
+    if net::is_url(text) {
+        match download {
+            Some(download) => warn!("still downloading {}", download.url()),
+            None => {
+                info!("downloading {}", text);
+                *download = Some(Download::start(text, cell));
+            }
+        }
+
+        return None;
+    }
+
+    match rle::decode(text) {
+        Ok(pattern) => Some(Input::Paste {
+            cells: pattern.cells,
+            x: cell.0,
+            y: cell.1,
+        }),
+        Err(error) => {
+            warn!("failed to paste pattern: {}", error);
+            None
+        }
+    }
+}
+
+/// Builds an input placing a catalog pattern at `cell`. Built-in patterns are
+/// recorded by name; user patterns and stamps stepped to a later phase are
+/// recorded cell by cell so replays don't depend on the pattern directory or
+/// on re-running the pattern.
+fn stamp_input(
+    entry: &CatalogEntry,
+    phase: usize,
+    phased: &[(i32, i32)],
+    cell: (i32, i32),
+) -> Input {
+    let (x, y) = cell;
+
+    match entry.category {
+        _ if phase > 0 => Input::Paste {
+            cells: phased.to_vec(),
+            x,
+            y,
+        },
+        Category::User => Input::Paste {
+            cells: entry.pattern.cells.clone(),
+            x,
+            y,
+        },
+        _ => Input::Stamp {
+            pattern: entry.key.clone(),
+            x,
+            y,
+        },
+    }
+}
+
+/// The built-in patterns plus those in the configured pattern directory.
+fn load_catalog(options: &Options) -> Result<Catalog, String> {
+    let mut catalog = Catalog::embedded();
+
+    if let Some(directory) = &options.config.pattern_dir {
+        catalog.load_directory(directory)?;
+    }
+
+    Ok(catalog)
+}
+
+fn apply_input(
+    board: &mut Board,
+    bookmarks: &mut Bookmarks,
+    fps: &mut u32,
+    input: &Input,
+) -> Result<(), String> {
+    debug!("generation {}: {:?}", board.generation, input);
+
+    if let Input::Speed(new_fps) = input {
+        *fps = *new_fps;
+    }
+
+    input.apply(board, bookmarks)
+}
+
+/// Opens a second window showing the whole board, for keeping an overview
+/// while the main window is zoomed in on a detail.
+fn open_overview(video_subsystem: &VideoSubsystem, board: &Board) -> Result<Viewport, String> {
+    let mut overview = Viewport::new(
+        video_subsystem,
+        "Rusty Game of Life (overview)",
+        OVERVIEW_SIZE,
+        OVERVIEW_SIZE,
+        Camera {
+            x: 0,
+            y: 0,
+            zoom: 1.0,
+        },
+    )?;
+
+    overview.camera.zoom = overview.zoom_to_fit(board.width, board.height);
+
+    Ok(overview)
+}
+
+/// Pans or zooms the camera of the viewport an event was sent to.
+pub(crate) fn move_camera(viewport: &mut Viewport, event: &Event) {
+    let (width, height) = viewport.canvas.window().drawable_size();
+    let step_x = ((width as f32 / viewport.scale()) * PAN_FRACTION).max(1.0) as i32;
+    let step_y = ((height as f32 / viewport.scale()) * PAN_FRACTION).max(1.0) as i32;
+    let camera = &mut viewport.camera;
+
+    match event {
+        Event::KeyDown {
+            keycode: Some(Keycode::Left),
+            ..
+        } => camera.x -= step_x,
+        Event::KeyDown {
+            keycode: Some(Keycode::Right),
+            ..
+        } => camera.x += step_x,
+        Event::KeyDown {
+            keycode: Some(Keycode::Up),
+            ..
+        } => camera.y -= step_y,
+        Event::KeyDown {
+            keycode: Some(Keycode::Down),
+            ..
+        } => camera.y += step_y,
+        Event::MouseWheel { y, .. } => camera.zoom_by(ZOOM_STEP.powi(*y)),
+        _ => {}
+    }
+}
+
+/// Shows the loaded pattern's name, author and first comment along with the
+/// period detector's findings in the window title.
+fn set_title(
+    viewport: &mut Viewport,
+    pattern: Option<&Pattern>,
+    periodicity: Option<&Periodicity>,
+    tabs: &Tabs,
+) -> Result<(), String> {
+    let mut title = "Rusty Game of Life".to_string();
+
+    if tabs.len() > 1 {
+        title.push_str(&format!(" [tab {} of {}]", tabs.active() + 1, tabs.len()));
+    }
+
+    if let Some(pattern) = pattern {
+        title.push_str(&format!(" - {}", pattern.title()));
+
+        if let Some(comment) = pattern.comments.first() {
+            title.push_str(&format!(" ({})", comment));
+        }
+    }
+
+    if let Some(periodicity) = periodicity {
+        title.push_str(&format!(" - {} (w to save as RLE)", periodicity.describe()));
+    }
+
+    viewport
+        .canvas
+        .window_mut()
+        .set_title(&title)
+        .map_err(|error| error.to_string())
+}
+
+/// The loaded pattern, which only the first tab started from.
+fn tab_pattern<'a>(options: &'a Options, tabs: &Tabs) -> Option<&'a Pattern> {
+    options.pattern.as_ref().filter(|_| tabs.active() == 0)
+}
+
+/// An empty board the size of `board` with the same rules and settings, for
+/// a new tab.
+fn blank_board(board: &Board, options: &Options) -> Board {
+    let mut blank = Board::empty(board.width, board.height);
+
+    blank.set_trail_length(board.trail_length);
+    blank.topology = board.topology;
+    blank.rule = board.rule;
+    blank.weighted_rule = board.weighted_rule;
+    blank.poison_chance = board.poison_chance;
+    blank.dying_policy = board.dying_policy;
+    blank.resources = resource_field(options, &blank);
+    blank.engine = board.engine;
+
+    blank
+}
+
+/// Writes the board to an RLE file in the working directory, named after
+/// the detected pattern if there is one.
+fn save_rle(
+    board: &Board,
+    pattern: Option<&Pattern>,
+    periodicity: Option<&Periodicity>,
+) -> Result<(), String> {
+    let path = match periodicity {
+        Some(periodicity) if periodicity.is_spaceship() => format!(
+            "spaceship-p{}-gen{}.rle",
+            periodicity.period, board.generation
+        ),
+        Some(periodicity) => format!(
+            "oscillator-p{}-gen{}.rle",
+            periodicity.period, board.generation
+        ),
+        None => format!("board-gen{}.rle", board.generation),
+    };
+
+    info!("saving board to {}", path);
+
+    fs::write(&path, export_rle(board, pattern, periodicity))
+        .map_err(|error| format!("failed to write {}: {}", path, error))
+}
+
+/// The board and settings saved in the session file if resuming, otherwise
+/// the usual starting board. Points out a saved session that isn't being
+/// used.
+fn start_session(options: &Options, seed: Option<u64>) -> Result<(Board, Session), String> {
+    if options.resume {
+        let (mut board, session) = Session::load(&options.session)?;
+
+        board.weighted_rule = options.config.weighted_rule;
+        board.resources = resource_field(options, &board);
+
+        return Ok((board, session));
+    }
+
+    if Path::new(&options.session).exists() {
+        println!(
+            "a saved session exists at {}; run with --resume to restore it",
+            options.session
+        );
+    }
+
+    let session = Session {
+        camera: (0, 0, 1.0),
+        fps: DEFAULT_FPS,
+        smoothing: options.config.smoothing,
+    };
+
+    Ok((initial_board(options, seed), session))
+}
+
+fn memory_report(
+    board: &Board,
+    detector: &PeriodDetector,
+    stats_history: &StatsHistory,
+    recording: &Replay,
+    bookmarks: &Bookmarks,
+    history: &CellHistory,
+    tabs: &Tabs,
+) -> MemoryReport {
+    let mut report = MemoryReport::default();
+
+    report.add("board", board.memory_usage());
+    report.add("period history", detector.memory_usage());
+    report.add("stats history", stats_history.memory_usage());
+    report.add("recording", recording.memory_usage());
+    report.add("bookmarks", bookmarks.memory_usage());
+    report.add("cell history", history.memory_usage());
+    report.add("hidden tabs", tabs.memory_usage());
+
+    report
+}
+
+/// The commands the `--script-actions` file gives for a generation.
+fn script_commands(options: &Options, generation: usize) -> Vec<ScriptCommand> {
+    options
+        .script
+        .iter()
+        .flat_map(|script| script.actions_at(generation))
+        .map(|action| action.command.clone())
+        .collect()
+}
+
+/// Saves what the canvas has drawn so far, overlays included, as a PNG.
+fn save_screenshot(canvas: &Canvas<Window>, path: &str) -> Result<(), String> {
+    let (width, height) = canvas.output_size()?;
+    let pixels = canvas.read_pixels(None, PixelFormatEnum::RGBA32)?;
+    let image = RgbaImage {
+        width: width as usize,
+        height: height as usize,
+        pixels,
+    };
+
+    info!("saving screenshot to {}", path);
+
+    png::save(path, &image)
+}
+
+/// The camera, speed and smoothing to save with the board.
+fn current_session(camera: &Camera, fps: u32, view: &View) -> Session {
+    Session {
+        camera: (camera.x, camera.y, camera.zoom),
+        fps,
+        smoothing: view.smoothing,
+    }
+}
+
+/// Saves the session, reporting failures rather than ending it.
+fn save_session(path: &str, board: &Board, camera: &Camera, fps: u32, view: &View) {
+    if let Err(error) = current_session(camera, fps, view).save(board, path) {
+        warn!("{}", error);
+    }
+}
+
+/// Everything the workspace menu saves, copied from the running session.
+fn snapshot_workspace(
+    board: &Board,
+    session: Session,
+    config: &Config,
+    bookmarks: &Bookmarks,
+    stats_history: &StatsHistory,
+) -> Workspace {
+    Workspace {
+        board: board.clone(),
+        session,
+        theme: config.theme.clone(),
+        bookmarks: bookmarks.clone(),
+        stats: stats_history.samples().copied().collect(),
+    }
+}
+
+/// Replaces the board, theme, bookmarks and stats history with the
+/// workspace's, returning the camera, speed and smoothing it was saved with.
+/// The board keeps running on the engine and config given on the command
+/// line.
+fn restore_workspace(
+    workspace: Workspace,
+    options: &Options,
+    board: &mut Board,
+    config: &mut Config,
+    bookmarks: &mut Bookmarks,
+    stats_history: &mut StatsHistory,
+) -> Session {
+    *board = workspace.board;
+    board.engine = options.engine;
+    board.weighted_rule = options.config.weighted_rule;
+    board.poison_chance = options.config.poison_chance;
+    board.dying_policy = options.config.dying_policy;
+    board.resources = resource_field(options, board);
+    config.theme = workspace.theme;
+    *bookmarks = workspace.bookmarks;
+    *stats_history = StatsHistory::new(STATS_HISTORY);
+
+    for stats in workspace.stats {
+        stats_history.record(stats);
+    }
+
+    workspace.session
+}
+
+/// Writes the board to an SVG file in the working directory, with the dying
+/// trail if `include_trail` is set.
+fn save_svg(board: &Board, config: &Config, include_trail: bool) -> Result<(), String> {
+    let path = format!("board-gen{}.svg", board.generation);
+
+    info!("saving board to {}", path);
+
+    fs::write(&path, svg::encode(board, config, include_trail))
+        .map_err(|error| format!("failed to write {}: {}", path, error))
+}
+
+pub fn run(options: &Options) -> Result<(), String> {
+    let (playback, seed) = load_playback(options)?;
+    let (mut board, session) = start_session(options, seed)?;
+    let (camera_x, camera_y, zoom) = session.camera;
+
+    let sdl_context = sdl2::init()?;
+    let video_subsystem = sdl_context.video()?;
+    let mut viewports = vec![Viewport::new(
+        &video_subsystem,
+        "Rusty Game of Life",
+        options.window_width as u32,
+        options.window_height as u32,
+        Camera {
+            x: camera_x,
+            y: camera_y,
+            zoom,
+        },
+    )?];
+    let mut event_pump = sdl_context.event_pump()?;
+    let mut fps = session.fps;
+    let mut view = View {
+        diff_mode: false,
+        smoothing: session.smoothing,
+        ruler: false,
+        stats: true,
+        history: false,
+        light_cone: false,
+        resources: false,
+        prediction: false,
+    };
+    let mut config = options.config.clone();
+    let mut last_step_at = Instant::now();
+    let detector = PeriodDetector::new(MAX_PERIOD);
+    let periodicity = None;
+    let mut stats_history = StatsHistory::new(STATS_HISTORY);
+    let mut stats_file = StatsFile::create(options)?;
+    let mut timelapse = Timelapse::create(options)?;
+    let mut pipe = Pipe::spawn(options)?;
+    let population_history = PopulationHistory::new();
+    let mut summary = RunSummary::start("gui", &board);
+    let brush = Brush::new(
+        config.brush_radius,
+        config.brush_density,
+        seed.unwrap_or_default(),
+    );
+    let mut memory = MemoryReport::default();
+    let mut over_budget = false;
+    let mut clock = SimulationClock::start();
+    let mut last_saved_at = Instant::now();
+    let catalog = load_catalog(options)?;
+    let mode = AppMode::Running;
+    let mut bookmarks = Bookmarks::new();
+    let console_history = Vec::new();
+    let tool = Tool::default();
+    let launcher = Launcher::new(
+        Pattern::builtin("glider").expect("glider is built in"),
+        board.rule,
+    );
+    let profiler = Profiler::new();
+    let text_input = video_subsystem.text_input();
+
+    // Text input is only wanted while typing a search, otherwise the key that
+    // opens the catalog would also be typed into it.
+    text_input.stop();
+
+    let workspace_name = options.workspace.clone();
+
+    if let Some(name) = &workspace_name {
+        let session = restore_workspace(
+            Workspace::load(&options.workspace_dir, name)?,
+            options,
+            &mut board,
+            &mut config,
+            &mut bookmarks,
+            &mut stats_history,
+        );
+
+        (
+            viewports[0].camera.x,
+            viewports[0].camera.y,
+            viewports[0].camera.zoom,
+        ) = session.camera;
+        fps = session.fps;
+        view.smoothing = session.smoothing;
+    }
+
+    let mut demo = options.demo.then(|| Demo::start(&mut board, options));
+    let sandbox: Option<Sandbox> = None;
+    let mut download: Option<Download> = None;
+    let follow = Follow::default();
+    let tabs = Tabs::new();
+    let previously_alive = board.alive_mask();
+    let mut history = CellHistory::new();
+    let light_cone = LightCone::new(&board);
+    let mut chunks = ChunkIndex::new(&board);
+    let mut board_touched = false;
+    // The next step's births and deaths, while paused with the prediction
+    // shown.
+    let mut prediction = None;
+
+    if let Some(replay) = &playback {
+        replay.settings.apply(&mut board)?;
+    }
+
+    let mut recording = Replay::recording(seed, &board);
+    let started_at = Instant::now();
+
+    history.record(&board, 0.0);
+    recording.record(0, 0, Input::TrailLength(board.trail_length));
+    set_title(&mut viewports[0], tab_pattern(options, &tabs), None, &tabs)?;
+
+    if let Some(replay) = &playback {
+        for event in replay.events_at(0) {
+            apply_input(&mut board, &mut bookmarks, &mut fps, &event.input)?;
+        }
+    }
+
+    let mut gui = Gui {
+        options,
+        video_subsystem,
+        text_input,
+        viewports,
+        board,
+        bookmarks,
+        fps,
+        view,
+        config,
+        mode,
+        tool,
+        brush,
+        follow,
+        tabs,
+        sandbox,
+        profiler,
+        detector,
+        periodicity,
+        stats_history,
+        population_history,
+        history,
+        light_cone,
+        previously_alive,
+        scripted_generation: None,
+        console_history,
+        typed_commands: Vec::new(),
+        goto_target: None,
+        pending_screenshot: None,
+        workspace_name,
+        catalog,
+        launcher,
+        playback,
+        recording,
+        started_at,
+    };
+
+    'running: loop {
+        let step_interval = Duration::from_secs(1) / gui.fps;
+        let smoothing = gui.view.smoothing != Smoothing::Off && gui.fps < RENDER_FPS;
+        let mut frame_times = FrameTimes::default();
+
+        // Script actions run before stepping, and a screenshot holds the step
+        // back a frame, so it shows the generation it was asked for.
+        gui.run_script();
+
+        // Steps normally come one at a time, but a `goto` takes as many as
+        // fit in a frame, stopping early for generations a script acts on.
+        let goto_deadline = Instant::now() + Duration::from_secs(1) / RENDER_FPS;
+
+        loop {
+            let going = gui.pending_screenshot.is_none()
+                && gui
+                    .goto_target
+                    .is_some_and(|target| gui.board.generation < target);
+
+            if !going
+                && (gui.mode.is_paused()
+                    || gui.pending_screenshot.is_some()
+                    || (smoothing && last_step_at.elapsed() < step_interval))
+            {
+                break;
+            }
+
+            gui.previously_alive = advance(&mut gui.board, &mut frame_times);
+            last_step_at = Instant::now();
+            clock.tick(step_interval);
+            gui.history.record(&gui.board, gui.config.history_decay);
+
+            if gui.tabs.background {
+                gui.tabs.step_hidden();
+            }
+
+            if let Some(demo) = &mut demo {
+                if demo.update(&mut gui.board, options) {
+                    gui.restart_tracking();
+                }
+            }
+
+            let stats = GenerationStats::measure(&gui.board, &gui.previously_alive);
+
+            if let Some(stats_file) = &mut stats_file {
+                stats_file.write(&stats)?;
+            }
+
+            if let Some(timelapse) = &mut timelapse {
+                timelapse.capture(&gui.board, &gui.config)?;
+            }
+
+            if let Some(pipe) = &mut pipe {
+                pipe.write(&gui.board)?;
+            }
+
+            gui.population_history.record(&gui.board);
+            summary.record(stats.population);
+            gui.stats_history.record(stats);
+            memory = memory_report(
+                &gui.board,
+                &gui.detector,
+                &gui.stats_history,
+                &gui.recording,
+                &gui.bookmarks,
+                &gui.history,
+                &gui.tabs,
+            );
+
+            match gui.config.memory_budget {
+                Some(budget) if memory.total() > budget => {
+                    if !over_budget {
+                        warn!(
+                            "using {} of memory, over the {} budget; trimming history",
+                            format_bytes(memory.total()),
+                            format_bytes(budget)
+                        );
+                    }
+
+                    over_budget = true;
+                    gui.detector.trim();
+                    gui.stats_history.trim();
+                }
+                _ => over_budget = false,
+            }
+
+            let detected = gui.detector.observe(&gui.board);
+
+            if detected != gui.periodicity {
+                gui.periodicity = detected;
+                set_title(
+                    &mut gui.viewports[0],
+                    tab_pattern(options, &gui.tabs),
+                    gui.periodicity.as_ref(),
+                    &gui.tabs,
+                )?;
+            }
+
+            gui.replay_events()?;
+
+            if !going
+                || Instant::now() >= goto_deadline
+                || !script_commands(options, gui.board.generation).is_empty()
+            {
+                break;
+            }
+        }
+
+        if gui
+            .goto_target
+            .is_some_and(|target| gui.board.generation >= target)
+        {
+            gui.goto_target = None;
+        }
+
+        let progress = if smoothing {
+            (last_step_at.elapsed().as_secs_f32() / step_interval.as_secs_f32()).min(1.0)
+        } else {
+            1.0
+        };
+
+        // Resizing the board invalidates the previous generation's mask.
+        if gui.previously_alive.len() != gui.board.cells.len() {
+            gui.previously_alive = gui.board.alive_mask();
+        }
+
+        if let Some(demo) = &demo {
+            demo.aim(&mut gui.viewports[0], &gui.board);
+        } else {
+            gui.follow.aim(&mut gui.viewports[0], &gui.board);
+        }
+
+        // Inputs and board swaps can change cells without moving the
+        // generation on, so the index is also rebuilt after any events.
+        if board_touched || !chunks.is_current(&gui.board) {
+            chunks = ChunkIndex::new(&gui.board);
+            prediction = None;
+        }
+
+        let predicting = gui.view.prediction && gui.mode.is_paused();
+
+        if predicting && prediction.is_none() {
+            prediction = Some(gui.board.predict_step());
+        }
+
+        let render_started_at = Instant::now();
+
+        for viewport in gui.viewports.iter_mut() {
+            render(
+                viewport,
+                &gui.board,
+                &chunks,
+                &gui.previously_alive,
+                &gui.history,
+                progress,
+                &gui.config,
+                &gui.view,
+            )?;
+
+            if let Some((births, deaths)) = prediction.as_ref().filter(|_| predicting) {
+                draw_prediction(viewport, &gui.board, births, deaths)?;
+            }
+        }
+
+        frame_times.rendering += render_started_at.elapsed();
+
+        let events: Vec<Event> = event_pump.poll_iter().collect();
+        // The demo swaps boards on its own, keeping the generation counting.
+        board_touched = !events.is_empty() || demo.is_some();
+        let mouse_state = event_pump.mouse_state();
+        let mouse = (mouse_state.x(), mouse_state.y());
+
+        let shift_held = sdl_context
+            .keyboard()
+            .mod_state()
+            .intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+        let hovered_cell = gui.viewports[0].cell_at(mouse);
+
+        gui.draw_overlays(hovered_cell, shift_held, demo.as_ref(), &clock, &memory)?;
+
+        if let Some(path) = gui.pending_screenshot.take() {
+            if let Err(error) = save_screenshot(&gui.viewports[0].canvas, &path) {
+                warn!("{}", error);
+            }
+        }
+
+        if let Some(pasted) = download.as_ref().and_then(Download::poll) {
+            download = None;
+
+            match pasted {
+                Ok(input) if gui.playback.is_none() => gui.edit(input)?,
+                Ok(_) => {}
+                Err(error) => warn!("failed to paste pattern: {}", error),
+            }
+        }
+
+        for event in events {
+            let primary_window_id = gui.viewports[0].window_id();
+
+            let pixel_ratio = gui.viewports[0].pixel_ratio;
+            let pixel_mouse = (
+                (mouse.0 as f32 * pixel_ratio) as i32,
+                (mouse.1 as f32 * pixel_ratio) as i32,
+            );
+
+            if gui.handle_panel_event(&event, pixel_mouse)? {
+                continue;
+            }
+
+            let key_action = match &event {
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    keymod,
+                    ..
+                } => gui.mode.key_action(
+                    *keycode,
+                    *keymod,
+                    KeyContext {
+                        sandbox: gui.sandbox.is_some(),
+                        demo: demo.is_some(),
+                        recording: options.record.is_some(),
+                        replaying: gui.playback.is_some(),
+                    },
+                ),
+                _ => KeyAction::None,
+            };
+
+            match event {
+                Event::Quit { .. } => break 'running,
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::Close,
+                    ..
+                } => {
+                    if window_id == primary_window_id {
+                        break 'running;
+                    }
+
+                    gui.viewports
+                        .retain(|viewport| viewport.window_id() != window_id);
+                }
+                _ => {}
+            }
+
+            let closed = match gui.perform(key_action)? {
+                KeyOutcome::Continue => None,
+                KeyOutcome::Quit => break 'running,
+                KeyOutcome::CloseSandbox {
+                    sandbox,
+                    write_back,
+                } => Some((sandbox, write_back)),
+            };
+
+            if let Some((open, write_back)) = closed {
+                for input in open.close(&mut gui.board, &mut gui.viewports[0], write_back) {
+                    gui.edit(input)?;
+                }
+
+                gui.scripted_generation = Some(gui.board.generation);
+                gui.restart_tracking();
+                continue;
+            }
+
+            let viewport = match gui
+                .viewports
+                .iter_mut()
+                .find(|viewport| Some(viewport.window_id()) == event.get_window_id())
+            {
+                Some(viewport) => viewport,
+                None => continue,
+            };
+
+            // While stamping, the wheel picks the held pattern's phase
+            // instead of zooming.
+            if let (AppMode::Stamping { .. }, Event::MouseWheel { y, .. }) = (&gui.mode, &event) {
+                gui.mode.shift_phase(*y, gui.board.rule);
+                continue;
+            }
+
+            move_camera(viewport, &event);
+
+            // Panels own the keyboard and mouse while they are open.
+            if gui.playback.is_some() || gui.mode.is_editing() {
+                continue;
+            }
+
+            let cell = viewport.cell_at(mouse);
+            let input = if let (
+                AppMode::Stamping {
+                    entry,
+                    phase,
+                    phased,
+                    ..
+                },
+                Event::MouseButtonDown {
+                    mouse_btn: MouseButton::Left,
+                    ..
+                },
+            ) = (&gui.mode, &event)
+            {
+                Some(stamp_input(entry, *phase, phased, cell))
+            } else if is_paste(&event) {
+                paste_input(&gui.video_subsystem, cell, &mut download)
+            } else if shift_held && is_left_click(&event) {
+                line_input(&mut gui.brush, cell)
+            } else if gui.tool == Tool::Sandbox && is_left_button(&event) {
+                match region_drag(&event, &mut gui.mode, cell) {
+                    Some(_) if gui.sandbox.is_some() || demo.is_some() => {
+                        warn!("a sandbox can't be opened from a sandbox or the demo")
+                    }
+                    Some(region) => {
+                        gui.sandbox = Some(Sandbox::open(&mut gui.board, region, viewport));
+                        gui.restart_tracking();
+                    }
+                    None => {}
+                }
+
+                None
+            } else if gui.tool == Tool::Freeze && is_left_button(&event) {
+                region_drag(&event, &mut gui.mode, cell).map(|region| {
+                    if gui.board.is_frozen(&region) {
+                        Input::Unfreeze(region)
+                    } else {
+                        Input::Freeze(region)
+                    }
+                })
+            } else if gui.tool != Tool::Brush && is_left_button(&event) {
+                drag_input(&event, gui.tool, &gui.launcher, &mut gui.mode, cell)
+            } else {
+                event_to_input(
+                    &event,
+                    &gui.board,
+                    &gui.bookmarks,
+                    &mut gui.brush,
+                    cell,
+                    gui.fps,
+                    &gui.config,
+                )
+            };
+
+            if let Some(input) = input {
+                gui.edit(input)?;
+            }
+        }
+
+        let present_started_at = Instant::now();
+
+        for viewport in gui.viewports.iter_mut() {
+            viewport.canvas.present();
+        }
+
+        frame_times.rendering += present_started_at.elapsed();
+        gui.profiler.record(frame_times);
+
+        if last_saved_at.elapsed() >= AUTOSAVE_INTERVAL {
+            let main_board = gui.sandbox.as_ref().map_or(&gui.board, Sandbox::parked);
+
+            save_session(
+                &options.session,
+                main_board,
+                &gui.viewports[0].camera,
+                gui.fps,
+                &gui.view,
+            );
+            last_saved_at = Instant::now();
+        }
+
+        let frame_interval = if smoothing {
+            Duration::from_secs(1) / RENDER_FPS
+        } else {
+            step_interval
+        };
+        ::std::thread::sleep(frame_interval);
+    }
+
+    if let Some(open) = gui.sandbox.take() {
+        open.close(&mut gui.board, &mut gui.viewports[0], false);
+    }
+
+    save_session(
+        &options.session,
+        &gui.board,
+        &gui.viewports[0].camera,
+        gui.fps,
+        &gui.view,
+    );
+
+    if let Some(path) = &options.population_chart {
+        save_population_chart(path, &gui.population_history, &gui.config)?;
+    }
+
+    if let Some(name) = &gui.workspace_name {
+        let session = current_session(&gui.viewports[0].camera, gui.fps, &gui.view);
+
+        snapshot_workspace(
+            &gui.board,
+            session,
+            &gui.config,
+            &gui.bookmarks,
+            &gui.stats_history,
+        )
+        .save(&options.workspace_dir, name)?;
+    }
+
+    if let Some(path) = &options.record {
+        gui.recording.save(path)?;
+    }
+
+    summary.finish(&gui.board, gui.periodicity.as_ref(), options)?;
+
+    Ok(())
+}
@@ -0,0 +1,408 @@
+use super::catalog_panel::CatalogPanel;
+use super::console::ConsolePanel;
+use super::rule_panel::RulePanel;
+use super::settings_panel::SettingsPanel;
+use super::workspace_panel::WorkspacePanel;
+use game_of_life::catalog::CatalogEntry;
+use game_of_life::{debug, Pattern, Rule};
+use sdl2::keyboard::{Keycode, Mod};
+
+/// A panel drawn over the board that takes the keyboard while it is open.
+pub enum Panel {
+    Catalog(CatalogPanel),
+    Settings(SettingsPanel),
+    Rule(RulePanel),
+    Workspaces(WorkspacePanel),
+    Console(ConsolePanel),
+}
+
+/// What the window is doing, which decides where input goes and which
+/// overlays are drawn. The editing, stamping and selecting modes are
+/// temporary: each remembers whether the simulation was paused underneath
+/// it and returns to running or paused when it ends.
+pub enum AppMode {
+    /// Stepping; clicks and keys edit the board with the current tool.
+    Running,
+    /// Like running, but not stepping.
+    Paused,
+    /// A panel has the keyboard, and the mouse for panels that use it.
+    Editing { panel: Panel, paused: bool },
+    /// Each left click stamps the chosen pattern, run `phase` generations
+    /// ahead on its own into the `phased` cells, until `Esc`.
+    Stamping {
+        entry: CatalogEntry,
+        phase: usize,
+        phased: Vec<(i32, i32)>,
+        paused: bool,
+    },
+    /// A rectangle or launcher drag is under way from `from`.
+    Selecting { from: (i32, i32), paused: bool },
+}
+
+/// What else the window has going on that changes what keys do.
+#[derive(Clone, Copy)]
+pub struct KeyContext {
+    pub sandbox: bool,
+    pub demo: bool,
+    pub recording: bool,
+    pub replaying: bool,
+}
+
+/// How `Ctrl+T`, `Ctrl+W` and `Ctrl+Tab` change the open tabs.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TabChange {
+    Open,
+    Close,
+    Switch(isize),
+}
+
+/// What a key press outside the panels asks for. The board editing keys
+/// aren't here; they go through `event_to_input` with the mouse.
+pub enum KeyAction {
+    None,
+    /// The key does nothing here, for the given reason.
+    Refuse(&'static str),
+    Quit,
+    /// Ends the temporary mode.
+    Finish,
+    CloseSandbox {
+        write_back: bool,
+    },
+    ToggleBackgroundTabs,
+    Tab(TabChange),
+    ToggleDiff,
+    NextSmoothing,
+    ToggleRuler,
+    SavePopulationChart,
+    ToggleStats,
+    ClearHistory,
+    ToggleHistory,
+    TogglePrediction,
+    RestartLightCone,
+    ToggleLightCone,
+    ToggleFollowFit,
+    ZoomToFit,
+    NextFollow,
+    ToggleResources,
+    ToggleProfiler,
+    SaveRle,
+    SaveSvg {
+        trail: bool,
+    },
+    OpenCatalog,
+    OpenConsole,
+    OpenSettings,
+    OpenWorkspaces,
+    OpenRule,
+    NextBrush,
+    NextSandboxEdges,
+    /// Switches tools, abandoning a drag under way with the old one.
+    NextTool {
+        end_drag: bool,
+    },
+    TogglePaused,
+    ToggleOverview,
+}
+
+impl AppMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppMode::Running => "running",
+            AppMode::Paused => "paused",
+            AppMode::Editing { .. } => "editing",
+            AppMode::Stamping { .. } => "stamping",
+            AppMode::Selecting { .. } => "selecting",
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        match self {
+            AppMode::Running => false,
+            AppMode::Paused => true,
+            AppMode::Editing { paused, .. }
+            | AppMode::Stamping { paused, .. }
+            | AppMode::Selecting { paused, .. } => *paused,
+        }
+    }
+
+    /// Pauses or resumes stepping without leaving the current mode.
+    pub fn set_paused(&mut self, paused: bool) {
+        match self {
+            AppMode::Running | AppMode::Paused => self.enter(Self::settled(paused)),
+            AppMode::Editing {
+                paused: current, ..
+            }
+            | AppMode::Stamping {
+                paused: current, ..
+            }
+            | AppMode::Selecting {
+                paused: current, ..
+            } => *current = paused,
+        }
+    }
+
+    pub fn toggle_paused(&mut self) {
+        self.set_paused(!self.is_paused());
+    }
+
+    pub fn edit(&mut self, panel: Panel) {
+        let paused = self.is_paused();
+        self.enter(AppMode::Editing { panel, paused });
+    }
+
+    pub fn stamp(&mut self, entry: CatalogEntry) {
+        let paused = self.is_paused();
+        let phased = entry.pattern.cells.clone();
+
+        self.enter(AppMode::Stamping {
+            entry,
+            phase: 0,
+            phased,
+            paused,
+        });
+    }
+
+    /// Moves the held stamp `steps` generations forward or back, never
+    /// before its catalog phase. Forward steps continue from the current
+    /// phase; backward ones rerun the pattern from the start.
+    pub fn shift_phase(&mut self, steps: i32, rule: Rule) {
+        if let AppMode::Stamping {
+            entry,
+            phase,
+            phased,
+            ..
+        } = self
+        {
+            let target = (*phase as i32 + steps).max(0) as usize;
+
+            *phased = if target >= *phase {
+                Pattern::new(&entry.pattern.name, phased.clone())
+                    .advanced(rule, target - *phase)
+                    .cells
+            } else {
+                entry.pattern.advanced(rule, target).cells
+            };
+            *phase = target;
+        }
+    }
+
+    pub fn select(&mut self, from: (i32, i32)) {
+        let paused = self.is_paused();
+        self.enter(AppMode::Selecting { from, paused });
+    }
+
+    /// Ends a temporary mode, going back to running or paused. Returns
+    /// whether there was one to end.
+    pub fn finish(&mut self) -> bool {
+        match self {
+            AppMode::Running | AppMode::Paused => false,
+            _ => {
+                self.enter(Self::settled(self.is_paused()));
+                true
+            }
+        }
+    }
+
+    /// Decides what a key press does in this mode. Panels take every key
+    /// while they are open, and `Esc` ends a temporary mode before it closes
+    /// a sandbox or the window.
+    pub fn key_action(&self, keycode: Keycode, keymod: Mod, context: KeyContext) -> KeyAction {
+        let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+        let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+
+        match (self, keycode) {
+            (AppMode::Editing { .. }, _) => KeyAction::None,
+            (AppMode::Stamping { .. } | AppMode::Selecting { .. }, Keycode::Escape) => {
+                KeyAction::Finish
+            }
+            (_, Keycode::Escape) if context.sandbox => {
+                KeyAction::CloseSandbox { write_back: false }
+            }
+            (_, Keycode::Escape) => KeyAction::Quit,
+            (_, Keycode::Return) if context.sandbox => KeyAction::CloseSandbox { write_back: true },
+            (_, Keycode::B) if ctrl => KeyAction::ToggleBackgroundTabs,
+            (_, Keycode::Tab | Keycode::T | Keycode::W) if ctrl => {
+                if context.recording || context.replaying {
+                    KeyAction::Refuse("tabs can't be used while recording or replaying")
+                } else if context.sandbox || context.demo {
+                    KeyAction::Refuse("tabs can't be used from a sandbox or the demo")
+                } else {
+                    KeyAction::Tab(match keycode {
+                        Keycode::T => TabChange::Open,
+                        Keycode::W => TabChange::Close,
+                        _ if shift => TabChange::Switch(-1),
+                        _ => TabChange::Switch(1),
+                    })
+                }
+            }
+            (_, Keycode::D) => KeyAction::ToggleDiff,
+            (_, Keycode::S) => KeyAction::NextSmoothing,
+            (_, Keycode::R) => KeyAction::ToggleRuler,
+            (_, Keycode::H) if shift => KeyAction::SavePopulationChart,
+            (_, Keycode::H) => KeyAction::ToggleStats,
+            (_, Keycode::O) if shift => KeyAction::ClearHistory,
+            (_, Keycode::O) => KeyAction::ToggleHistory,
+            (_, Keycode::U) => KeyAction::TogglePrediction,
+            (_, Keycode::C) if shift => KeyAction::RestartLightCone,
+            (_, Keycode::C) => KeyAction::ToggleLightCone,
+            (_, Keycode::Z) if shift => KeyAction::ToggleFollowFit,
+            (_, Keycode::Z) => KeyAction::ZoomToFit,
+            (_, Keycode::L) => KeyAction::NextFollow,
+            (_, Keycode::F) => KeyAction::ToggleResources,
+            (_, Keycode::F2) => KeyAction::ToggleProfiler,
+            (_, Keycode::W) => KeyAction::SaveRle,
+            (_, Keycode::E) => KeyAction::SaveSvg { trail: shift },
+            (_, Keycode::P) => KeyAction::OpenCatalog,
+            // `:` has its own key on some layouts and is `Shift+;` on
+            // others.
+            (_, Keycode::Colon) => KeyAction::OpenConsole,
+            (_, Keycode::Semicolon) if shift => KeyAction::OpenConsole,
+            (_, Keycode::Tab) => KeyAction::OpenSettings,
+            (_, Keycode::F3) if context.sandbox => {
+                KeyAction::Refuse("close the sandbox before opening workspaces")
+            }
+            (_, Keycode::F3) => KeyAction::OpenWorkspaces,
+            (_, Keycode::B) if shift => KeyAction::OpenRule,
+            (_, Keycode::B) => KeyAction::NextBrush,
+            (_, Keycode::T) if shift && context.sandbox => KeyAction::NextSandboxEdges,
+            (AppMode::Selecting { .. }, Keycode::T) => KeyAction::NextTool { end_drag: true },
+            (_, Keycode::T) => KeyAction::NextTool { end_drag: false },
+            (_, Keycode::Space) => KeyAction::TogglePaused,
+            (_, Keycode::N) => KeyAction::ToggleOverview,
+            _ => KeyAction::None,
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        matches!(self, AppMode::Editing { .. })
+    }
+
+    pub fn panel_mut(&mut self) -> Option<&mut Panel> {
+        match self {
+            AppMode::Editing { panel, .. } => Some(panel),
+            _ => None,
+        }
+    }
+
+    /// Whether the open panel takes typed text, which needs SDL text input.
+    pub fn is_typing(&self) -> bool {
+        matches!(
+            self,
+            AppMode::Editing {
+                panel: Panel::Catalog(_) | Panel::Workspaces(_) | Panel::Console(_),
+                ..
+            }
+        )
+    }
+
+    fn settled(paused: bool) -> Self {
+        if paused {
+            AppMode::Paused
+        } else {
+            AppMode::Running
+        }
+    }
+
+    fn enter(&mut self, mode: AppMode) {
+        debug!("mode: {} -> {}", self.name(), mode.name());
+        *self = mode;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::Catalog;
+
+    const IDLE: KeyContext = KeyContext {
+        sandbox: false,
+        demo: false,
+        recording: false,
+        replaying: false,
+    };
+
+    fn stamping() -> AppMode {
+        AppMode::Stamping {
+            entry: Catalog::embedded().find("glider").unwrap().clone(),
+            phase: 0,
+            phased: Vec::new(),
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn escape_ends_stamping_and_selecting_before_quitting() {
+        let selecting = AppMode::Selecting {
+            from: (0, 0),
+            paused: false,
+        };
+
+        for mode in [stamping(), selecting] {
+            assert!(matches!(
+                mode.key_action(Keycode::Escape, Mod::NOMOD, IDLE),
+                KeyAction::Finish
+            ));
+        }
+
+        assert!(matches!(
+            AppMode::Running.key_action(Keycode::Escape, Mod::NOMOD, IDLE),
+            KeyAction::Quit
+        ));
+        assert!(matches!(
+            AppMode::Running.key_action(
+                Keycode::Escape,
+                Mod::NOMOD,
+                KeyContext {
+                    sandbox: true,
+                    ..IDLE
+                }
+            ),
+            KeyAction::CloseSandbox { write_back: false }
+        ));
+    }
+
+    #[test]
+    fn tabs_are_refused_while_recording_or_replaying() {
+        for context in [
+            KeyContext {
+                recording: true,
+                ..IDLE
+            },
+            KeyContext {
+                replaying: true,
+                ..IDLE
+            },
+        ] {
+            assert!(matches!(
+                AppMode::Running.key_action(Keycode::T, Mod::LCTRLMOD, context),
+                KeyAction::Refuse(_)
+            ));
+        }
+
+        assert!(matches!(
+            AppMode::Running.key_action(Keycode::T, Mod::LCTRLMOD, IDLE),
+            KeyAction::Tab(TabChange::Open)
+        ));
+        assert!(matches!(
+            AppMode::Running.key_action(Keycode::Tab, Mod::LCTRLMOD | Mod::LSHIFTMOD, IDLE),
+            KeyAction::Tab(TabChange::Switch(-1))
+        ));
+    }
+
+    #[test]
+    fn panels_keep_the_keys_to_themselves() {
+        let editing = AppMode::Editing {
+            panel: Panel::Settings(SettingsPanel::new()),
+            paused: false,
+        };
+
+        assert!(matches!(
+            editing.key_action(Keycode::Escape, Mod::NOMOD, IDLE),
+            KeyAction::None
+        ));
+        assert!(matches!(
+            editing.key_action(Keycode::Space, Mod::NOMOD, IDLE),
+            KeyAction::None
+        ));
+    }
+}
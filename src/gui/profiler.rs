@@ -0,0 +1,162 @@
+use super::text::{draw_text, LINE_HEIGHT};
+use game_of_life::memory::{format_bytes, MemoryReport};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Frames of history shown, one bar each.
+const HISTORY: usize = 120;
+const BAR_WIDTH: u32 = 3;
+/// Pixels per millisecond of frame time.
+const PIXELS_PER_MS: f32 = 4.0;
+const GRAPH_HEIGHT: u32 = 200;
+const TEXT_SIZE: i32 = 2;
+const MARGIN: i32 = 12;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 200);
+const TEXT: Color = Color::RGB(230, 230, 230);
+const COUNTING: Color = Color::RGB(80, 160, 255);
+const STEPPING: Color = Color::RGB(255, 190, 80);
+const RENDERING: Color = Color::RGB(120, 220, 120);
+/// Reference line drawn at the frame time needed for 60 frames per second.
+const FRAME_BUDGET_MS: f32 = 1000.0 / 60.0;
+
+/// Time spent in each stage of a single frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTimes {
+    pub counting: Duration,
+    pub stepping: Duration,
+    pub rendering: Duration,
+}
+
+/// Keeps recent frame timings and draws them as stacked bars, newest on the
+/// right.
+pub struct Profiler {
+    pub visible: bool,
+    history: VecDeque<FrameTimes>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler {
+            visible: false,
+            history: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    pub fn record(&mut self, times: FrameTimes) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(times);
+    }
+
+    /// Draws the frame times along with the memory report and, if set, the
+    /// budget it is held to.
+    pub fn render(
+        &self,
+        canvas: &mut Canvas<Window>,
+        memory: &MemoryReport,
+        budget: Option<usize>,
+    ) -> Result<(), String> {
+        let (width, _) = canvas.output_size()?;
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+        let graph_width = BAR_WIDTH * HISTORY as u32;
+        let left = width as i32 - graph_width as i32 - MARGIN * 2;
+        let graph_bottom = MARGIN + GRAPH_HEIGHT as i32;
+        let average = |stage: fn(&FrameTimes) -> Duration| {
+            let total: Duration = self.history.iter().map(stage).sum();
+
+            milliseconds(total) / self.history.len().max(1) as f32
+        };
+        let legend = [
+            ("count", COUNTING, average(|times| times.counting)),
+            ("step", STEPPING, average(|times| times.stepping)),
+            ("render", RENDERING, average(|times| times.rendering)),
+        ];
+        let mut memory_lines: Vec<String> = memory
+            .components
+            .iter()
+            .map(|(name, bytes)| format!("{} {}", name, format_bytes(*bytes)))
+            .collect();
+
+        memory_lines.push(match budget {
+            Some(budget) => format!(
+                "memory {} of {}",
+                format_bytes(memory.total()),
+                format_bytes(budget)
+            ),
+            None => format!("memory {}", format_bytes(memory.total())),
+        });
+
+        let text_lines = (legend.len() + memory_lines.len()) as i32;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(Rect::new(
+            left - MARGIN,
+            0,
+            graph_width + MARGIN as u32 * 3,
+            (graph_bottom + MARGIN + line_height * text_lines) as u32,
+        ))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        for (frame, times) in self.history.iter().enumerate() {
+            let x = left + frame as i32 * BAR_WIDTH as i32;
+            let mut bottom = graph_bottom;
+
+            for (duration, color) in [
+                (times.counting, COUNTING),
+                (times.stepping, STEPPING),
+                (times.rendering, RENDERING),
+            ] {
+                let height = ((milliseconds(duration) * PIXELS_PER_MS) as i32).min(bottom - MARGIN);
+
+                if height <= 0 {
+                    continue;
+                }
+
+                bottom -= height;
+                canvas.set_draw_color(color);
+                canvas.fill_rect(Rect::new(x, bottom, BAR_WIDTH, height as u32))?;
+            }
+        }
+
+        let budget = graph_bottom - (FRAME_BUDGET_MS * PIXELS_PER_MS) as i32;
+
+        canvas.set_draw_color(TEXT);
+        canvas.fill_rect(Rect::new(left, budget, graph_width, 1))?;
+
+        for (row, (name, color, average)) in legend.iter().enumerate() {
+            draw_text(
+                canvas,
+                (left, graph_bottom + MARGIN / 2 + row as i32 * line_height),
+                TEXT_SIZE,
+                &format!("{} {:.2}ms", name, average),
+                *color,
+            )?;
+        }
+
+        let memory_top = graph_bottom + MARGIN / 2 + legend.len() as i32 * line_height;
+
+        for (row, line) in memory_lines.iter().enumerate() {
+            draw_text(
+                canvas,
+                (left, memory_top + row as i32 * line_height),
+                TEXT_SIZE,
+                line,
+                TEXT,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn milliseconds(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
@@ -0,0 +1,434 @@
+use super::history::{CellHistory, HISTORY_TINT};
+use super::viewport::Viewport;
+use game_of_life::raster::render_pattern;
+use game_of_life::trail::fade_stage;
+use game_of_life::{
+    Board, CellState, ChunkIndex, Config, Pattern, Rgb, RgbaImage, Smoothing, Theme,
+};
+use sdl2::pixels::Color;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::ops::Range;
+
+/// Opacity of stamp, line and brush previews.
+const GHOST_ALPHA: u8 = 160;
+/// Colors of exhausted and full ground in the resource overlay, and its
+/// opacity.
+const EXHAUSTED: Rgb = Rgb::new(150, 70, 20);
+const FULL: Rgb = Rgb::new(40, 170, 70);
+const RESOURCE_ALPHA: u8 = 90;
+/// Colors of the births and deaths predicted while paused.
+const PREDICTED_BIRTH: Color = Color::RGB(0, 192, 0);
+const PREDICTED_DEATH: Color = Color::RGB(224, 0, 0);
+/// Tint laid over frozen cells, and its opacity.
+const FROZEN: Rgb = Rgb::new(120, 190, 255);
+const FROZEN_ALPHA: u8 = 70;
+
+/// Display options shared by every viewport that only affect how the board is
+/// drawn.
+pub struct View {
+    pub diff_mode: bool,
+    pub smoothing: Smoothing,
+    /// Whether to draw rulers along the window edges.
+    pub ruler: bool,
+    /// Whether to show population, births and deaths.
+    pub stats: bool,
+    /// Whether to tint cells that have been alive, see `CellHistory`.
+    pub history: bool,
+    /// Whether to outline the starting pattern's light cone.
+    pub light_cone: bool,
+    /// Whether to color the board by its resource levels, when it has them.
+    pub resources: bool,
+    /// Whether to show what the next step would do while paused.
+    pub prediction: bool,
+}
+
+/// Draws the board. `chunks` indexes its occupied cells, so only those in
+/// view are visited unless the history tint needs the dead ones too.
+/// `previously_alive` holds each cell's state before the last step and
+/// `progress` how far (0.0 to 1.0) the display has advanced from that
+/// generation towards the current one.
+#[allow(clippy::too_many_arguments)]
+pub fn render(
+    viewport: &mut Viewport,
+    board: &Board,
+    chunks: &ChunkIndex,
+    previously_alive: &[bool],
+    history: &CellHistory,
+    progress: f32,
+    config: &Config,
+    view: &View,
+) -> Result<(), String> {
+    let scale = viewport.scale();
+    let camera = (viewport.camera.x, viewport.camera.y);
+    let visible_range = viewport.visible_range(board.width, board.height);
+    let visible: Vec<usize> = if view.history {
+        visible_indices(board, visible_range.clone()).collect()
+    } else {
+        chunks.occupied_in(board, visible_range.clone())
+    };
+    let canvas = &mut viewport.canvas;
+    let texture_creator = canvas.texture_creator();
+    let sprites = Sprites::new(&texture_creator, &config.theme)?;
+
+    canvas.set_scale(scale, scale)?;
+    canvas.set_draw_color(to_color(config.theme.background));
+    canvas.clear();
+
+    if view.diff_mode {
+        draw_diff(canvas, board, camera, visible.into_iter(), previously_alive)?;
+        return Ok(());
+    }
+
+    let smoothing = if progress < 1.0 {
+        view.smoothing
+    } else {
+        Smoothing::Off
+    };
+    let mut scaled_cells = Vec::new();
+
+    for index in visible {
+        let cell = &board.cells[index];
+        let color = match cell.state {
+            CellState::Alive => Some(config.theme.alive),
+            CellState::Dying(cycles_left) => Some(config.theme.dying_color(
+                &config.trail_curve,
+                cycles_left,
+                board.trail_length,
+            )),
+            CellState::Poison => Some(config.theme.poison),
+            CellState::Dead if view.history && history.intensity(index) > 0.0 => Some(
+                config
+                    .theme
+                    .background
+                    .lerp(config.theme.alive, HISTORY_TINT * history.intensity(index)),
+            ),
+            CellState::Dead => None,
+        };
+        let was_alive = previously_alive[index];
+        let born = !was_alive && cell.is_alive();
+        let died = was_alive && !cell.is_alive();
+
+        match smoothing {
+            Smoothing::Fade if born => {
+                let color = config.theme.background.lerp(config.theme.alive, progress);
+                draw_cell(canvas, board, camera, index, to_color(color))?;
+            }
+            Smoothing::Fade if died => {
+                let target = color.unwrap_or(config.theme.background);
+                let color = config.theme.alive.lerp(target, progress);
+                draw_cell(canvas, board, camera, index, to_color(color))?;
+            }
+            Smoothing::Scale if born => {
+                scaled_cells.push((index, progress, config.theme.alive));
+            }
+            Smoothing::Scale if died => {
+                if let Some(color) = color {
+                    draw_cell(canvas, board, camera, index, to_color(color))?;
+                }
+                scaled_cells.push((index, 1.0 - progress, config.theme.alive));
+            }
+            _ => match sprites.for_state(&cell.state, board.trail_length) {
+                Some(texture) => draw_sprite(canvas, board, camera, index, texture)?,
+                None => {
+                    if let Some(color) = color {
+                        draw_cell(canvas, board, camera, index, to_color(color))?;
+                    }
+                }
+            },
+        }
+    }
+
+    if !scaled_cells.is_empty() {
+        draw_scaled_cells(canvas, board, camera, scale, &scaled_cells)?;
+    }
+
+    if let Some(resources) = board.resources.as_ref().filter(|_| view.resources) {
+        let visible = visible_indices(board, viewport.visible_range(board.width, board.height));
+
+        viewport.canvas.set_blend_mode(BlendMode::Blend);
+
+        for index in visible {
+            let color = to_color(EXHAUSTED.lerp(FULL, resources.level(index)));
+
+            draw_cell(
+                &mut viewport.canvas,
+                board,
+                camera,
+                index,
+                Color::RGBA(color.r, color.g, color.b, RESOURCE_ALPHA),
+            )?;
+        }
+
+        viewport.canvas.set_blend_mode(BlendMode::None);
+    }
+
+    let frozen: Vec<usize> = chunks
+        .occupied_in(board, visible_range)
+        .into_iter()
+        .filter(|index| board.cells[*index].frozen)
+        .collect();
+
+    if !frozen.is_empty() {
+        viewport.canvas.set_blend_mode(BlendMode::Blend);
+
+        for index in frozen {
+            draw_cell(
+                &mut viewport.canvas,
+                board,
+                camera,
+                index,
+                Color::RGBA(FROZEN.r, FROZEN.g, FROZEN.b, FROZEN_ALPHA),
+            )?;
+        }
+
+        viewport.canvas.set_blend_mode(BlendMode::None);
+    }
+
+    Ok(())
+}
+
+/// The theme's cell sprites as textures. They are uploaded every frame
+/// because textures can't outlive the `TextureCreator` borrowed from the
+/// canvas; sprites are small, so this is cheap, and themes without sprites
+/// upload nothing.
+struct Sprites<'a> {
+    alive: Option<Texture<'a>>,
+    dying: Vec<Texture<'a>>,
+}
+
+impl<'a> Sprites<'a> {
+    fn new(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        theme: &Theme,
+    ) -> Result<Self, String> {
+        Ok(Sprites {
+            alive: theme
+                .alive_sprite
+                .as_ref()
+                .map(|image| to_texture(texture_creator, image))
+                .transpose()?,
+            dying: theme
+                .dying_sprites
+                .iter()
+                .map(|image| to_texture(texture_creator, image))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn for_state(&self, state: &CellState, trail_length: usize) -> Option<&Texture<'a>> {
+        match state {
+            CellState::Alive => self.alive.as_ref(),
+            CellState::Dying(cycles_left) => {
+                let stage = fade_stage(*cycles_left, trail_length, self.dying.len());
+
+                self.dying.get(stage)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub fn to_texture<'a>(
+    texture_creator: &'a TextureCreator<WindowContext>,
+    image: &RgbaImage,
+) -> Result<Texture<'a>, String> {
+    let mut texture = texture_creator
+        .create_texture_static(
+            PixelFormatEnum::RGBA32,
+            image.width as u32,
+            image.height as u32,
+        )
+        .map_err(|error| error.to_string())?;
+
+    texture
+        .update(None, &image.pixels, image.width * 4)
+        .map_err(|error| error.to_string())?;
+    texture.set_blend_mode(BlendMode::Blend);
+
+    Ok(texture)
+}
+
+/// Indices of the cells in the given columns and rows, row by row.
+fn visible_indices(
+    board: &Board,
+    (columns, rows): (Range<usize>, Range<usize>),
+) -> impl Iterator<Item = usize> {
+    let width = board.width;
+
+    rows.flat_map(move |y| columns.clone().map(move |x| y * width + x))
+}
+
+/// Marks what the next step would do: births outlined and deaths crossed
+/// out. Cells too small on screen for either are filled instead.
+pub fn draw_prediction(
+    viewport: &mut Viewport,
+    board: &Board,
+    births: &[usize],
+    deaths: &[usize],
+) -> Result<(), String> {
+    let scale = viewport.scale();
+    let camera = (viewport.camera.x, viewport.camera.y);
+    let canvas = &mut viewport.canvas;
+    let side = scale as i32;
+
+    canvas.set_scale(1.0, 1.0)?;
+
+    for (cells, color) in [(births, PREDICTED_BIRTH), (deaths, PREDICTED_DEATH)] {
+        canvas.set_draw_color(color);
+
+        for index in cells.iter() {
+            let (x, y) = board.index_to_coordinates(*index);
+            let (left, top) = (
+                ((x - camera.0) as f32 * scale) as i32,
+                ((y - camera.1) as f32 * scale) as i32,
+            );
+            let (right, bottom) = (left + side - 1, top + side - 1);
+            let cell = Rect::new(left, top, side.max(1) as u32, side.max(1) as u32);
+            let drawn = if side < 3 {
+                canvas.fill_rect(cell)
+            } else if color == PREDICTED_BIRTH {
+                canvas.draw_rect(cell)
+            } else {
+                canvas
+                    .draw_line((left, top), (right, bottom))
+                    .and_then(|_| canvas.draw_line((right, top), (left, bottom)))
+            };
+
+            drawn?;
+        }
+    }
+
+    canvas.set_scale(scale, scale)?;
+
+    Ok(())
+}
+
+/// Colors cells by what the last step did to them: births green, deaths red
+/// and survivors black.
+fn draw_diff(
+    canvas: &mut Canvas<Window>,
+    board: &Board,
+    camera: (i32, i32),
+    visible: impl Iterator<Item = usize>,
+    previously_alive: &[bool],
+) -> Result<(), String> {
+    for index in visible {
+        let color = match (previously_alive[index], board.cells[index].is_alive()) {
+            (false, true) => Color::RGB(0, 192, 0),
+            (true, false) => Color::RGB(224, 0, 0),
+            (true, true) => Color::RGB(0, 0, 0),
+            (false, false) => continue,
+        };
+
+        draw_cell(canvas, board, camera, index, color)?;
+    }
+
+    Ok(())
+}
+
+/// Draws cells shrunk to a fraction of their size, centered in their slot.
+/// The canvas is drawn in cell units, so this temporarily switches to pixel
+/// units to allow sub-cell sizes.
+fn draw_scaled_cells(
+    canvas: &mut Canvas<Window>,
+    board: &Board,
+    camera: (i32, i32),
+    scale: f32,
+    cells: &[(usize, f32, Rgb)],
+) -> Result<(), String> {
+    canvas.set_scale(1.0, 1.0)?;
+
+    for (index, size, color) in cells.iter() {
+        let (x, y) = board.index_to_coordinates(*index);
+        let (x, y) = (x - camera.0, y - camera.1);
+        let side = (scale * size.max(0.0)) as u32;
+        let offset = ((scale - side as f32) / 2.0) as i32;
+
+        if side == 0 {
+            continue;
+        }
+
+        canvas.set_draw_color(to_color(*color));
+        canvas.fill_rect(Rect::new(
+            (x as f32 * scale) as i32 + offset,
+            (y as f32 * scale) as i32 + offset,
+            side,
+            side,
+        ))?;
+    }
+
+    canvas.set_scale(scale, scale)?;
+
+    Ok(())
+}
+
+/// Draws a translucent preview of where a pattern would be stamped with its
+/// top left corner at `origin`.
+pub fn draw_ghost(
+    viewport: &mut Viewport,
+    cells: &[(i32, i32)],
+    origin: (i32, i32),
+    config: &Config,
+) -> Result<(), String> {
+    if cells.is_empty() {
+        return Ok(());
+    }
+
+    let pattern = Pattern::new("preview", cells.to_vec());
+    let (left, top) = pattern.origin();
+    let image = render_pattern(&pattern, config, 1);
+    let camera = (viewport.camera.x, viewport.camera.y);
+    let canvas = &mut viewport.canvas;
+    let texture_creator = canvas.texture_creator();
+    let mut texture = to_texture(&texture_creator, &image)?;
+
+    texture.set_alpha_mod(GHOST_ALPHA);
+    canvas.copy(
+        &texture,
+        None,
+        Rect::new(
+            origin.0 + left - camera.0,
+            origin.1 + top - camera.1,
+            image.width as u32,
+            image.height as u32,
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn to_color(rgb: Rgb) -> Color {
+    Color::RGB(rgb.r, rgb.g, rgb.b)
+}
+
+fn draw_sprite(
+    canvas: &mut Canvas<Window>,
+    board: &Board,
+    camera: (i32, i32),
+    index: usize,
+    texture: &Texture,
+) -> Result<(), String> {
+    let (x, y) = board.index_to_coordinates(index);
+
+    canvas.copy(texture, None, Rect::new(x - camera.0, y - camera.1, 1, 1))?;
+
+    Ok(())
+}
+
+fn draw_cell(
+    canvas: &mut Canvas<Window>,
+    board: &Board,
+    camera: (i32, i32),
+    index: usize,
+    color: Color,
+) -> Result<(), String> {
+    let (x, y) = board.index_to_coordinates(index);
+
+    canvas.set_draw_color(color);
+    canvas.draw_point((x - camera.0, y - camera.1))?;
+
+    Ok(())
+}
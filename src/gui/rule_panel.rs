@@ -0,0 +1,232 @@
+use super::text::{draw_text, text_width, GLYPH_HEIGHT, LINE_HEIGHT};
+use game_of_life::Rule;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+const PANEL_WIDTH: u32 = 420;
+const PANEL_HEIGHT: u32 = 250;
+const TEXT_SIZE: i32 = 2;
+const MARGIN: i32 = 12;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 230);
+const TEXT: Color = Color::RGB(230, 230, 230);
+const HEADING: Color = Color::RGB(255, 190, 80);
+const BIT_ON: Color = Color::RGB(80, 170, 90);
+const BIT_OFF: Color = Color::RGB(60, 60, 72);
+const SELECTION: Color = Color::RGB(255, 190, 80);
+/// Side length of each bit's box, in pixels.
+const BOX_SIZE: i32 = 32;
+const BOX_SPACING: i32 = 4;
+/// Left edge of the first box in each row.
+const BOXES_LEFT: i32 = MARGIN + 40;
+/// Top of the birth row; the survival row sits below it.
+const ROWS_TOP: i32 = MARGIN + LINE_HEIGHT * TEXT_SIZE * 2;
+
+/// What the panel wants done after handling an event.
+pub enum RuleAction {
+    None,
+    Close,
+    /// Flip the birth (`birth` set) or survival bit for a neighbor count.
+    Flip {
+        birth: bool,
+        count: usize,
+    },
+}
+
+/// Shows the board's B0-B8 and S0-S8 bits as two rows of boxes that can be
+/// flipped while the simulation keeps running.
+pub struct RulePanel {
+    /// Selected row, 0 for birth and 1 for survival, and column.
+    selected: (usize, usize),
+}
+
+impl RulePanel {
+    pub fn new() -> Self {
+        RulePanel { selected: (0, 3) }
+    }
+
+    /// Handles a key press or click. `mouse` is in drawable pixels.
+    pub fn handle_event(&mut self, event: &Event, mouse: (i32, i32)) -> RuleAction {
+        let (row, column) = self.selected;
+
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            }
+            | Event::KeyDown {
+                keycode: Some(Keycode::B),
+                ..
+            } => RuleAction::Close,
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            }
+            | Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => {
+                self.selected.0 = 1 - row;
+                RuleAction::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Left),
+                ..
+            } => {
+                self.selected.1 = column.saturating_sub(1);
+                RuleAction::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Right),
+                ..
+            } => {
+                self.selected.1 = (column + 1).min(8);
+                RuleAction::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Space),
+                ..
+            }
+            | Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } => self.flip(row, column),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } => match digit(*keycode) {
+                Some(count) => self.flip(row, count),
+                None => RuleAction::None,
+            },
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => match box_at(mouse) {
+                Some((row, column)) => self.flip(row, column),
+                None => RuleAction::None,
+            },
+            _ => RuleAction::None,
+        }
+    }
+
+    fn flip(&mut self, row: usize, column: usize) -> RuleAction {
+        self.selected = (row, column);
+
+        RuleAction::Flip {
+            birth: row == 0,
+            count: column,
+        }
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>, rule: Rule) -> Result<(), String> {
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(Rect::new(0, 0, PANEL_WIDTH, PANEL_HEIGHT))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        draw_text(
+            canvas,
+            (MARGIN, MARGIN),
+            TEXT_SIZE,
+            &format!("Rule {}", rule.name()),
+            HEADING,
+        )?;
+
+        for (row, (label, mask)) in [("B", rule.birth), ("S", rule.survive)].iter().enumerate() {
+            let top = ROWS_TOP + row as i32 * (BOX_SIZE + line_height);
+            let text_top = top + (BOX_SIZE - GLYPH_HEIGHT * TEXT_SIZE) / 2;
+
+            draw_text(canvas, (MARGIN, text_top), TEXT_SIZE, label, TEXT)?;
+
+            for column in 0..=8 {
+                let left = box_left(column);
+                let color = if mask & (1 << column) != 0 {
+                    BIT_ON
+                } else {
+                    BIT_OFF
+                };
+
+                if (row, column) == self.selected {
+                    canvas.set_draw_color(SELECTION);
+                    canvas.fill_rect(Rect::new(
+                        left - 2,
+                        top - 2,
+                        BOX_SIZE as u32 + 4,
+                        BOX_SIZE as u32 + 4,
+                    ))?;
+                }
+
+                canvas.set_draw_color(color);
+                canvas.fill_rect(Rect::new(left, top, BOX_SIZE as u32, BOX_SIZE as u32))?;
+
+                let count = column.to_string();
+
+                draw_text(
+                    canvas,
+                    (
+                        left + (BOX_SIZE - text_width(&count, TEXT_SIZE)) / 2,
+                        text_top,
+                    ),
+                    TEXT_SIZE,
+                    &count,
+                    TEXT,
+                )?;
+            }
+        }
+
+        draw_text(
+            canvas,
+            (
+                MARGIN,
+                PANEL_HEIGHT as i32 - MARGIN - GLYPH_HEIGHT * TEXT_SIZE,
+            ),
+            TEXT_SIZE,
+            "0-8 or click flips, Esc closes",
+            HEADING,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn box_left(column: usize) -> i32 {
+    BOXES_LEFT + column as i32 * (BOX_SIZE + BOX_SPACING)
+}
+
+/// The row and column of the box under the mouse, if any.
+fn box_at((x, y): (i32, i32)) -> Option<(usize, usize)> {
+    let line_height = LINE_HEIGHT * TEXT_SIZE;
+    let row = (0..2).find(|row| {
+        let top = ROWS_TOP + row * (BOX_SIZE + line_height);
+        y >= top && y < top + BOX_SIZE
+    })?;
+    let column = (0..=8).find(|column| {
+        let left = box_left(*column);
+        x >= left && x < left + BOX_SIZE
+    })?;
+
+    Some((row as usize, column))
+}
+
+fn digit(keycode: Keycode) -> Option<usize> {
+    let digits = [
+        Keycode::Num0,
+        Keycode::Num1,
+        Keycode::Num2,
+        Keycode::Num3,
+        Keycode::Num4,
+        Keycode::Num5,
+        Keycode::Num6,
+        Keycode::Num7,
+        Keycode::Num8,
+    ];
+
+    digits.iter().position(|digit| *digit == keycode)
+}
@@ -0,0 +1,94 @@
+use super::hud;
+use super::viewport::Viewport;
+use game_of_life::{info, Board, Input, Region};
+use std::mem;
+
+/// A region of the board simulated on its own. While a sandbox is open its
+/// board takes the main board's place in the window, so stepping, editing
+/// and drawing all act on it, and the main board waits here unchanged until
+/// the sandbox is closed.
+pub struct Sandbox {
+    region: Region,
+    parked: Board,
+    /// Where the camera was looking at the main board.
+    camera: (i32, i32, f32),
+}
+
+impl Sandbox {
+    /// Swaps the region's cells in for the board and centers the camera on
+    /// them.
+    pub fn open(board: &mut Board, region: Region, viewport: &mut Viewport) -> Self {
+        let sandbox_board = board.extract(&region);
+        let camera = (viewport.camera.x, viewport.camera.y, viewport.camera.zoom);
+
+        info!(
+            "sandboxing {}x{} cells at {},{}",
+            region.width, region.height, region.x, region.y
+        );
+        viewport.center_on((region.width as i32 / 2, region.height as i32 / 2));
+
+        Sandbox {
+            region,
+            parked: mem::replace(board, sandbox_board),
+            camera,
+        }
+    }
+
+    /// Puts the main board back and points the camera where it was. When
+    /// `write_back` is set, returns the inputs that copy the sandbox's live
+    /// cells over the region, for the caller to apply and record.
+    pub fn close(self, board: &mut Board, viewport: &mut Viewport, write_back: bool) -> Vec<Input> {
+        let sandbox_board = mem::replace(board, self.parked);
+
+        (viewport.camera.x, viewport.camera.y, viewport.camera.zoom) = self.camera;
+
+        if !write_back {
+            info!("discarded the sandbox");
+            return Vec::new();
+        }
+
+        info!(
+            "wrote the sandbox back after {} generations",
+            sandbox_board.generation
+        );
+
+        let cells = sandbox_board.live_cells();
+        let mut inputs = vec![Input::Clear(self.region)];
+
+        if !cells.is_empty() {
+            inputs.push(Input::Paste {
+                cells,
+                x: self.region.x,
+                y: self.region.y,
+            });
+        }
+
+        inputs
+    }
+
+    /// The main board, as it was when the sandbox opened.
+    pub fn parked(&self) -> &Board {
+        &self.parked
+    }
+
+    /// Shows how the sandbox's edges behave and how to leave it.
+    pub fn draw_caption(&self, viewport: &mut Viewport, board: &Board) -> Result<(), String> {
+        hud::draw_caption(
+            viewport,
+            &format!(
+                "sandbox on a {}: Shift+T edges, Enter writes back, Esc discards",
+                board.topology.name()
+            ),
+        )
+    }
+}
+
+/// The region spanned by a drag from `from` to `to`, corners included.
+pub fn drag_region(from: (i32, i32), to: (i32, i32)) -> Region {
+    Region::new(
+        from.0.min(to.0),
+        from.1.min(to.1),
+        (from.0 - to.0).unsigned_abs() as usize + 1,
+        (from.1 - to.1).unsigned_abs() as usize + 1,
+    )
+}
@@ -0,0 +1,233 @@
+use super::text::{draw_text, text_width, LINE_HEIGHT};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+const PANEL_WIDTH: u32 = 420;
+const TEXT_SIZE: i32 = 2;
+const MARGIN: i32 = 12;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 230);
+const TEXT: Color = Color::RGB(230, 230, 230);
+const HEADING: Color = Color::RGB(255, 190, 80);
+const HIGHLIGHT: Color = Color::RGB(70, 90, 160);
+/// Left edge of the value column.
+const VALUE_LEFT: i32 = 200;
+/// Top of the first setting row.
+const ROWS_TOP: i32 = MARGIN + LINE_HEIGHT * TEXT_SIZE * 2;
+
+/// A setting shown in the panel, in display order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Setting {
+    Speed,
+    TrailLength,
+    Smoothing,
+    DiffView,
+    Ruler,
+    Theme,
+    BoardSize,
+    Rule,
+    LoadPattern,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 9] = [
+        Setting::Speed,
+        Setting::TrailLength,
+        Setting::Smoothing,
+        Setting::DiffView,
+        Setting::Ruler,
+        Setting::Theme,
+        Setting::BoardSize,
+        Setting::Rule,
+        Setting::LoadPattern,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Speed => "Speed",
+            Setting::TrailLength => "Trail",
+            Setting::Smoothing => "Smoothing",
+            Setting::DiffView => "Diff view",
+            Setting::Ruler => "Ruler",
+            Setting::Theme => "Theme",
+            Setting::BoardSize => "Board",
+            Setting::Rule => "Rule",
+            Setting::LoadPattern => "Load pattern...",
+        }
+    }
+}
+
+/// What the panel wants done after handling an event.
+pub enum SettingsAction {
+    None,
+    Close,
+    /// Step the setting to its previous (-1) or next (1) value.
+    Adjust(Setting, i32),
+    /// Open the pattern catalog.
+    LoadPattern,
+    /// Open the rule editor.
+    EditRule,
+}
+
+/// A list of settings drawn over the left side of the window. Values are
+/// changed with the left and right arrow keys or by clicking the arrows
+/// beside them; `Enter` or clicking the rule's label opens the rule editor
+/// for rules that aren't presets.
+pub struct SettingsPanel {
+    selected: usize,
+}
+
+impl SettingsPanel {
+    pub fn new() -> Self {
+        SettingsPanel { selected: 0 }
+    }
+
+    /// Handles a key press or click. `mouse` is in drawable pixels.
+    pub fn handle_event(&mut self, event: &Event, mouse: (i32, i32)) -> SettingsAction {
+        let setting = Setting::ALL[self.selected];
+
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            }
+            | Event::KeyDown {
+                keycode: Some(Keycode::Tab),
+                ..
+            } => SettingsAction::Close,
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => {
+                self.selected = self.selected.saturating_sub(1);
+                SettingsAction::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => {
+                self.selected = (self.selected + 1).min(Setting::ALL.len() - 1);
+                SettingsAction::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Left),
+                ..
+            } => SettingsAction::Adjust(setting, -1),
+            Event::KeyDown {
+                keycode: Some(Keycode::Right),
+                ..
+            } => SettingsAction::Adjust(setting, 1),
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } if setting == Setting::LoadPattern => SettingsAction::LoadPattern,
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } if setting == Setting::Rule => SettingsAction::EditRule,
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => self.click(mouse),
+            _ => SettingsAction::None,
+        }
+    }
+
+    /// Selects the clicked row, adjusting its value if an arrow was hit.
+    fn click(&mut self, (x, y): (i32, i32)) -> SettingsAction {
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+        let row = (y - ROWS_TOP) / line_height;
+
+        if x > PANEL_WIDTH as i32 || y < ROWS_TOP || row as usize >= Setting::ALL.len() {
+            return SettingsAction::None;
+        }
+
+        self.selected = row as usize;
+
+        let setting = Setting::ALL[self.selected];
+
+        if setting == Setting::LoadPattern {
+            SettingsAction::LoadPattern
+        } else if setting == Setting::Rule && x < VALUE_LEFT {
+            SettingsAction::EditRule
+        } else if x >= VALUE_LEFT && x < VALUE_LEFT + line_height {
+            SettingsAction::Adjust(setting, -1)
+        } else if x >= PANEL_WIDTH as i32 - MARGIN - line_height {
+            SettingsAction::Adjust(setting, 1)
+        } else {
+            SettingsAction::None
+        }
+    }
+
+    /// Draws the panel with `values` holding the current value of each
+    /// setting in `Setting::ALL` order.
+    pub fn render(&self, canvas: &mut Canvas<Window>, values: &[String]) -> Result<(), String> {
+        let (_, height) = canvas.output_size()?;
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(Rect::new(0, 0, PANEL_WIDTH, height))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        draw_text(canvas, (MARGIN, MARGIN), TEXT_SIZE, "Settings", HEADING)?;
+
+        for (row, (setting, value)) in Setting::ALL.iter().zip(values.iter()).enumerate() {
+            let y = ROWS_TOP + row as i32 * line_height;
+
+            if row == self.selected {
+                canvas.set_draw_color(HIGHLIGHT);
+                canvas.fill_rect(Rect::new(
+                    MARGIN / 2,
+                    y - TEXT_SIZE * 2,
+                    PANEL_WIDTH - MARGIN as u32,
+                    line_height as u32,
+                ))?;
+            }
+
+            draw_text(canvas, (MARGIN, y), TEXT_SIZE, setting.label(), TEXT)?;
+
+            if *setting == Setting::LoadPattern {
+                continue;
+            }
+
+            let right = PANEL_WIDTH as i32 - MARGIN;
+            let value_width = text_width(value, TEXT_SIZE);
+            let center = (VALUE_LEFT + right) / 2;
+
+            draw_text(canvas, (VALUE_LEFT, y), TEXT_SIZE, "<", TEXT)?;
+            draw_text(
+                canvas,
+                (center - value_width / 2, y),
+                TEXT_SIZE,
+                value,
+                TEXT,
+            )?;
+            draw_text(
+                canvas,
+                (right - text_width(">", TEXT_SIZE), y),
+                TEXT_SIZE,
+                ">",
+                TEXT,
+            )?;
+        }
+
+        draw_text(
+            canvas,
+            (
+                MARGIN,
+                ROWS_TOP + (Setting::ALL.len() as i32 + 1) * line_height,
+            ),
+            TEXT_SIZE,
+            "Tab or Esc closes",
+            HEADING,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use super::viewport::{Camera, Viewport};
+use super::{move_camera, MAX_FPS};
+use crate::cli::Options;
+use crate::initial_board_3d;
+use game_of_life::Board3d;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use std::time::Duration;
+
+/// 3D lattices are much more expensive to step, so run them slower by
+/// default.
+const DEFAULT_FPS_3D: u32 = 10;
+/// Shade used for live cells in the slices directly above and below the
+/// current one, as a depth cue.
+const ADJACENT_SLICE_COLOR: Color = Color::RGB(200, 200, 230);
+
+/// Draws one Z slice of the lattice, with the neighboring slices shown faintly.
+fn render_slice(viewport: &mut Viewport, board: &Board3d, z: usize) -> Result<(), String> {
+    let scale = viewport.scale();
+    let (columns, rows) = viewport.visible_range(board.width, board.height);
+    let (camera_x, camera_y) = (viewport.camera.x, viewport.camera.y);
+    let canvas = &mut viewport.canvas;
+
+    canvas.set_scale(scale, scale)?;
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    canvas.clear();
+
+    for y in rows {
+        for x in columns.clone() {
+            let color = if board.is_alive(x, y, z) {
+                Color::RGB(0, 0, 0)
+            } else if (z > 0 && board.is_alive(x, y, z - 1))
+                || (z + 1 < board.depth && board.is_alive(x, y, z + 1))
+            {
+                ADJACENT_SLICE_COLOR
+            } else {
+                continue;
+            };
+
+            canvas.set_draw_color(color);
+            canvas.draw_point((x as i32 - camera_x, y as i32 - camera_y))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn run_3d(options: &Options) -> Result<(), String> {
+    let sdl_context = sdl2::init()?;
+    let video_subsystem = sdl_context.video()?;
+    let mut board = initial_board_3d(options);
+    let mut viewport = Viewport::new(
+        &video_subsystem,
+        "Rusty Game of Life 3D",
+        options.window_width as u32,
+        options.window_height as u32,
+        Camera {
+            x: 0,
+            y: 0,
+            zoom: 1.0,
+        },
+    )?;
+    let mut event_pump = sdl_context.event_pump()?;
+    let mut z = board.depth / 2;
+    let mut fps = DEFAULT_FPS_3D;
+
+    viewport.camera.zoom = viewport.zoom_to_fit(board.width, board.height);
+
+    'running: loop {
+        board.step();
+        render_slice(&mut viewport, &board, z)?;
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(Keycode::PageUp),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Period),
+                    ..
+                } => z = (z + 1).min(board.depth.saturating_sub(1)),
+                Event::KeyDown {
+                    keycode: Some(Keycode::PageDown),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Comma),
+                    ..
+                } => z = z.saturating_sub(1),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Equals),
+                    ..
+                } => fps = (fps * 2).min(MAX_FPS),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Minus),
+                    ..
+                } => fps = (fps / 2).max(1),
+                _ => move_camera(&mut viewport, &event),
+            }
+        }
+
+        viewport
+            .canvas
+            .window_mut()
+            .set_title(&format!(
+                "Rusty Game of Life 3D - slice {}/{} - generation {} - population {}",
+                z + 1,
+                board.depth,
+                board.generation,
+                board.population()
+            ))
+            .map_err(|error| error.to_string())?;
+        viewport.canvas.present();
+        ::std::thread::sleep(Duration::from_secs(1) / fps);
+    }
+
+    Ok(())
+}
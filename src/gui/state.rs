@@ -0,0 +1,693 @@
+//! The state a GUI session keeps between frames, and what the keyboard
+//! shortcuts do to it.
+
+use super::catalog_panel::PanelAction;
+use super::console::ConsoleAction;
+use super::demo::Demo;
+use super::follow::{fit_live_cells, Follow};
+use super::history::CellHistory;
+use super::hud;
+use super::light_cone::LightCone;
+use super::mode::{AppMode, KeyAction, Panel, TabChange};
+use super::profiler::Profiler;
+use super::render::{draw_ghost, View};
+use super::rule_panel::RuleAction;
+use super::sandbox::Sandbox;
+use super::settings_panel::SettingsAction;
+use super::tabs::{Shown, Tab, Tabs};
+use super::viewport::{Camera, Viewport};
+use super::workspace_panel::WorkspaceAction;
+use super::{
+    adjust_setting, apply_input, blank_board, current_session, drag_cells, open_overview,
+    restore_workspace, save_rle, save_svg, script_commands, set_title, setting_values,
+    snapshot_workspace, tab_pattern, CatalogPanel, ConsolePanel, RulePanel, SettingsPanel,
+    WorkspacePanel, STATS_HISTORY,
+};
+use crate::cli::Options;
+use crate::{goto_steps, save_population_chart, screenshot_path};
+use game_of_life::brush::line;
+use game_of_life::memory::MemoryReport;
+use game_of_life::{
+    info, warn, Board, Bookmarks, Brush, BrushShape, Catalog, Config, Input, Launcher,
+    PeriodDetector, Periodicity, PopulationHistory, Replay, ScriptCommand, SimulationClock,
+    StatsHistory, Tool, Workspace,
+};
+use sdl2::event::Event;
+use sdl2::keyboard::TextInputUtil;
+use sdl2::VideoSubsystem;
+use std::time::Instant;
+
+pub struct Gui<'a> {
+    pub options: &'a Options,
+    pub video_subsystem: VideoSubsystem,
+    pub text_input: TextInputUtil,
+    /// The main window first, then the overview if it is open.
+    pub viewports: Vec<Viewport>,
+    pub board: Board,
+    pub bookmarks: Bookmarks,
+    pub fps: u32,
+    pub view: View,
+    pub config: Config,
+    pub mode: AppMode,
+    pub tool: Tool,
+    pub brush: Brush,
+    pub follow: Follow,
+    pub tabs: Tabs,
+    pub sandbox: Option<Sandbox>,
+    pub profiler: Profiler,
+    pub detector: PeriodDetector,
+    pub periodicity: Option<Periodicity>,
+    pub stats_history: StatsHistory,
+    pub population_history: PopulationHistory,
+    pub history: CellHistory,
+    pub light_cone: Option<LightCone>,
+    /// Which cells were alive before the last step, for the diff view.
+    pub previously_alive: Vec<bool>,
+    /// The generation the script last ran for, so switching boards doesn't
+    /// run it again.
+    pub scripted_generation: Option<usize>,
+    pub console_history: Vec<String>,
+    /// Commands run from the console, waiting to go with the script's.
+    pub typed_commands: Vec<ScriptCommand>,
+    /// Where a `goto` is running the board on to, stepping through every
+    /// generation in between as fast as frames allow.
+    pub goto_target: Option<usize>,
+    pub pending_screenshot: Option<String>,
+    pub workspace_name: Option<String>,
+    pub catalog: Catalog,
+    pub launcher: Launcher,
+    pub playback: Option<Replay>,
+    pub recording: Replay,
+    pub started_at: Instant,
+}
+
+/// What the run loop does after a key's action.
+pub enum KeyOutcome {
+    Continue,
+    Quit,
+    /// The sandbox was closed, to be written back or discarded.
+    CloseSandbox {
+        sandbox: Box<Sandbox>,
+        write_back: bool,
+    },
+}
+
+impl Gui<'_> {
+    pub fn perform(&mut self, action: KeyAction) -> Result<KeyOutcome, String> {
+        match action {
+            KeyAction::None => {}
+            KeyAction::Refuse(reason) => warn!("{}", reason),
+            KeyAction::Quit => return Ok(KeyOutcome::Quit),
+            KeyAction::Finish => {
+                self.mode.finish();
+            }
+            KeyAction::CloseSandbox { write_back } => {
+                if let Some(sandbox) = self.sandbox.take() {
+                    return Ok(KeyOutcome::CloseSandbox {
+                        sandbox: Box::new(sandbox),
+                        write_back,
+                    });
+                }
+            }
+            KeyAction::ToggleBackgroundTabs => {
+                self.tabs.background = !self.tabs.background;
+                info!(
+                    "hidden tabs {}",
+                    if self.tabs.background {
+                        "keep running"
+                    } else {
+                        "are paused"
+                    }
+                );
+            }
+            KeyAction::Tab(change) => self.change_tab(change)?,
+            KeyAction::ToggleDiff => self.view.diff_mode = !self.view.diff_mode,
+            KeyAction::NextSmoothing => self.view.smoothing = self.view.smoothing.next(),
+            KeyAction::ToggleRuler => self.view.ruler = !self.view.ruler,
+            KeyAction::SavePopulationChart => save_population_chart(
+                &format!("population-gen{}.png", self.board.generation),
+                &self.population_history,
+                &self.config,
+            )?,
+            KeyAction::ToggleStats => self.view.stats = !self.view.stats,
+            KeyAction::ClearHistory => self.history.clear(),
+            KeyAction::ToggleHistory => self.view.history = !self.view.history,
+            KeyAction::TogglePrediction => self.view.prediction = !self.view.prediction,
+            KeyAction::RestartLightCone => {
+                self.light_cone = LightCone::new(&self.board);
+                self.view.light_cone = true;
+            }
+            KeyAction::ToggleLightCone => self.view.light_cone = !self.view.light_cone,
+            KeyAction::ToggleFollowFit => {
+                self.follow = if self.follow == Follow::Fit {
+                    Follow::Off
+                } else {
+                    Follow::Fit
+                };
+                info!("camera follow: {}", self.follow.name());
+            }
+            KeyAction::ZoomToFit => {
+                if !fit_live_cells(&mut self.viewports[0], &self.board) {
+                    info!("nothing alive to zoom to");
+                }
+            }
+            KeyAction::NextFollow => {
+                self.follow = self.follow.next();
+                info!("camera follow: {}", self.follow.name());
+            }
+            KeyAction::ToggleResources => match self.board.resources {
+                Some(_) => self.view.resources = !self.view.resources,
+                None => warn!("no resource field; set resources.* in the config"),
+            },
+            KeyAction::ToggleProfiler => self.profiler.visible = !self.profiler.visible,
+            KeyAction::SaveRle => save_rle(
+                &self.board,
+                self.options.pattern.as_ref(),
+                self.periodicity.as_ref(),
+            )?,
+            KeyAction::SaveSvg { trail } => {
+                save_svg(&self.board, &self.config, self.options.svg_trail || trail)?
+            }
+            KeyAction::OpenCatalog => self.open_panel(Panel::Catalog(CatalogPanel::new()), true),
+            KeyAction::OpenConsole => self.open_panel(
+                Panel::Console(ConsolePanel::new(self.console_history.clone())),
+                true,
+            ),
+            KeyAction::OpenSettings => {
+                self.open_panel(Panel::Settings(SettingsPanel::new()), false)
+            }
+            KeyAction::OpenWorkspaces => {
+                let names = Workspace::list(&self.options.workspace_dir)?;
+                let panel = WorkspacePanel::new(names, self.workspace_name.as_deref());
+
+                self.open_panel(Panel::Workspaces(panel), true);
+            }
+            KeyAction::OpenRule => self.open_panel(Panel::Rule(RulePanel::new()), false),
+            KeyAction::NextBrush => {
+                self.brush.shape = self.brush.shape.next();
+                info!("brush: {}", self.brush.shape.name());
+            }
+            KeyAction::NextSandboxEdges => {
+                self.board.topology = self.board.topology.next();
+                info!("sandbox edges: {}", self.board.topology.name());
+            }
+            KeyAction::NextTool { end_drag } => {
+                self.tool = self.tool.next();
+
+                if end_drag {
+                    self.mode.finish();
+                }
+
+                info!("tool: {}", self.tool.name());
+            }
+            KeyAction::TogglePaused => self.mode.toggle_paused(),
+            KeyAction::ToggleOverview => {
+                if self.viewports.len() > 1 {
+                    self.viewports.truncate(1);
+                } else {
+                    let overview = open_overview(&self.video_subsystem, &self.board)?;
+
+                    self.viewports.push(overview);
+                }
+            }
+        }
+
+        Ok(KeyOutcome::Continue)
+    }
+
+    /// Applies an edit made in the window, a panel or a script, recording it
+    /// unless a sandbox has the board's place.
+    pub fn edit(&mut self, input: Input) -> Result<(), String> {
+        apply_input(&mut self.board, &mut self.bookmarks, &mut self.fps, &input)?;
+
+        if let Input::RestoreBookmark(_) = input {
+            self.detector.reset();
+        }
+
+        if self.sandbox.is_none() {
+            self.recording.record(
+                self.board.generation,
+                self.started_at.elapsed().as_millis(),
+                input,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Applies the inputs the replay made at the current generation.
+    pub fn replay_events(&mut self) -> Result<(), String> {
+        if let Some(replay) = &self.playback {
+            for event in replay.events_at(self.board.generation) {
+                apply_input(
+                    &mut self.board,
+                    &mut self.bookmarks,
+                    &mut self.fps,
+                    &event.input,
+                )?;
+
+                if let Input::RestoreBookmark(_) = event.input {
+                    self.detector.reset();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the script's commands for this generation, once, along with any
+    /// typed into the console. Scripts only drive the first tab, and wait
+    /// while a sandbox has its place. A command that fails is skipped with a
+    /// warning rather than closing the window.
+    pub fn run_script(&mut self) {
+        let mut commands = Vec::new();
+
+        if self.sandbox.is_none()
+            && self.tabs.active() == 0
+            && self.scripted_generation != Some(self.board.generation)
+        {
+            self.scripted_generation = Some(self.board.generation);
+            commands = script_commands(self.options, self.board.generation);
+        }
+
+        commands.append(&mut self.typed_commands);
+
+        for command in commands {
+            match command {
+                // Replays already hold the inputs the script made when they
+                // were recorded.
+                ScriptCommand::Input(input) if self.playback.is_none() => {
+                    if let Err(error) = self.edit(input) {
+                        warn!("{}", error);
+                    }
+                }
+                ScriptCommand::Input(_) => {}
+                ScriptCommand::Goto(target) => match goto_steps(&self.board, target) {
+                    Ok(_) => {
+                        info!("running on to generation {}", target);
+                        self.goto_target = Some(target);
+                    }
+                    Err(error) => warn!("{}", error),
+                },
+                ScriptCommand::Load(_) | ScriptCommand::Seed { .. } if self.playback.is_none() => {
+                    match command.replacement(&self.board) {
+                        Ok(inputs) => {
+                            for input in inputs {
+                                if let Err(error) = self.edit(input) {
+                                    warn!("{}", error);
+                                }
+                            }
+
+                            self.restart_tracking();
+                        }
+                        Err(error) => warn!("{}", error),
+                    }
+                }
+                ScriptCommand::Load(_) | ScriptCommand::Seed { .. } => {}
+                ScriptCommand::Pause => {
+                    info!(
+                        "paused at generation {}; press Space to resume",
+                        self.board.generation
+                    );
+                    self.mode.set_paused(true);
+                }
+                ScriptCommand::Screenshot(path) => {
+                    self.pending_screenshot =
+                        Some(screenshot_path(path.as_deref(), self.board.generation))
+                }
+            }
+        }
+    }
+
+    /// Passes an event to the open panel, if it is one the panel takes.
+    /// Returns whether it did.
+    pub fn handle_panel_event(
+        &mut self,
+        event: &Event,
+        pixel_mouse: (i32, i32),
+    ) -> Result<bool, String> {
+        match self.mode.panel_mut() {
+            Some(Panel::Catalog(panel))
+                if matches!(event, Event::KeyDown { .. } | Event::TextInput { .. }) =>
+            {
+                match panel.handle_event(event, &self.catalog) {
+                    PanelAction::None => {}
+                    PanelAction::Close => {
+                        self.mode.finish();
+                    }
+                    PanelAction::Choose(entry) if self.tool == Tool::Launcher => {
+                        self.launcher = Launcher::new(entry.pattern, self.board.rule);
+                        self.mode.finish();
+                    }
+                    PanelAction::Choose(entry) => self.mode.stamp(entry),
+                }
+
+                if !self.mode.is_typing() {
+                    self.text_input.stop();
+                }
+
+                return Ok(true);
+            }
+            Some(Panel::Workspaces(panel))
+                if matches!(event, Event::KeyDown { .. } | Event::TextInput { .. }) =>
+            {
+                match panel.handle_event(event) {
+                    WorkspaceAction::None => {}
+                    WorkspaceAction::Close => {
+                        self.mode.finish();
+                    }
+                    WorkspaceAction::Save(name) => {
+                        let session =
+                            current_session(&self.viewports[0].camera, self.fps, &self.view);
+                        let workspace = snapshot_workspace(
+                            &self.board,
+                            session,
+                            &self.config,
+                            &self.bookmarks,
+                            &self.stats_history,
+                        );
+
+                        match workspace.save(&self.options.workspace_dir, &name) {
+                            Ok(()) => {
+                                self.workspace_name = Some(name);
+                                self.mode.finish();
+                            }
+                            Err(error) => warn!("{}", error),
+                        }
+                    }
+                    WorkspaceAction::Load(_) if self.playback.is_some() => {
+                        warn!("workspaces can't be loaded while replaying")
+                    }
+                    WorkspaceAction::Load(name) => {
+                        match Workspace::load(&self.options.workspace_dir, &name) {
+                            Ok(workspace) => {
+                                let session = restore_workspace(
+                                    workspace,
+                                    self.options,
+                                    &mut self.board,
+                                    &mut self.config,
+                                    &mut self.bookmarks,
+                                    &mut self.stats_history,
+                                );
+
+                                (
+                                    self.viewports[0].camera.x,
+                                    self.viewports[0].camera.y,
+                                    self.viewports[0].camera.zoom,
+                                ) = session.camera;
+                                self.fps = session.fps;
+                                self.view.smoothing = session.smoothing;
+                                self.restart_tracking();
+                                self.workspace_name = Some(name);
+                                self.mode.finish();
+
+                                if self.options.record.is_some() {
+                                    warn!("the recording won't replay past loading a workspace");
+                                }
+                            }
+                            Err(error) => warn!("{}", error),
+                        }
+                    }
+                }
+
+                if !self.mode.is_typing() {
+                    self.text_input.stop();
+                }
+
+                return Ok(true);
+            }
+            Some(Panel::Console(panel))
+                if matches!(event, Event::KeyDown { .. } | Event::TextInput { .. }) =>
+            {
+                match panel.handle_event(event) {
+                    ConsoleAction::None => {}
+                    ConsoleAction::Close => {
+                        self.mode.finish();
+                    }
+                    ConsoleAction::Run(command) => {
+                        // Catch what would fail now, while the command
+                        // can still be fixed, rather than when it runs.
+                        let checked = match &command {
+                            ScriptCommand::Input(_)
+                            | ScriptCommand::Load(_)
+                            | ScriptCommand::Seed { .. }
+                                if self.playback.is_some() =>
+                            {
+                                Err("the board can't be edited while replaying".to_string())
+                            }
+                            ScriptCommand::Input(Input::RestoreBookmark(slot))
+                                if !self.bookmarks.is_saved(*slot) =>
+                            {
+                                Err(format!("bookmark {} is empty", slot))
+                            }
+                            ScriptCommand::Goto(target) => {
+                                goto_steps(&self.board, *target).map(drop)
+                            }
+                            _ => command.replacement(&self.board).map(drop),
+                        };
+
+                        match checked {
+                            Ok(()) => {
+                                self.console_history.push(panel.text().to_string());
+                                self.typed_commands.push(command);
+                                self.mode.finish();
+                            }
+                            Err(error) => panel.fail(error),
+                        }
+                    }
+                }
+
+                if !self.mode.is_typing() {
+                    self.text_input.stop();
+                }
+
+                return Ok(true);
+            }
+            Some(Panel::Settings(panel))
+                if matches!(event, Event::KeyDown { .. } | Event::MouseButtonDown { .. }) =>
+            {
+                let input = match panel.handle_event(event, pixel_mouse) {
+                    SettingsAction::None => None,
+                    SettingsAction::Close => {
+                        self.mode.finish();
+                        None
+                    }
+                    SettingsAction::LoadPattern => {
+                        self.mode.edit(Panel::Catalog(CatalogPanel::new()));
+                        self.text_input.start();
+                        None
+                    }
+                    SettingsAction::EditRule => {
+                        self.mode.edit(Panel::Rule(RulePanel::new()));
+                        None
+                    }
+                    SettingsAction::Adjust(setting, direction) => adjust_setting(
+                        setting,
+                        direction,
+                        &self.board,
+                        self.fps,
+                        &mut self.view,
+                        &mut self.config,
+                    ),
+                };
+
+                if let (Some(input), None) = (input, &self.playback) {
+                    self.edit(input)?;
+                }
+
+                return Ok(true);
+            }
+            Some(Panel::Rule(panel))
+                if matches!(event, Event::KeyDown { .. } | Event::MouseButtonDown { .. }) =>
+            {
+                match panel.handle_event(event, pixel_mouse) {
+                    RuleAction::None => {}
+                    RuleAction::Close => {
+                        self.mode.finish();
+                    }
+                    RuleAction::Flip { birth, count } if self.playback.is_none() => {
+                        let mut rule = self.board.rule;
+
+                        if birth {
+                            rule.birth ^= 1 << count;
+                        } else {
+                            rule.survive ^= 1 << count;
+                        }
+
+                        let input = Input::Rule(rule);
+
+                        self.edit(input)?;
+                    }
+                    RuleAction::Flip { .. } => {}
+                }
+
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        Ok(false)
+    }
+
+    /// Draws what goes over the board in the main window: the ghost of what
+    /// a click would place, the light cone, the HUD and any open panel.
+    pub fn draw_overlays(
+        &mut self,
+        hovered_cell: (i32, i32),
+        shift_held: bool,
+        demo: Option<&Demo>,
+        clock: &SimulationClock,
+        memory: &MemoryReport,
+    ) -> Result<(), String> {
+        match (&self.mode, self.brush.anchor) {
+            (AppMode::Stamping { phased, .. }, _) => {
+                draw_ghost(&mut self.viewports[0], phased, hovered_cell, &self.config)?
+            }
+            (AppMode::Selecting { from, .. }, _) => {
+                let cells = drag_cells(self.tool, &self.launcher, *from, hovered_cell);
+                draw_ghost(&mut self.viewports[0], &cells, *from, &self.config)?;
+            }
+            (AppMode::Editing { .. }, _) => {}
+            (_, Some(from)) if shift_held => draw_ghost(
+                &mut self.viewports[0],
+                &line(from, hovered_cell),
+                from,
+                &self.config,
+            )?,
+            _ if self.brush.shape != BrushShape::Cell => draw_ghost(
+                &mut self.viewports[0],
+                &self.brush.footprint(),
+                hovered_cell,
+                &self.config,
+            )?,
+            _ => {}
+        }
+
+        if let Some(light_cone) = self.light_cone.as_ref().filter(|_| self.view.light_cone) {
+            light_cone.draw(&mut self.viewports[0], self.board.generation)?;
+        }
+
+        if self.view.ruler {
+            hud::draw_ruler(&mut self.viewports[0])?;
+        }
+
+        hud::draw_coordinates(&mut self.viewports[0], hovered_cell)?;
+
+        if let Some(demo) = demo {
+            demo.draw_caption(&mut self.viewports[0])?;
+        } else if let AppMode::Stamping { phase, .. } = &self.mode {
+            hud::draw_caption(
+                &mut self.viewports[0],
+                &format!("phase +{} (scroll to change)", phase),
+            )?;
+        } else if let Some(sandbox) = &self.sandbox {
+            sandbox.draw_caption(&mut self.viewports[0], &self.board)?;
+        }
+
+        if self.view.stats {
+            hud::draw_stats(&mut self.viewports[0], &self.stats_history, clock)?;
+        }
+
+        match &self.mode {
+            AppMode::Editing {
+                panel: Panel::Catalog(panel),
+                ..
+            } => panel.render(&mut self.viewports[0].canvas, &self.catalog, &self.config)?,
+            AppMode::Editing {
+                panel: Panel::Settings(panel),
+                ..
+            } => {
+                let values = setting_values(&self.board, self.fps, &self.view, &self.config);
+
+                panel.render(&mut self.viewports[0].canvas, &values)?;
+            }
+            AppMode::Editing {
+                panel: Panel::Rule(panel),
+                ..
+            } => panel.render(&mut self.viewports[0].canvas, self.board.rule)?,
+            AppMode::Editing {
+                panel: Panel::Workspaces(panel),
+                ..
+            } => panel.render(&mut self.viewports[0].canvas)?,
+            AppMode::Editing {
+                panel: Panel::Console(panel),
+                ..
+            } => panel.render(&mut self.viewports[0].canvas)?,
+            _ => {}
+        }
+
+        if self.profiler.visible {
+            self.profiler.render(
+                &mut self.viewports[0].canvas,
+                memory,
+                self.config.memory_budget,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the cell history, light cone, diff view and period detection
+    /// over, after the board was replaced or edited wholesale.
+    pub fn restart_tracking(&mut self) {
+        self.previously_alive = self.board.alive_mask();
+        self.history.clear();
+        self.history.record(&self.board, 0.0);
+        self.light_cone = LightCone::new(&self.board);
+        self.detector.reset();
+    }
+
+    /// Opens a panel, with text input on for the ones that are typed into.
+    fn open_panel(&mut self, panel: Panel, typed: bool) {
+        self.mode.edit(panel);
+
+        if typed {
+            self.text_input.start();
+        }
+    }
+
+    /// Opens, closes or switches tabs, swapping the shown board for another.
+    fn change_tab(&mut self, change: TabChange) -> Result<(), String> {
+        let new_tab = (change == TabChange::Open).then(|| Tab {
+            board: blank_board(&self.board, self.options),
+            camera: Camera {
+                x: 0,
+                y: 0,
+                zoom: self.viewports[0].camera.zoom,
+            },
+            fps: self.fps,
+            bookmarks: Bookmarks::new(),
+            stats_history: StatsHistory::new(STATS_HISTORY),
+            follow: Follow::Off,
+        });
+        let mut shown = Shown {
+            board: &mut self.board,
+            camera: &mut self.viewports[0].camera,
+            fps: &mut self.fps,
+            bookmarks: &mut self.bookmarks,
+            stats_history: &mut self.stats_history,
+            follow: &mut self.follow,
+        };
+
+        match (new_tab, change) {
+            (Some(tab), _) => self.tabs.open(&mut shown, tab),
+            (None, TabChange::Switch(offset)) => self.tabs.switch(&mut shown, offset),
+            (None, _) => {
+                if !self.tabs.close(&mut shown) {
+                    info!("the last tab can't be closed");
+                }
+            }
+        }
+
+        info!("tab {} of {}", self.tabs.active() + 1, self.tabs.len());
+        self.scripted_generation = Some(self.board.generation);
+        self.restart_tracking();
+        self.periodicity = None;
+        set_title(
+            &mut self.viewports[0],
+            tab_pattern(self.options, &self.tabs),
+            None,
+            &self.tabs,
+        )
+    }
+}
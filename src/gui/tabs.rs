@@ -0,0 +1,142 @@
+use super::follow::Follow;
+use super::viewport::Camera;
+use game_of_life::{Board, Bookmarks, StatsHistory};
+use std::mem;
+
+/// Everything that belongs to one tab's board rather than to the window.
+pub struct Tab {
+    pub board: Board,
+    pub camera: Camera,
+    pub fps: u32,
+    pub bookmarks: Bookmarks,
+    pub stats_history: StatsHistory,
+    pub follow: Follow,
+}
+
+/// The window's own variables holding the shown tab's state.
+pub struct Shown<'a> {
+    pub board: &'a mut Board,
+    pub camera: &'a mut Camera,
+    pub fps: &'a mut u32,
+    pub bookmarks: &'a mut Bookmarks,
+    pub stats_history: &'a mut StatsHistory,
+    pub follow: &'a mut Follow,
+}
+
+impl Tab {
+    /// A stand-in for the shown tab's slot.
+    fn empty() -> Self {
+        Tab {
+            board: Board::empty(0, 0),
+            camera: Camera {
+                x: 0,
+                y: 0,
+                zoom: 1.0,
+            },
+            fps: 0,
+            bookmarks: Bookmarks::new(),
+            stats_history: StatsHistory::new(0),
+            follow: Follow::Off,
+        }
+    }
+
+    /// Trades this tab's state for the one the window is showing.
+    fn swap(&mut self, shown: &mut Shown) {
+        mem::swap(&mut self.board, shown.board);
+        mem::swap(&mut self.camera, shown.camera);
+        mem::swap(&mut self.fps, shown.fps);
+        mem::swap(&mut self.bookmarks, shown.bookmarks);
+        mem::swap(&mut self.stats_history, shown.stats_history);
+        mem::swap(&mut self.follow, shown.follow);
+    }
+}
+
+/// Several boards open at once, one shown at a time. The shown tab's state
+/// lives in the window's own variables, so its slot here only holds
+/// whatever was last swapped out of them. Hidden boards can keep stepping
+/// in the background.
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+    /// Whether hidden boards step along with the shown one.
+    pub background: bool,
+}
+
+impl Tabs {
+    /// A single tab, whose state is the window's.
+    pub fn new() -> Self {
+        Tabs {
+            tabs: vec![Tab::empty()],
+            active: 0,
+            background: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    /// Position of the shown tab, counting from 0.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Adds a tab after the shown one and shows it.
+    pub fn open(&mut self, shown: &mut Shown, tab: Tab) {
+        self.tabs.insert(self.active + 1, tab);
+        self.show(shown, self.active + 1);
+    }
+
+    /// Shows the tab `offset` places after the shown one, wrapping around.
+    pub fn switch(&mut self, shown: &mut Shown, offset: isize) {
+        let target = (self.active as isize + offset).rem_euclid(self.tabs.len() as isize);
+
+        self.show(shown, target as usize);
+    }
+
+    /// Drops the shown tab and shows the next one. Returns false, keeping
+    /// it, if it is the only tab.
+    pub fn close(&mut self, shown: &mut Shown) -> bool {
+        if self.tabs.len() == 1 {
+            return false;
+        }
+
+        self.tabs.remove(self.active);
+        self.active = self.active.min(self.tabs.len() - 1);
+        self.tabs[self.active].swap(shown);
+        self.tabs[self.active] = Tab::empty();
+
+        true
+    }
+
+    /// Steps every hidden board one generation.
+    pub fn step_hidden(&mut self) {
+        let active = self.active;
+
+        for (_, tab) in self
+            .tabs
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| *index != active)
+        {
+            tab.board.update_live_neighbor_counts();
+            tab.board.step();
+        }
+    }
+
+    /// Bytes used by the hidden boards.
+    pub fn memory_usage(&self) -> usize {
+        self.tabs
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.active)
+            .map(|(_, tab)| tab.board.memory_usage() + tab.stats_history.memory_usage())
+            .sum()
+    }
+
+    fn show(&mut self, shown: &mut Shown, index: usize) {
+        self.tabs[self.active].swap(shown);
+        self.active = index;
+        self.tabs[self.active].swap(shown);
+    }
+}
@@ -0,0 +1,27 @@
+//! Draws text in the built-in bitmap font on the SDL canvas.
+
+use game_of_life::font::dots;
+pub use game_of_life::font::{text_width, GLYPH_HEIGHT, LINE_HEIGHT};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+/// Draws the text with its top left corner at `(x, y)`. Coordinates are in
+/// canvas units, so callers drawing in pixels should reset the canvas scale
+/// to 1.0 first.
+pub fn draw_text(
+    canvas: &mut Canvas<Window>,
+    (x, y): (i32, i32),
+    size: i32,
+    text: &str,
+    color: Color,
+) -> Result<(), String> {
+    let dots: Vec<Rect> = dots(text)
+        .into_iter()
+        .map(|(column, row)| Rect::new(x + column * size, y + row * size, size as u32, size as u32))
+        .collect();
+
+    canvas.set_draw_color(color);
+    canvas.fill_rects(&dots)
+}
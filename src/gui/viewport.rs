@@ -0,0 +1,147 @@
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::VideoSubsystem;
+use std::ops::Range;
+
+/// Window coordinates per cell on a standard density display.
+const SCALE: f32 = 10.0;
+/// The DPI a display is assumed to have when it reports no scaling of its
+/// own.
+const REFERENCE_DPI: f32 = 96.0;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 8.0;
+
+/// Which part of the board a viewport shows.
+pub struct Camera {
+    /// Board coordinates of the cell in the top left corner.
+    pub x: i32,
+    pub y: i32,
+    /// Multiplier on the viewport's base cell size.
+    pub zoom: f32,
+}
+
+/// A window showing the board through its own camera.
+pub struct Viewport {
+    pub canvas: Canvas<Window>,
+    pub camera: Camera,
+    /// Drawable pixels per cell at a zoom of 1.0.
+    pub base_scale: f32,
+    /// Drawable pixels per window coordinate; above 1.0 on HiDPI displays.
+    pub pixel_ratio: f32,
+}
+
+impl Camera {
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+impl Viewport {
+    pub fn new(
+        video_subsystem: &VideoSubsystem,
+        title: &str,
+        width: u32,
+        height: u32,
+        camera: Camera,
+    ) -> Result<Self, String> {
+        let window = video_subsystem
+            .window(title, width, height)
+            .position_centered()
+            .allow_highdpi()
+            .build()
+            .map_err(|error| error.to_string())?;
+        let (pixel_ratio, base_scale) = display_scale(video_subsystem, &window);
+        let canvas = window
+            .into_canvas()
+            .build()
+            .map_err(|error| error.to_string())?;
+
+        Ok(Viewport {
+            canvas,
+            camera,
+            base_scale,
+            pixel_ratio,
+        })
+    }
+
+    pub fn window_id(&self) -> u32 {
+        self.canvas.window().id()
+    }
+
+    /// Drawable pixels per board cell at the current zoom.
+    pub fn scale(&self) -> f32 {
+        self.base_scale * self.camera.zoom
+    }
+
+    /// Converts a mouse position in window coordinates to board coordinates.
+    pub fn cell_at(&self, mouse: (i32, i32)) -> (i32, i32) {
+        let to_cell =
+            |coordinate: i32| (coordinate as f32 * self.pixel_ratio / self.scale()) as i32;
+
+        (
+            self.camera.x + to_cell(mouse.0),
+            self.camera.y + to_cell(mouse.1),
+        )
+    }
+
+    /// The columns and rows of a board of the given size that fall inside
+    /// the window, so the renderer can skip everything off-screen.
+    pub fn visible_range(
+        &self,
+        board_width: usize,
+        board_height: usize,
+    ) -> (Range<usize>, Range<usize>) {
+        let (width, height) = self.canvas.window().drawable_size();
+        let span = |start: i32, pixels: u32, limit: usize| {
+            let end = start + (pixels as f32 / self.scale()).ceil() as i32 + 1;
+
+            (start.max(0) as usize).min(limit)..(end.max(0) as usize).min(limit)
+        };
+
+        (
+            span(self.camera.x, width, board_width),
+            span(self.camera.y, height, board_height),
+        )
+    }
+
+    /// Moves the camera so the cell is in the middle of the window.
+    pub fn center_on(&mut self, (x, y): (i32, i32)) {
+        let (width, height) = self.canvas.window().drawable_size();
+        let scale = self.scale();
+
+        self.camera.x = x - (width as f32 / scale / 2.0) as i32;
+        self.camera.y = y - (height as f32 / scale / 2.0) as i32;
+    }
+
+    /// Zoom that fits a board of the given size inside the window.
+    pub fn zoom_to_fit(&self, board_width: usize, board_height: usize) -> f32 {
+        let (width, height) = self.canvas.window().drawable_size();
+        let fit_width = width as f32 / (board_width as f32 * self.base_scale);
+        let fit_height = height as f32 / (board_height as f32 * self.base_scale);
+
+        fit_width.min(fit_height).clamp(MIN_ZOOM, MAX_ZOOM)
+    }
+}
+
+/// Works out how many drawable pixels a window coordinate covers and how many
+/// drawable pixels a cell should span so cells have the same physical size on
+/// every display. Platforms with HiDPI support report a drawable size larger
+/// than the window; elsewhere fall back to the display's reported DPI.
+fn display_scale(video_subsystem: &VideoSubsystem, window: &Window) -> (f32, f32) {
+    let (window_width, _) = window.size();
+    let (drawable_width, _) = window.drawable_size();
+    let pixel_ratio = drawable_width as f32 / window_width.max(1) as f32;
+
+    if pixel_ratio > 1.0 {
+        return (pixel_ratio, SCALE * pixel_ratio);
+    }
+
+    let dpi_ratio = window
+        .display_index()
+        .and_then(|index| video_subsystem.display_dpi(index))
+        .map(|(_, horizontal, _)| horizontal / REFERENCE_DPI)
+        .unwrap_or(1.0)
+        .max(1.0);
+
+    (pixel_ratio, SCALE * dpi_ratio)
+}
@@ -0,0 +1,148 @@
+use super::text::{draw_text, LINE_HEIGHT};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+const PANEL_WIDTH: u32 = 360;
+const TEXT_SIZE: i32 = 2;
+const MARGIN: i32 = 12;
+const BACKGROUND: Color = Color::RGBA(24, 24, 32, 230);
+const TEXT: Color = Color::RGB(230, 230, 230);
+const HEADING: Color = Color::RGB(255, 190, 80);
+const HIGHLIGHT: Color = Color::RGB(70, 90, 160);
+
+/// What the panel wants done after handling an event.
+pub enum WorkspaceAction {
+    None,
+    Close,
+    Load(String),
+    Save(String),
+}
+
+/// Lists the saved workspaces over the left side of the window. Typing
+/// names a workspace, `Up` / `Down` pick a saved one, `Enter` loads it and
+/// `Ctrl+S` saves the current one under the name.
+pub struct WorkspacePanel {
+    names: Vec<String>,
+    name: String,
+    selected: Option<usize>,
+}
+
+impl WorkspacePanel {
+    /// A panel listing `names`, with `current` as the name to save under.
+    pub fn new(names: Vec<String>, current: Option<&str>) -> Self {
+        let selected = current.and_then(|current| names.iter().position(|name| name == current));
+
+        WorkspacePanel {
+            names,
+            name: current.unwrap_or_default().to_string(),
+            selected,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> WorkspaceAction {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } => return WorkspaceAction::Close,
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => self.select(self.selected.map_or(0, |index| index.saturating_sub(1))),
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => self.select(self.selected.map_or(0, |index| index + 1)),
+            Event::KeyDown {
+                keycode: Some(Keycode::Backspace),
+                ..
+            } => {
+                self.name.pop();
+                self.selected = None;
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            } if self.names.contains(&self.name) => {
+                return WorkspaceAction::Load(self.name.clone());
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::S),
+                keymod,
+                ..
+            } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) && !self.name.is_empty() => {
+                return WorkspaceAction::Save(self.name.clone());
+            }
+            Event::TextInput { text, .. } => {
+                self.name.push_str(text);
+                self.selected = None;
+            }
+            _ => {}
+        }
+
+        WorkspaceAction::None
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+        let (_, height) = canvas.output_size()?;
+        let line_height = LINE_HEIGHT * TEXT_SIZE;
+
+        canvas.set_scale(1.0, 1.0)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(Rect::new(0, 0, PANEL_WIDTH, height))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        draw_text(
+            canvas,
+            (MARGIN, MARGIN),
+            TEXT_SIZE,
+            &format!("Workspace: {}_", self.name),
+            TEXT,
+        )?;
+        draw_text(
+            canvas,
+            (MARGIN, MARGIN + line_height),
+            TEXT_SIZE,
+            "Enter loads, Ctrl+S saves",
+            HEADING,
+        )?;
+
+        for (row, name) in self.names.iter().enumerate() {
+            let y = MARGIN + line_height * (row as i32 + 3);
+
+            if y + line_height > height as i32 {
+                break;
+            }
+
+            if Some(row) == self.selected {
+                canvas.set_draw_color(HIGHLIGHT);
+                canvas.fill_rect(Rect::new(
+                    MARGIN / 2,
+                    y - TEXT_SIZE * 2,
+                    PANEL_WIDTH - MARGIN as u32,
+                    line_height as u32,
+                ))?;
+            }
+
+            draw_text(canvas, (MARGIN * 2, y), TEXT_SIZE, name, TEXT)?;
+        }
+
+        Ok(())
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.names.is_empty() {
+            return;
+        }
+
+        let index = index.min(self.names.len() - 1);
+
+        self.selected = Some(index);
+        self.name = self.names[index].clone();
+    }
+}
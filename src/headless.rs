@@ -0,0 +1,397 @@
+use crate::cli::Options;
+use crate::output::{HashLog, Pipe, RunSummary, StatsFile, Timelapse};
+use crate::{
+    export_rle, goto_steps, initial_board, initial_board_3d, load_playback, resource_field,
+    save_population_chart, screenshot_path,
+};
+use game_of_life::memory::format_bytes;
+use game_of_life::{
+    debug, info, png, pnm, raster, svg, warn, Board, Bookmarks, Checkpoints, GenerationStats,
+    Input, PeriodDetector, PopulationHistory, Replay, ScriptCommand,
+};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Longest period the detector looks back for.
+pub const MAX_PERIOD: usize = 64;
+
+pub fn run(options: &Options) -> Result<(), String> {
+    let (playback, seed) = load_playback(options)?;
+    let mut board = if options.resume_run {
+        resume_run(options)?
+    } else {
+        initial_board(options, seed)
+    };
+    let mut bookmarks = Bookmarks::new();
+
+    if let Some(replay) = &playback {
+        replay.settings.apply(&mut board)?;
+    }
+
+    let mut recording = Replay::recording(seed, &board);
+    let started_at = Instant::now();
+
+    recording.record(0, 0, Input::TrailLength(board.trail_length));
+
+    if let Some(replay) = &playback {
+        for event in replay.events_at(0) {
+            event.input.apply(&mut board, &mut bookmarks)?;
+        }
+    }
+
+    let mut detector = PeriodDetector::new(MAX_PERIOD);
+
+    // `--generations` is where the run ends, unless a script's `goto` asks
+    // for later.
+    let mut end = options.generations;
+
+    run_script(
+        options,
+        &mut board,
+        &mut bookmarks,
+        &mut detector,
+        &mut end,
+        playback.is_none().then_some((&mut recording, started_at)),
+    )?;
+
+    let mut periodicity = None;
+    let mut stats_file = StatsFile::create(options)?;
+    let mut timelapse = Timelapse::create(options)?;
+    let mut hash_log = HashLog::create(options)?;
+    let mut pipe = Pipe::spawn(options)?;
+    let mut population_history = options
+        .population_chart
+        .as_ref()
+        .map(|_| PopulationHistory::new());
+    let checkpoints = options
+        .checkpoint_dir
+        .as_ref()
+        .map(|directory| Checkpoints::create(directory, options.checkpoint_every))
+        .transpose()?;
+    let mut over_budget = false;
+    let mut summary = RunSummary::start("headless", &board);
+    // A resumed run is already part of the way to the end.
+    let generations = end.saturating_sub(board.generation);
+
+    info!(
+        "running {} generations on a {}x{} board",
+        generations, board.width, board.height
+    );
+
+    if let Some(timelapse) = &mut timelapse {
+        timelapse.capture(&board, &options.config)?;
+    }
+
+    render_every(options, &board)?;
+
+    if let Some(hash_log) = &mut hash_log {
+        hash_log.write(&board)?;
+    }
+
+    if let Some(pipe) = &mut pipe {
+        pipe.write(&board)?;
+    }
+
+    if let Some(history) = &mut population_history {
+        history.record(&board);
+    }
+
+    while board.generation < end {
+        let previously_alive = stats_file.as_ref().map(|_| board.alive_mask());
+
+        board.update_live_neighbor_counts();
+        board.step();
+        periodicity = detector.observe(&board);
+        summary.record(board.population());
+
+        if let Some(budget) = options.config.memory_budget {
+            let used = board.memory_usage() + detector.memory_usage();
+
+            if used > budget && !over_budget {
+                warn!(
+                    "using {} of memory, over the {} budget; trimming period history",
+                    format_bytes(used),
+                    format_bytes(budget)
+                );
+            }
+
+            over_budget = used > budget;
+
+            if over_budget {
+                detector.trim();
+            }
+        }
+
+        if let (Some(stats_file), Some(previously_alive)) = (&mut stats_file, previously_alive) {
+            stats_file.write(&GenerationStats::measure(&board, &previously_alive))?;
+        }
+
+        if let Some(timelapse) = &mut timelapse {
+            timelapse.capture(&board, &options.config)?;
+        }
+
+        render_every(options, &board)?;
+
+        if let Some(hash_log) = &mut hash_log {
+            hash_log.write(&board)?;
+        }
+
+        if let Some(pipe) = &mut pipe {
+            pipe.write(&board)?;
+        }
+
+        if let Some(history) = &mut population_history {
+            history.record(&board);
+        }
+
+        if let Some(checkpoints) = &checkpoints {
+            checkpoints.save_if_due(&board)?;
+        }
+
+        if let Some(replay) = &playback {
+            for event in replay.events_at(board.generation) {
+                debug!("generation {}: {:?}", board.generation, event.input);
+                event.input.apply(&mut board, &mut bookmarks)?;
+
+                if let Input::RestoreBookmark(_) = event.input {
+                    detector.reset();
+                }
+            }
+        }
+
+        run_script(
+            options,
+            &mut board,
+            &mut bookmarks,
+            &mut detector,
+            &mut end,
+            playback.is_none().then_some((&mut recording, started_at)),
+        )?;
+    }
+
+    if let Some(checkpoints) = &checkpoints {
+        checkpoints.finish(&board)?;
+    }
+
+    println!(
+        "generation {}: population {}",
+        board.generation,
+        board.population()
+    );
+
+    info!(
+        "memory: board {}, period history {}",
+        format_bytes(board.memory_usage()),
+        format_bytes(detector.memory_usage())
+    );
+
+    match &periodicity {
+        Some(periodicity) => println!("{}", periodicity.describe()),
+        None => println!("no period detected"),
+    }
+
+    summary.finish(&board, periodicity.as_ref(), options)?;
+
+    if let Some(path) = &options.record {
+        recording.save(path)?;
+    }
+
+    if let Some(path) = &options.save_rle {
+        info!("saving board to {}", path);
+
+        let rle = export_rle(&board, options.pattern.as_ref(), periodicity.as_ref());
+
+        fs::write(path, rle).map_err(|error| format!("failed to write {}: {}", path, error))?;
+    }
+
+    if let Some(path) = &options.render_out {
+        render(options, &board, path)?;
+    }
+
+    if let Some(path) = &options.save_svg {
+        info!("saving board to {}", path);
+
+        let svg = svg::encode(&board, &options.config, options.svg_trail);
+
+        fs::write(path, svg).map_err(|error| format!("failed to write {}: {}", path, error))?;
+    }
+
+    if let (Some(path), Some(history)) = (&options.population_chart, &population_history) {
+        save_population_chart(path, history, &options.config)?;
+    }
+
+    if let Some(path) = &options.save_pbm {
+        info!("saving board to {}", path);
+
+        fs::write(path, pnm::encode_pbm(&board))
+            .map_err(|error| format!("failed to write {}: {}", path, error))?;
+    }
+
+    if let Some(path) = &options.save_pgm {
+        info!("saving board to {}", path);
+
+        fs::write(path, pnm::encode_pgm(&board, &options.config.trail_curve))
+            .map_err(|error| format!("failed to write {}: {}", path, error))?;
+    }
+
+    Ok(())
+}
+
+/// The board from the last checkpoint in the run directory, with the
+/// settings checkpoints don't keep (engine, weighted rule, poison chance,
+/// dying policy and resources, which start full again) taken from the
+/// options.
+fn resume_run(options: &Options) -> Result<Board, String> {
+    let directory = options
+        .checkpoint_dir
+        .as_ref()
+        .ok_or("--resume-run needs a --checkpoint-dir")?;
+    let mut board = Checkpoints::load_latest(directory)?;
+
+    board.engine = options.engine;
+    board.weighted_rule = options.config.weighted_rule;
+    board.poison_chance = options.config.poison_chance;
+    board.dying_policy = options.config.dying_policy;
+    board.resources = resource_field(options, &board);
+
+    info!(
+        "resuming from generation {} in {}",
+        board.generation, directory
+    );
+
+    Ok(board)
+}
+
+/// Renders the board to a PNG with every cell `--cell-size` pixels across,
+/// through the same off-screen path as time-lapses and screenshots.
+fn render(options: &Options, board: &Board, path: &str) -> Result<(), String> {
+    let cell_size = options.cell_size;
+    let image = raster::render_fit(
+        board,
+        &options.config,
+        board.width * cell_size,
+        board.height * cell_size,
+    );
+
+    info!("rendering board to {}", path);
+    png::save(path, &image)
+}
+
+/// Renders the board next to `--render-out`, named after the generation, if
+/// it is on a multiple of `--render-every`.
+fn render_every(options: &Options, board: &Board) -> Result<(), String> {
+    match (&options.render_out, options.render_every) {
+        (Some(path), Some(every)) if board.generation.is_multiple_of(every) => {
+            let path = Path::new(path);
+            let stem = path
+                .file_stem()
+                .map_or("render".into(), |stem| stem.to_string_lossy());
+
+            render(
+                options,
+                board,
+                &path
+                    .with_file_name(format!("{}-gen{:06}.png", stem, board.generation))
+                    .to_string_lossy(),
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Carries out the `--script-actions` for the board's generation. Pauses
+/// mean nothing without a window and are skipped; screenshots render the
+/// whole board at the window size. Every generation is stepped anyway, so
+/// a `goto` only moves the `end` of the run if it is further.
+///
+/// The inputs the script makes are recorded along with the time since the
+/// run started. While replaying there is no `recording`, and they are
+/// skipped, since the replay already holds them.
+fn run_script(
+    options: &Options,
+    board: &mut Board,
+    bookmarks: &mut Bookmarks,
+    detector: &mut PeriodDetector,
+    end: &mut usize,
+    mut recording: Option<(&mut Replay, Instant)>,
+) -> Result<(), String> {
+    let script = match &options.script {
+        Some(script) => script,
+        None => return Ok(()),
+    };
+
+    for action in script.actions_at(board.generation) {
+        debug!("generation {}: {:?}", board.generation, action.command);
+
+        match &action.command {
+            ScriptCommand::Input(input) => {
+                let (recording, started_at) = match &mut recording {
+                    Some(recording) => recording,
+                    None => continue,
+                };
+
+                input.apply(board, bookmarks)?;
+                recording.record(
+                    board.generation,
+                    started_at.elapsed().as_millis(),
+                    input.clone(),
+                );
+
+                if let Input::RestoreBookmark(_) = input {
+                    detector.reset();
+                }
+            }
+            ScriptCommand::Pause => {}
+            ScriptCommand::Goto(target) => {
+                goto_steps(board, *target)?;
+                *end = (*end).max(*target);
+            }
+            ScriptCommand::Load(_) | ScriptCommand::Seed { .. } => {
+                let (recording, started_at) = match &mut recording {
+                    Some(recording) => recording,
+                    None => continue,
+                };
+
+                for input in action.command.replacement(board)? {
+                    input.apply(board, bookmarks)?;
+                    recording.record(board.generation, started_at.elapsed().as_millis(), input);
+                }
+
+                detector.reset();
+            }
+            ScriptCommand::Screenshot(path) => {
+                let path = screenshot_path(path.as_deref(), board.generation);
+                let image = raster::render_fit(
+                    board,
+                    &options.config,
+                    options.window_width,
+                    options.window_height,
+                );
+
+                info!("saving screenshot to {}", path);
+                png::save(&path, &image)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn run_3d(options: &Options) -> Result<(), String> {
+    let mut board = initial_board_3d(options);
+
+    for _ in 0..options.generations {
+        board.step();
+    }
+
+    info!("memory: lattice {}", format_bytes(board.memory_usage()));
+
+    println!(
+        "generation {}: population {}",
+        board.generation,
+        board.population()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::theme::Rgb;
+
+/// An in-memory RGBA image with 8 bits per channel, stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// An opaque image filled with one color.
+    pub fn new(width: usize, height: usize, color: Rgb) -> Self {
+        let pixels = [color.r, color.g, color.b, 255].repeat(width * height);
+
+        RgbaImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * self.width + x) * 4;
+        let mut pixel = [0; 4];
+
+        pixel.copy_from_slice(&self.pixels[offset..offset + 4]);
+
+        pixel
+    }
+
+    /// Coordinates of the pixels darker than `threshold`, a luminance from 0
+    /// (black) to 1 (white), with transparent pixels counting as white. With
+    /// `dither` set, Floyd-Steinberg error diffusion turns gray areas into a
+    /// matching density of dark pixels instead of all or nothing.
+    pub fn dark_pixels(&self, threshold: f64, dither: bool) -> Vec<(i32, i32)> {
+        let mut luminance: Vec<f64> = self
+            .pixels
+            .chunks_exact(4)
+            .map(|pixel| {
+                let gray = (0.2126 * pixel[0] as f64
+                    + 0.7152 * pixel[1] as f64
+                    + 0.0722 * pixel[2] as f64)
+                    / 255.0;
+                let alpha = pixel[3] as f64 / 255.0;
+
+                gray * alpha + (1.0 - alpha)
+            })
+            .collect();
+        let mut dark = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = luminance[y * self.width + x];
+                let is_dark = value < threshold;
+
+                if is_dark {
+                    dark.push((x as i32, y as i32));
+                }
+
+                if dither {
+                    let error = value - if is_dark { 0.0 } else { 1.0 };
+                    let mut spread = |dx: i32, dy: usize, weight: f64| {
+                        let (x, y) = (x as i32 + dx, y + dy);
+
+                        if x >= 0 && (x as usize) < self.width && y < self.height {
+                            luminance[y * self.width + x as usize] += error * weight;
+                        }
+                    };
+
+                    spread(1, 0, 7.0 / 16.0);
+                    spread(-1, 1, 3.0 / 16.0);
+                    spread(0, 1, 5.0 / 16.0);
+                    spread(1, 1, 1.0 / 16.0);
+                }
+            }
+        }
+
+        dark
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, pixel: [u8; 4]) {
+        let offset = (y * self.width + x) * 4;
+
+        self.pixels[offset..offset + 4].copy_from_slice(&pixel);
+    }
+
+    /// Draws `image` stretched over a rectangle, clipped to this image, by
+    /// nearest-neighbor sampling and blending by its alpha channel.
+    pub fn draw_scaled(&mut self, image: &RgbaImage, x: i64, y: i64, width: usize, height: usize) {
+        if image.width == 0 || image.height == 0 {
+            return;
+        }
+
+        for row in 0..height {
+            let target_y = y + row as i64;
+
+            if target_y < 0 || target_y >= self.height as i64 {
+                continue;
+            }
+
+            for column in 0..width {
+                let target_x = x + column as i64;
+
+                if target_x < 0 || target_x >= self.width as i64 {
+                    continue;
+                }
+
+                let source = image.pixel(column * image.width / width, row * image.height / height);
+                let target = self.pixel(target_x as usize, target_y as usize);
+                let alpha = source[3] as u32;
+                let blend = |index: usize| {
+                    ((source[index] as u32 * alpha + target[index] as u32 * (255 - alpha)) / 255)
+                        as u8
+                };
+
+                self.set_pixel(
+                    target_x as usize,
+                    target_y as usize,
+                    [blend(0), blend(1), blend(2), 255],
+                );
+            }
+        }
+    }
+
+    /// Fills a rectangle with an opaque color, clipped to the image.
+    pub fn fill_rect(&mut self, x: i64, y: i64, width: usize, height: usize, color: Rgb) {
+        let clip = |start: i64, length: usize, limit: usize| {
+            let end = (start + length as i64).clamp(0, limit as i64) as usize;
+
+            (start.clamp(0, limit as i64) as usize)..end
+        };
+        let pixel = [color.r, color.g, color.b, 255];
+
+        for row in clip(y, height, self.height) {
+            for column in clip(x, width, self.width) {
+                self.set_pixel(column, row, pixel);
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Fires spaceships by hand: works out which way a pattern travels and turns
+//! it to head in a chosen direction.
+
+use crate::pattern::Pattern;
+use crate::period::find_periodicity;
+use crate::rule::Rule;
+
+/// Longest period recognized when working out a pattern's heading.
+const MAX_PERIOD: usize = 32;
+
+/// Maps a cell to its position in a rotated or reflected pattern.
+type Orientation = fn((i32, i32)) -> (i32, i32);
+
+/// The rotations and reflections a pattern can be placed in.
+const ORIENTATIONS: [Orientation; 8] = [
+    |(x, y)| (x, y),
+    |(x, y)| (-y, x),
+    |(x, y)| (-x, -y),
+    |(x, y)| (y, -x),
+    |(x, y)| (-x, y),
+    |(x, y)| (x, -y),
+    |(x, y)| (y, x),
+    |(x, y)| (-y, -x),
+];
+
+/// A pattern ready to be launched, along with the way it travels.
+pub struct Launcher {
+    pub pattern: Pattern,
+    /// How far the pattern moves each period, or `None` if it does not
+    /// travel under the rule it was measured with.
+    pub heading: Option<(i32, i32)>,
+}
+
+impl Launcher {
+    pub fn new(pattern: Pattern, rule: Rule) -> Self {
+        let heading = heading(&pattern, rule);
+
+        Launcher { pattern, heading }
+    }
+
+    /// The pattern's cells, centered on the origin and turned so it travels
+    /// as close to `direction` as it can. Patterns that do not travel, and a
+    /// zero direction, keep their orientation.
+    pub fn aim(&self, direction: (i32, i32)) -> Vec<(i32, i32)> {
+        let orientation = match self.heading {
+            Some(heading) if direction != (0, 0) => ORIENTATIONS
+                .iter()
+                .max_by(|a, b| {
+                    alignment(a(heading), direction).total_cmp(&alignment(b(heading), direction))
+                })
+                .expect("there are orientations"),
+            _ => &ORIENTATIONS[0],
+        };
+        let cells: Vec<(i32, i32)> = self
+            .pattern
+            .cells
+            .iter()
+            .map(|cell| orientation(*cell))
+            .collect();
+        let (min_x, max_x) = bounds(cells.iter().map(|(x, _)| *x));
+        let (min_y, max_y) = bounds(cells.iter().map(|(_, y)| *y));
+        let center = ((min_x + max_x) / 2, (min_y + max_y) / 2);
+
+        cells
+            .iter()
+            .map(|(x, y)| (x - center.0, y - center.1))
+            .collect()
+    }
+}
+
+/// How far the pattern moves each period under the rule, found by running it
+/// until it repeats.
+pub fn heading(pattern: &Pattern, rule: Rule) -> Option<(i32, i32)> {
+    find_periodicity(&pattern.cells, rule, MAX_PERIOD)
+        .map(|periodicity| (periodicity.dx, periodicity.dy))
+        .filter(|heading| *heading != (0, 0))
+}
+
+/// Cosine of the angle between the two vectors.
+fn alignment(a: (i32, i32), b: (i32, i32)) -> f64 {
+    let length = |(x, y): (i32, i32)| ((x * x + y * y) as f64).sqrt();
+
+    (a.0 * b.0 + a.1 * b.1) as f64 / (length(a) * length(b))
+}
+
+fn bounds(values: impl Iterator<Item = i32>) -> (i32, i32) {
+    values.fold((i32::MAX, i32::MIN), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
@@ -0,0 +1,82 @@
+//! Conway's Game of Life engine.
+//!
+//! The engine has no windowing dependencies so it can be embedded in other
+//! programs or driven headlessly; the SDL frontend lives behind the `gui`
+//! cargo feature. Other window frontends draw with `raster` onto a
+//! `frontend::Surface`, of which `x11` is one.
+
+pub mod board;
+pub mod board3d;
+pub mod bookmark;
+pub mod brush;
+pub mod catalog;
+pub mod chart;
+pub mod checkpoint;
+pub mod chunk;
+pub mod clock;
+pub mod collide;
+pub mod config;
+pub mod demo;
+pub mod engine;
+pub mod evolve;
+pub mod font;
+pub mod frontend;
+pub mod golden;
+pub mod image;
+pub mod launcher;
+pub mod log;
+pub mod memory;
+pub mod net;
+pub mod pattern;
+pub mod period;
+pub mod png;
+pub mod pnm;
+pub mod predecessor;
+pub mod raster;
+pub mod region;
+pub mod replay;
+pub mod resources;
+pub mod rle;
+pub mod rng;
+pub mod rule;
+pub mod rule_search;
+pub mod script;
+pub mod session;
+pub mod stats;
+pub mod svg;
+pub mod theme;
+pub mod topology;
+pub mod trail;
+pub mod weighted;
+pub mod workspace;
+#[cfg(feature = "x11")]
+pub mod x11;
+pub mod zlib;
+
+pub use board::{Board, Cell, CellState, CYCLES_TO_DIE, MAX_TRAIL_LENGTH, SOUP_DENSITY};
+pub use board3d::{Board3d, Rule3d};
+pub use bookmark::Bookmarks;
+pub use brush::{Brush, BrushShape, Tool};
+pub use catalog::{Catalog, Category};
+pub use chart::PopulationHistory;
+pub use checkpoint::Checkpoints;
+pub use chunk::ChunkIndex;
+pub use clock::SimulationClock;
+pub use config::{Config, Smoothing};
+pub use engine::Engine;
+pub use image::RgbaImage;
+pub use launcher::Launcher;
+pub use pattern::{Pattern, Placement};
+pub use period::{PeriodDetector, Periodicity};
+pub use region::Region;
+pub use replay::{Input, Replay, ReplaySettings};
+pub use resources::{ResourceField, ResourceRule, ResourceScope};
+pub use rule::Rule;
+pub use script::{Script, ScriptCommand};
+pub use session::Session;
+pub use stats::{GenerationStats, StatsHistory};
+pub use theme::{Rgb, Theme};
+pub use topology::{Boundary, Topology};
+pub use trail::{DyingPolicy, FadeCurve};
+pub use weighted::WeightedRule;
+pub use workspace::Workspace;
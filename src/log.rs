@@ -0,0 +1,185 @@
+//! A small leveled logger. Messages go to stderr, or to a file when one is
+//! configured, as `<seconds since start> <LEVEL> <module>: <message>`.
+//!
+//! Errors are echoed to stderr as well when logging to a file.
+//!
+//! Use the `error!`, `warn!`, `info!`, `debug!` and `trace!` macros; they skip
+//! formatting entirely when their level is disabled. Until `init` is called
+//! only warnings and errors are written, to stderr.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    #[default]
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("unknown log level: {}", text)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+struct Logger {
+    level: Level,
+    file: Option<Mutex<File>>,
+    started_at: Instant,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Sets the most verbose level to write and, optionally, a file to append to
+/// instead of stderr. Only the first call has any effect.
+pub fn init(level: Level, path: Option<&str>) -> Result<(), String> {
+    let file = match path {
+        Some(path) => Some(Mutex::new(
+            File::options()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|error| format!("failed to open log file {}: {}", path, error))?,
+        )),
+        None => None,
+    };
+
+    let _ = LOGGER.set(Logger {
+        level,
+        file,
+        started_at: Instant::now(),
+    });
+
+    Ok(())
+}
+
+pub fn enabled(level: Level) -> bool {
+    level <= LOGGER.get().map_or(Level::default(), |logger| logger.level)
+}
+
+/// Writes a message; use the macros instead, which check `enabled` first.
+pub fn write(level: Level, module: &str, message: fmt::Arguments) {
+    let elapsed = LOGGER
+        .get()
+        .map_or(0.0, |logger| logger.started_at.elapsed().as_secs_f64());
+    let line = format!(
+        "{:10.4} {:5} {}: {}\n",
+        elapsed,
+        level.name(),
+        module,
+        message
+    );
+
+    // A failing log write has nowhere better to be reported, so it is
+    // dropped.
+    let _ = match LOGGER.get().and_then(|logger| logger.file.as_ref()) {
+        Some(file) => {
+            if level == Level::Error {
+                let _ = io::stderr().write_all(line.as_bytes());
+            }
+
+            file.lock()
+                .map_err(|_| io::Error::other("log file lock poisoned"))
+                .and_then(|mut file| file.write_all(line.as_bytes()))
+        }
+        None => io::stderr().write_all(line.as_bytes()),
+    };
+}
+
+/// Logs how long it lives when dropped, for timing a block of work.
+pub struct Span {
+    level: Level,
+    module: &'static str,
+    name: &'static str,
+    started_at: Instant,
+}
+
+impl Span {
+    pub fn new(level: Level, module: &'static str, name: &'static str) -> Self {
+        Span {
+            level,
+            module,
+            name,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if enabled(self.level) {
+            write(
+                self.level,
+                self.module,
+                format_args!("{} took {:?}", self.name, self.started_at.elapsed()),
+            );
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            $crate::log::write($level, module_path!(), format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+/// Starts a `Span` that logs at `$level` how long the enclosing block takes
+/// from here on.
+#[macro_export]
+macro_rules! span {
+    ($level:expr, $name:expr) => {
+        $crate::log::Span::new($level, module_path!(), $name)
+    };
+}
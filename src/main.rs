@@ -1,264 +1,225 @@
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
-use std::time::Duration;
-
-const WIDTH: usize = 800;
-const HEIGHT: usize = 800;
-const SCALE: f32 = 10.0;
-const CYCLES_TO_DIE: usize = 8;
-
-#[derive(Clone)]
-enum CellState {
-    Alive,
-    Dying(usize),
-    Dead,
-}
+use cli::{Command, Frontend, Options};
+use game_of_life::chart::{self, DEFAULT_CHART_SIZE};
+use game_of_life::{
+    error, info, log, png, rle, warn, Board, Board3d, Config, Pattern, Periodicity,
+    PopulationHistory, Replay, ResourceField, Rule, SOUP_DENSITY,
+};
+use std::env;
+use std::process;
+
+mod cli;
+mod commands;
+#[cfg(feature = "gui")]
+mod gui;
+mod headless;
+mod output;
+#[cfg(feature = "x11")]
+mod window;
+
+const SOUP_DENSITY_3D: f64 = 0.2;
+
+/// The starting board: the `--pattern`s placed on an empty board if given,
+/// otherwise a random soup when seeded, otherwise the glider gun.
+pub fn initial_board(options: &Options, seed: Option<u64>) -> Board {
+    let mut board = match (&options.pattern, seed) {
+        (Some(_), _) => {
+            let mut board = Board::empty(options.board_width, options.board_height);
+
+            for placement in options.placements.iter() {
+                placement.stamp(&mut board);
+            }
 
-#[derive(Clone)]
-struct Cell {
-    state: CellState,
-    neighbor_count: usize,
+            board
+        }
+        (None, Some(seed)) => Board::seeded(
+            options.board_width,
+            options.board_height,
+            seed,
+            SOUP_DENSITY,
+        ),
+        (None, None) => Board::new(options.board_width, options.board_height),
+    };
+
+    board.set_trail_length(options.config.trail_length);
+    board.topology = options.topology;
+    board.engine = options.engine;
+    board.rule = options
+        .rule
+        .unwrap_or_else(|| pattern_rule(options.pattern.as_ref()));
+    board.weighted_rule = options.config.weighted_rule;
+    board.poison_chance = options.config.poison_chance;
+    board.dying_policy = options.config.dying_policy;
+    board.resources = resource_field(options, &board);
+
+    board
 }
 
-struct Board {
-    generation: usize,
-    width: usize,
-    height: usize,
-    cells: Vec<Cell>,
+/// A full resource field for the board when the config asks for resource
+/// dynamics.
+pub fn resource_field(options: &Options, board: &Board) -> Option<ResourceField> {
+    options
+        .config
+        .resource_rule
+        .map(|rule| ResourceField::new(rule, board.width * board.height))
 }
 
-impl Cell {
-    pub fn dead() -> Self {
-        Cell {
-            state: CellState::Dead,
-            neighbor_count: 0,
-        }
-    }
-
-    pub fn alive() -> Self {
-        Cell {
-            state: CellState::Alive,
-            neighbor_count: 0,
-        }
+/// The rule named by the pattern file, falling back to Conway's Life when
+/// there is none or it isn't a B/S rule.
+pub fn pattern_rule(pattern: Option<&Pattern>) -> Rule {
+    match pattern.and_then(|pattern| pattern.rule.as_deref()) {
+        Some(name) => Rule::parse(name).unwrap_or_else(|error| {
+            warn!("{}; running Conway's Life", error);
+            Rule::default()
+        }),
+        None => Rule::default(),
     }
 }
 
-impl Board {
-    pub fn new(width: usize, height: usize) -> Self {
-        let cells = vec![Cell::dead(); width * height];
-        let mut board = Self {
-            generation: 0,
-            width,
-            height,
-            cells,
-        };
-
-        board.add_glider_gun();
-
-        board
-    }
+/// The starting 3D lattice: a random soup in its center.
+pub fn initial_board_3d(options: &Options) -> Board3d {
+    let (width, height, depth) = options.lattice;
+
+    Board3d::seeded(
+        width,
+        height,
+        depth,
+        options.rule3d,
+        options.seed.unwrap_or_default(),
+        SOUP_DENSITY_3D,
+    )
+}
 
-    pub fn step(self: &mut Self) {
-        for cell in self.cells.iter_mut() {
-            match cell.state {
-                CellState::Alive => {
-                    if cell.neighbor_count < 2 || cell.neighbor_count > 3 {
-                        cell.state = CellState::Dying(CYCLES_TO_DIE)
-                    }
-                }
-                CellState::Dying(cycles_left) => {
-                    if cell.neighbor_count == 3 {
-                        cell.state = CellState::Alive
-                    } else if cycles_left == 0 {
-                        cell.state = CellState::Dead
-                    } else {
-                        cell.state = CellState::Dying(cycles_left - 1)
-                    }
-                }
-                CellState::Dead => {
-                    if cell.neighbor_count == 3 {
-                        cell.state = CellState::Alive
-                    }
-                }
-            }
+/// Loads the `--replay` file if given, along with the seed the session should
+/// start from (the replay's seed takes precedence over `--seed`).
+pub fn load_playback(options: &Options) -> Result<(Option<Replay>, Option<u64>), String> {
+    match &options.replay {
+        Some(path) => {
+            let replay = Replay::load(path)?;
+            let seed = replay.seed;
+            Ok((Some(replay), seed))
         }
+        None => Ok((None, options.seed)),
     }
+}
 
-    pub fn update_live_neighbor_counts(self: &mut Self) {
-        let neighbor_counts: Vec<usize> = self
-            .cells
-            .iter()
-            .enumerate()
-            .map(|(index, _cell)| self.live_neighbor_count(index))
-            .collect();
-
-        for (index, cell) in self.cells.iter_mut().enumerate() {
-            cell.neighbor_count = neighbor_counts[index];
+/// Encodes the board's live cells as RLE, keeping the name, author and
+/// comments of the pattern it was started from and appending any period
+/// analysis.
+pub fn export_rle(
+    board: &Board,
+    source: Option<&Pattern>,
+    periodicity: Option<&Periodicity>,
+) -> String {
+    let mut pattern = match source {
+        Some(source) => Pattern {
+            cells: board.live_cells(),
+            ..source.clone()
+        },
+        None => Pattern::new("board", board.live_cells()),
+    };
+
+    pattern.rule = Some(board.rule.name());
+
+    if let Some(periodicity) = periodicity {
+        if source.is_none() {
+            pattern.name = periodicity.name();
         }
-    }
 
-    pub fn index_to_coordinates(self: &Self, index: usize) -> (i32, i32) {
-        let x = index.wrapping_rem(self.width) as i32;
-        let y = index.wrapping_div(self.width) as i32;
-
-        (x, y)
+        pattern
+            .comments
+            .extend(periodicity.comments(board.generation));
     }
 
-    fn coordinates_to_index(self: &Self, x: i32, y: i32) -> Option<usize> {
-        if x < 0 || x >= (self.width as i32) {
-            return None;
-        }
-
-        if y < 0 || y >= (self.height as i32) {
-            return None;
-        }
+    rle::encode(&pattern)
+}
 
-        Some((y as usize) * self.width + (x as usize))
-    }
+/// Charts the population history as a PNG in the config's theme.
+pub fn save_population_chart(
+    path: &str,
+    history: &PopulationHistory,
+    config: &Config,
+) -> Result<(), String> {
+    info!("saving population chart to {}", path);
+
+    png::save(
+        path,
+        &chart::population_chart(history.samples(), &config.theme, DEFAULT_CHART_SIZE),
+    )
+}
 
-    fn live_neighbor_count(self: &Self, index: usize) -> usize {
-        let (x, y) = self.index_to_coordinates(index);
-        let cell_indices = vec![
-            self.coordinates_to_index(x - 1, y - 1),
-            self.coordinates_to_index(x, y - 1),
-            self.coordinates_to_index(x + 1, y - 1),
-            self.coordinates_to_index(x - 1, y),
-            self.coordinates_to_index(x + 1, y),
-            self.coordinates_to_index(x - 1, y + 1),
-            self.coordinates_to_index(x, y + 1),
-            self.coordinates_to_index(x + 1, y + 1),
-        ];
+/// Where a script's screenshot goes: the path it names, or one named after
+/// the generation.
+pub fn screenshot_path(path: Option<&str>, generation: usize) -> String {
+    path.map_or_else(
+        || format!("screenshot-gen{}.png", generation),
+        |path| path.to_string(),
+    )
+}
 
-        cell_indices
-            .iter()
-            .filter(|maybe_index| match maybe_index {
-                Some(index) => match self.cells[*index].state {
-                    CellState::Alive => true,
-                    _ => false,
-                },
-                None => false,
-            })
-            .count()
-    }
+/// How many steps a `goto` takes from the board's generation; going back
+/// isn't possible.
+pub fn goto_steps(board: &Board, target: usize) -> Result<usize, String> {
+    target.checked_sub(board.generation).ok_or_else(|| {
+        format!(
+            "can't go back to generation {} from {}",
+            target, board.generation
+        )
+    })
+}
 
-    fn add_glider_gun(self: &mut Self) {
-        for (x, y) in [
-            (25, 1),
-            (23, 2),
-            (25, 2),
-            (13, 3),
-            (14, 3),
-            (21, 3),
-            (22, 3),
-            (35, 3),
-            (36, 3),
-            (12, 4),
-            (16, 4),
-            (21, 4),
-            (22, 4),
-            (35, 4),
-            (36, 4),
-            (1, 5),
-            (2, 5),
-            (11, 5),
-            (17, 5),
-            (21, 5),
-            (22, 5),
-            (1, 6),
-            (2, 6),
-            (11, 6),
-            (15, 6),
-            (17, 6),
-            (18, 6),
-            (23, 6),
-            (25, 6),
-            (11, 7),
-            (17, 7),
-            (25, 7),
-            (12, 8),
-            (16, 8),
-            (13, 9),
-            (14, 9),
-        ]
-        .iter()
-        {
-            self.cells[x + y * self.width] = Cell::alive();
-        }
+pub fn main() {
+    if let Err(error) = run() {
+        error!("{}", error);
+        process::exit(1);
     }
 }
 
-fn draw(mut canvas: &mut Canvas<Window>, board: &mut Board) {
-    board.generation += 1;
-
-    canvas.set_draw_color(Color::RGB(255, 255, 255));
-    canvas.clear();
-
-    board.update_live_neighbor_counts();
-    board.step();
-
-    for (index, cell) in board.cells.iter().enumerate() {
-        match cell.state {
-            CellState::Alive => {
-                draw_cell(&mut canvas, board, index, Color::RGB(0, 0, 0));
-            }
-            CellState::Dying(cycles_left) => {
-                let percent_done: f32 = (cycles_left as f32) / (CYCLES_TO_DIE as f32);
-                let intensity: u8 = ((-0.25 * percent_done).exp() * 255.0) as u8;
-                draw_cell(
-                    &mut canvas,
-                    board,
-                    index,
-                    Color::RGB(intensity, intensity, intensity),
-                );
-            }
-            CellState::Dead => {}
-        }
+fn run() -> Result<(), String> {
+    let options = Options::parse(env::args())?;
+
+    log::init(options.log_level, options.log_file.as_deref())?;
+
+    match (&options.command, options.headless, options.dimensions) {
+        (Command::Predecessor, _, _) => commands::predecessor(&options),
+        (Command::Stress, _, _) => commands::stress(&options),
+        (Command::Evolve, _, _) => commands::evolve(&options),
+        (Command::Collide, _, _) => commands::collide(&options),
+        (Command::Diverge, _, _) => commands::diverge(&options),
+        (Command::RuleSearch, _, _) => commands::rule_search(&options),
+        (Command::Run, _, _) if options.verify => commands::verify(&options),
+        (Command::Run, true, 3) => headless::run_3d(&options),
+        (Command::Run, true, _) => headless::run(&options),
+        (Command::Run, false, _) if options.frontend == Frontend::X11 => run_x11(&options),
+        (Command::Run, false, _) => run_gui(&options),
     }
 }
 
-fn draw_cell(canvas: &mut Canvas<Window>, board: &Board, index: usize, color: Color) {
-    let (x, y) = board.index_to_coordinates(index);
+#[cfg(feature = "x11")]
+fn run_x11(options: &Options) -> Result<(), String> {
+    let mut surface = game_of_life::x11::X11Window::open(
+        options.window_width,
+        options.window_height,
+        "Rusty Game of Life",
+    )?;
 
-    canvas.set_draw_color(color);
-    canvas.draw_point((x, y)).expect("failed to draw pixel")
+    window::run(&mut surface, "x11", options)
 }
 
-pub fn main() -> Result<(), String> {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-
-    let window = video_subsystem
-        .window("Rusty Game of Life", WIDTH as u32, HEIGHT as u32)
-        .position_centered()
-        .build()
-        .unwrap();
-
-    let mut canvas = window.into_canvas().build().unwrap();
-    let mut event_pump = sdl_context.event_pump().unwrap();
-    let mut board = Board::new(WIDTH, HEIGHT);
-
-    canvas.set_scale(SCALE, SCALE)?;
-
-    'running: loop {
-        draw(&mut canvas, &mut board);
-
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => break 'running,
-                _ => {}
-            }
-        }
+#[cfg(not(feature = "x11"))]
+fn run_x11(_options: &Options) -> Result<(), String> {
+    Err("built without the `x11` feature".to_string())
+}
 
-        canvas.present();
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+#[cfg(feature = "gui")]
+fn run_gui(options: &Options) -> Result<(), String> {
+    if options.dimensions == 3 {
+        return gui::run_3d(options);
     }
 
-    Ok(())
+    gui::run(options)
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_options: &Options) -> Result<(), String> {
+    Err("built without the `gui` feature; run with --headless".to_string())
 }
@@ -0,0 +1,54 @@
+//! Memory footprint reporting, so frontends can show where memory goes and
+//! trim caches when a configured budget is exceeded.
+
+/// Approximate heap and inline bytes used by each named part of a run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryReport {
+    pub components: Vec<(&'static str, usize)>,
+}
+
+impl MemoryReport {
+    pub fn add(&mut self, name: &'static str, bytes: usize) {
+        self.components.push((name, bytes));
+    }
+
+    pub fn total(&self) -> usize {
+        self.components.iter().map(|(_, bytes)| bytes).sum()
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Parses a byte count with an optional `K`, `M` or `G` (binary) suffix,
+/// e.g. `512M`.
+pub fn parse_bytes(text: &str) -> Result<usize, String> {
+    let text = text.trim();
+    let (number, multiplier) = match text.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&text[..text.len() - 1], 1 << 10),
+        Some('M') => (&text[..text.len() - 1], 1 << 20),
+        Some('G') => (&text[..text.len() - 1], 1 << 30),
+        _ => (text, 1),
+    };
+    let number: usize = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size: {}", text))?;
+
+    Ok(number * multiplier)
+}
@@ -0,0 +1,80 @@
+//! Fetching patterns over HTTP(S), behind the `net` cargo feature.
+//!
+//! Downloads are delegated to the system `curl` binary so the crate itself
+//! stays free of a TLS stack.
+
+/// How long a download may take before curl gives up on it.
+pub const FETCH_TIMEOUT_SECS: u64 = 30;
+/// The largest response [`fetch`] accepts; patterns are far smaller.
+pub const MAX_FETCH_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Whether the string looks like something [`fetch`] can download.
+pub fn is_url(text: &str) -> bool {
+    text.starts_with("https://") || text.starts_with("http://")
+}
+
+/// Downloads the URL and returns the response body as text, giving up after
+/// `FETCH_TIMEOUT_SECS` or `MAX_FETCH_BYTES`. This blocks until then, so
+/// interactive callers run it on a thread of their own.
+#[cfg(feature = "net")]
+pub fn fetch(url: &str) -> Result<String, String> {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+
+    crate::debug!("fetching {} with curl", url);
+
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", &FETCH_TIMEOUT_SECS.to_string()])
+        .args(["--max-filesize", &MAX_FETCH_BYTES.to_string()])
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => format!(
+                "cannot fetch {}: the `net` feature downloads with curl, which isn't on the PATH",
+                url
+            ),
+            _ => format!("failed to run curl to fetch {}: {}", url, error),
+        })?;
+    let mut body = Vec::new();
+
+    // curl only enforces --max-filesize when the server announces the size,
+    // so the body is capped here too.
+    if let Some(stdout) = child.stdout.take() {
+        stdout
+            .take(MAX_FETCH_BYTES + 1)
+            .read_to_end(&mut body)
+            .map_err(|error| format!("failed to read {}: {}", url, error))?;
+    }
+
+    if body.len() as u64 > MAX_FETCH_BYTES {
+        let _ = child.kill();
+        let _ = child.wait();
+
+        return Err(format!("{} is larger than {} bytes", url, MAX_FETCH_BYTES));
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|error| format!("failed to run curl to fetch {}: {}", url, error))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "failed to fetch {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    String::from_utf8(body).map_err(|_| format!("{} is not valid UTF-8 text", url))
+}
+
+#[cfg(not(feature = "net"))]
+pub fn fetch(url: &str) -> Result<String, String> {
+    Err(format!(
+        "cannot fetch {}: built without the `net` feature",
+        url
+    ))
+}
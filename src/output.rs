@@ -0,0 +1,335 @@
+//! The files and processes a run writes to as it goes, and the summary it
+//! prints at the end.
+
+use crate::cli::Options;
+use game_of_life::clock::format_duration;
+use game_of_life::{
+    debug, info, png, raster, rle, warn, Board, Config, GenerationStats, Pattern, Periodicity,
+};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::{self, Child, ChildStdin, Stdio};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Writes per-generation statistics as CSV rows.
+pub struct StatsFile {
+    path: String,
+    writer: BufWriter<File>,
+}
+
+impl StatsFile {
+    /// Creates the `--stats` file, if one was asked for, and writes its
+    /// header.
+    pub fn create(options: &Options) -> Result<Option<Self>, String> {
+        let path = match &options.stats {
+            Some(path) => path.clone(),
+            None => return Ok(None),
+        };
+        let file =
+            File::create(&path).map_err(|error| format!("failed to create {}: {}", path, error))?;
+        let mut stats_file = StatsFile {
+            path,
+            writer: BufWriter::new(file),
+        };
+
+        stats_file.write_line(GenerationStats::CSV_HEADER)?;
+
+        Ok(Some(stats_file))
+    }
+
+    pub fn write(&mut self, stats: &GenerationStats) -> Result<(), String> {
+        self.write_line(&stats.to_csv_row())
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.writer, "{}", line)
+            .map_err(|error| format!("failed to write {}: {}", self.path, error))
+    }
+}
+
+/// Writes each generation's state hash as a `<generation> <hash>` line, for
+/// comparing runs across engines, builds and machines.
+pub struct HashLog {
+    path: String,
+    writer: BufWriter<File>,
+}
+
+impl HashLog {
+    /// Creates the `--hash-log` file, if one was asked for.
+    pub fn create(options: &Options) -> Result<Option<Self>, String> {
+        let path = match &options.hash_log {
+            Some(path) => path.clone(),
+            None => return Ok(None),
+        };
+        let file =
+            File::create(&path).map_err(|error| format!("failed to create {}: {}", path, error))?;
+
+        Ok(Some(HashLog {
+            path,
+            writer: BufWriter::new(file),
+        }))
+    }
+
+    pub fn write(&mut self, board: &Board) -> Result<(), String> {
+        writeln!(
+            self.writer,
+            "{} {:016x}",
+            board.generation,
+            board.state_hash()
+        )
+        .map_err(|error| format!("failed to write {}: {}", self.path, error))
+    }
+}
+
+/// Streams the board to a `--pipe-command` child's stdin, one line per
+/// generation, so external analyzers and visualizers in any language can
+/// follow a run. A `full <generation> <x> <y> <rle body>` line sends every
+/// live cell as an RLE body whose top-left corner is at `x`, `y`; a
+/// `delta <generation> +<x>,<y> ... -<x>,<y> ...` line lists the cells born
+/// and died since the previous line. The first line is always full.
+pub struct Pipe {
+    command: String,
+    child: Child,
+    /// Taken when the pipe is dropped, which closes the child's stdin.
+    writer: Option<BufWriter<ChildStdin>>,
+    /// Sends a full line every this many generations instead of a delta.
+    full_every: Option<usize>,
+    /// The live cells as of the last line sent, in cell order.
+    last_sent: Vec<bool>,
+}
+
+impl Pipe {
+    /// Starts the `--pipe-command` through the shell, if one was asked for.
+    pub fn spawn(options: &Options) -> Result<Option<Self>, String> {
+        let command = match &options.pipe_command {
+            Some(command) => command.clone(),
+            None => return Ok(None),
+        };
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let mut child = process::Command::new(shell)
+            .arg(flag)
+            .arg(&command)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|error| format!("failed to start {}: {}", command, error))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| format!("failed to open the stdin of {}", command))?;
+
+        info!("piping generations to {}", command);
+
+        Ok(Some(Pipe {
+            command,
+            child,
+            writer: Some(BufWriter::new(stdin)),
+            full_every: options.pipe_full_every,
+            last_sent: Vec::new(),
+        }))
+    }
+
+    /// Sends the board's generation, in full if it is the first, the board
+    /// was resized or `full_every` says so, otherwise as a delta.
+    pub fn write(&mut self, board: &Board) -> Result<(), String> {
+        let alive = board.alive_mask();
+        let full = alive.len() != self.last_sent.len()
+            || self
+                .full_every
+                .is_some_and(|every| board.generation.is_multiple_of(every));
+        let line = if full {
+            let cells = board.live_cells();
+            let (x, y) = Pattern::new("board", cells.clone()).origin();
+
+            format!(
+                "full {} {} {} {}",
+                board.generation,
+                x,
+                y,
+                rle::encode_body(&cells)
+            )
+        } else {
+            let mut line = format!("delta {}", board.generation);
+            let changed = |born: bool| {
+                alive
+                    .iter()
+                    .zip(&self.last_sent)
+                    .enumerate()
+                    .filter(move |(_, (now, before))| **now == born && **before != born)
+                    .map(|(index, _)| board.index_to_coordinates(index))
+            };
+
+            for (x, y) in changed(true) {
+                line.push_str(&format!(" +{},{}", x, y));
+            }
+
+            for (x, y) in changed(false) {
+                line.push_str(&format!(" -{},{}", x, y));
+            }
+
+            line
+        };
+
+        self.last_sent = alive;
+
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => return Ok(()),
+        };
+
+        writeln!(writer, "{}", line)
+            .and_then(|_| writer.flush())
+            .map_err(|error| format!("failed to write to {}: {}", self.command, error))
+    }
+}
+
+impl Drop for Pipe {
+    /// Closes the child's stdin so it sees the end of the run, then waits
+    /// for it to finish.
+    fn drop(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            let _ = writer.flush();
+        }
+
+        match self.child.wait() {
+            Ok(status) if !status.success() => warn!("{} exited with {}", self.command, status),
+            Ok(_) => {}
+            Err(error) => warn!("failed to wait for {}: {}", self.command, error),
+        }
+    }
+}
+
+/// Renders every Nth generation to a numbered PNG for assembling into a
+/// video.
+pub struct Timelapse {
+    directory: String,
+    every: usize,
+    size: (usize, usize),
+    frame: usize,
+}
+
+impl Timelapse {
+    /// Creates the `--timelapse-dir` directory, if a time-lapse was asked
+    /// for.
+    pub fn create(options: &Options) -> Result<Option<Self>, String> {
+        let every = match options.timelapse_every {
+            Some(every) => every,
+            None => return Ok(None),
+        };
+
+        fs::create_dir_all(&options.timelapse_dir)
+            .map_err(|error| format!("failed to create {}: {}", options.timelapse_dir, error))?;
+
+        Ok(Some(Timelapse {
+            directory: options.timelapse_dir.clone(),
+            every,
+            size: options.timelapse_size,
+            frame: 0,
+        }))
+    }
+
+    /// Saves the next frame if the board is on a multiple of `every`.
+    pub fn capture(&mut self, board: &Board, config: &Config) -> Result<(), String> {
+        if !board.generation.is_multiple_of(self.every) {
+            return Ok(());
+        }
+
+        let path = Path::new(&self.directory).join(format!("frame-{:06}.png", self.frame));
+        let image = raster::render_fit(board, config, self.size.0, self.size.1);
+
+        debug!("saving time-lapse frame {}", path.display());
+        self.frame += 1;
+
+        png::save(&path.to_string_lossy(), &image)
+    }
+}
+
+/// What a run did, printed when it ends and appended to `--runs-log` if
+/// given.
+pub struct RunSummary {
+    mode: &'static str,
+    started_at: Instant,
+    generations: usize,
+    peak_population: usize,
+}
+
+impl RunSummary {
+    pub const CSV_HEADER: &'static str = "finished_at,mode,generations,wall_seconds,generations_per_second,peak_population,final_population,period";
+
+    /// Starts timing a run, `headless` or `gui`, from the starting board.
+    pub fn start(mode: &'static str, board: &Board) -> Self {
+        RunSummary {
+            mode,
+            started_at: Instant::now(),
+            generations: 0,
+            peak_population: board.population(),
+        }
+    }
+
+    /// Counts a generation stepped to with the given population.
+    pub fn record(&mut self, population: usize) {
+        self.generations += 1;
+        self.peak_population = self.peak_population.max(population);
+    }
+
+    /// Prints the summary and appends it to the `--runs-log`, if any.
+    pub fn finish(
+        &self,
+        board: &Board,
+        periodicity: Option<&Periodicity>,
+        options: &Options,
+    ) -> Result<(), String> {
+        let wall_time = self.started_at.elapsed();
+        let rate = self.generations as f64 / wall_time.as_secs_f64().max(f64::EPSILON);
+        let final_population = board.population();
+
+        println!("run summary:");
+        println!("  generations simulated: {}", self.generations);
+        println!("  wall time: {}", format_duration(wall_time));
+        println!("  generations per second: {:.1}", rate);
+        println!("  peak population: {}", self.peak_population);
+        println!("  final population: {}", final_population);
+        println!(
+            "  period: {}",
+            periodicity.map_or("none detected".to_string(), Periodicity::describe)
+        );
+
+        let path = match &options.runs_log {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let row = format!(
+            "{},{},{},{:.3},{:.1},{},{},{}",
+            finished_at,
+            self.mode,
+            self.generations,
+            wall_time.as_secs_f64(),
+            rate,
+            self.peak_population,
+            final_population,
+            periodicity.map_or(String::new(), |periodicity| periodicity.period.to_string())
+        );
+        let is_new = fs::metadata(path).map_or(true, |metadata| metadata.len() == 0);
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| format!("failed to open {}: {}", path, error))?;
+
+        info!("appending run summary to {}", path);
+
+        if is_new {
+            writeln!(file, "{}", Self::CSV_HEADER)
+                .map_err(|error| format!("failed to write {}: {}", path, error))?;
+        }
+
+        writeln!(file, "{}", row).map_err(|error| format!("failed to write {}: {}", path, error))
+    }
+}
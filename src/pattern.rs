@@ -0,0 +1,223 @@
+use crate::board::Board;
+use crate::catalog::Catalog;
+use crate::net;
+use crate::rle;
+use crate::rule::Rule;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub name: String,
+    pub cells: Vec<(i32, i32)>,
+    /// Provenance carried along from pattern files so it survives a round
+    /// trip through the program.
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    /// The rulestring the pattern was written for, if its file named one.
+    pub rule: Option<String>,
+}
+
+impl Pattern {
+    pub fn new(name: &str, cells: Vec<(i32, i32)>) -> Self {
+        Pattern {
+            name: name.to_string(),
+            cells,
+            author: None,
+            comments: Vec::new(),
+            rule: None,
+        }
+    }
+
+    /// Reads a pattern file, or downloads it if given an http(s) URL,
+    /// choosing the format from its extension.
+    pub fn load(path: &str) -> Result<Self, String> {
+        crate::info!("loading pattern {}", path);
+
+        let text = if net::is_url(path) {
+            net::fetch(path)?
+        } else {
+            fs::read_to_string(path)
+                .map_err(|error| format!("failed to read pattern {}: {}", path, error))?
+        };
+
+        Self::parse(path, &text)
+    }
+
+    /// Parses pattern text, choosing the format from the extension of the
+    /// file or URL it came from.
+    pub fn parse(source: &str, text: &str) -> Result<Self, String> {
+        let path = source.split(['?', '#']).next().unwrap_or(source);
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let mut pattern = match extension.as_str() {
+            "rle" => rle::decode(text),
+            _ => Err(format!("unsupported pattern format: {}", source)),
+        }
+        .map_err(|error| format!("{}: {}", source, error))?;
+
+        if pattern.name.is_empty() {
+            pattern.name = Path::new(path)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("pattern")
+                .to_string();
+        }
+
+        Ok(pattern)
+    }
+
+    /// A one line summary of the pattern's name and author.
+    pub fn title(&self) -> String {
+        match &self.author {
+            Some(author) => format!("{} by {}", self.name, author),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Size of the pattern's bounding box.
+    pub fn size(&self) -> (usize, usize) {
+        if self.cells.is_empty() {
+            return (0, 0);
+        }
+
+        let (min_x, min_y, max_x, max_y) = self.cells.iter().fold(
+            (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+            |(min_x, min_y, max_x, max_y), (x, y)| {
+                (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+            },
+        );
+
+        ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize)
+    }
+
+    /// The top left corner of the pattern's bounding box.
+    pub fn origin(&self) -> (i32, i32) {
+        self.cells
+            .iter()
+            .fold(None, |origin, &(x, y)| match origin {
+                Some((left, top)) => Some((x.min(left), y.min(top))),
+                None => Some((x, y)),
+            })
+            .unwrap_or((0, 0))
+    }
+
+    /// The pattern turned clockwise by `degrees`, a multiple of 90, about
+    /// the top left corner of its bounding box, which stays where it is.
+    pub fn rotated(&self, degrees: u32) -> Self {
+        let turn: fn((i32, i32)) -> (i32, i32) = match degrees % 360 {
+            90 => |(x, y)| (-y, x),
+            180 => |(x, y)| (-x, -y),
+            270 => |(x, y)| (y, -x),
+            _ => |cell| cell,
+        };
+        let mut rotated = self.clone();
+
+        rotated.cells = self.cells.iter().map(|cell| turn(*cell)).collect();
+
+        let ((left, top), (new_left, new_top)) = (self.origin(), rotated.origin());
+
+        for (x, y) in rotated.cells.iter_mut() {
+            *x += left - new_left;
+            *y += top - new_top;
+        }
+
+        rotated
+    }
+
+    /// The pattern after running `generations` generations on its own under
+    /// `rule`, in the same coordinates. The detached board leaves room for
+    /// light-speed growth, so nothing is clipped by its edges.
+    pub fn advanced(&self, rule: Rule, generations: usize) -> Self {
+        let mut advanced = self.clone();
+
+        if generations == 0 || self.cells.is_empty() {
+            return advanced;
+        }
+
+        let margin = generations as i32 + 1;
+        let (width, height) = self.size();
+        let (left, top) = self.origin();
+        let mut board = Board::empty(width + 2 * margin as usize, height + 2 * margin as usize);
+
+        board.rule = rule;
+        board.stamp(self, margin - left, margin - top);
+        board.step_n(generations);
+
+        advanced.cells = board
+            .live_cells()
+            .into_iter()
+            .map(|(x, y)| (x + left - margin, y + top - margin))
+            .collect();
+        advanced
+    }
+
+    /// Looks up one of the built-in patterns by its catalog key.
+    pub fn builtin(key: &str) -> Option<Self> {
+        Catalog::embedded()
+            .find(key)
+            .map(|entry| entry.pattern.clone())
+    }
+}
+
+/// A pattern placed on the starting board, as given by a
+/// `<file>@<x>,<y>[,<rotation>]` specification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    /// The pattern, already rotated.
+    pub pattern: Pattern,
+    /// Where the top left corner of the pattern's bounding box goes, or
+    /// `None` to center it on the board.
+    pub position: Option<(i32, i32)>,
+}
+
+impl Placement {
+    /// Parses a specification and loads its pattern. A bare path or URL is
+    /// centered unrotated; rotations are clockwise degrees, a multiple of 90.
+    /// Only a trailing `@` followed by two or three numbers places the
+    /// pattern, so sources with an `@` of their own, like URLs with
+    /// credentials, load as they are.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let placed = spec.rsplit_once('@').and_then(|(path, fields)| {
+            let numbers = fields
+                .split(',')
+                .map(|field| field.trim().parse::<i32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+
+            match numbers.as_slice() {
+                [x, y] => Some((path, (*x, *y), 0)),
+                [x, y, degrees] => Some((path, (*x, *y), *degrees)),
+                _ => None,
+            }
+        });
+        let (path, position, degrees) = match placed {
+            Some((path, position, degrees)) => (path, Some(position), degrees),
+            None => (spec, None, 0),
+        };
+
+        if degrees.rem_euclid(90) != 0 {
+            return Err(format!("rotation must be a multiple of 90: {}", degrees));
+        }
+
+        Ok(Placement {
+            pattern: Pattern::load(path)?.rotated(degrees.rem_euclid(360) as u32),
+            position,
+        })
+    }
+
+    /// Stamps the pattern onto the board, clipping anything that falls
+    /// outside it.
+    pub fn stamp(&self, board: &mut Board) {
+        let (left, top) = self.pattern.origin();
+        let (width, height) = self.pattern.size();
+        let (x, y) = self.position.unwrap_or((
+            (board.width as i32 - width as i32) / 2,
+            (board.height as i32 - height as i32) / 2,
+        ));
+
+        board.stamp(&self.pattern, x - left, y - top);
+    }
+}
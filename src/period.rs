@@ -0,0 +1,306 @@
+use crate::board::Board;
+use crate::rule::Rule;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// A repeating pattern found by the [`PeriodDetector`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Periodicity {
+    pub period: usize,
+    /// How far the pattern moves each period.
+    pub dx: i32,
+    pub dy: i32,
+    /// Average number of cells that are born or die per generation.
+    pub heat: f64,
+}
+
+/// Watches successive generations for the live cells repeating, possibly at
+/// a different position.
+pub struct PeriodDetector {
+    max_period: usize,
+    history: VecDeque<Snapshot>,
+}
+
+struct Snapshot {
+    generation: usize,
+    /// Top left corner of the live cells' bounding box.
+    origin: (i32, i32),
+    /// Live cells relative to `origin`, in row-major order.
+    shape: Vec<(i32, i32)>,
+    hash: u64,
+    /// Cells that changed state since the previous snapshot.
+    changes: usize,
+}
+
+impl Periodicity {
+    pub fn is_still_life(&self) -> bool {
+        self.period == 1 && self.dx == 0 && self.dy == 0
+    }
+
+    pub fn is_spaceship(&self) -> bool {
+        self.dx != 0 || self.dy != 0
+    }
+
+    /// Speed in the usual notation, e.g. `c/4 diagonal`, `2c/5 orthogonal`
+    /// or, for oblique ships, which move a different distance along each
+    /// axis, `(2,1)c/6 oblique`.
+    pub fn velocity(&self) -> String {
+        let (dx, dy) = (
+            self.dx.unsigned_abs() as usize,
+            self.dy.unsigned_abs() as usize,
+        );
+
+        if dx != 0 && dy != 0 && dx != dy {
+            let divisor = gcd(gcd(dx, dy), self.period);
+
+            return format!(
+                "({},{})c/{} oblique",
+                dx / divisor,
+                dy / divisor,
+                self.period / divisor
+            );
+        }
+
+        let distance = dx.max(dy);
+        let divisor = gcd(distance, self.period);
+        let (distance, period) = (distance / divisor, self.period / divisor);
+        let speed = match (distance, period) {
+            (1, 1) => "c".to_string(),
+            (1, _) => format!("c/{}", period),
+            (_, 1) => format!("{}c", distance),
+            _ => format!("{}c/{}", distance, period),
+        };
+        let direction = if dx == 0 || dy == 0 {
+            "orthogonal"
+        } else {
+            "diagonal"
+        };
+
+        format!("{} {}", speed, direction)
+    }
+
+    /// A short name for the pattern, e.g. `c/4 diagonal spaceship`.
+    pub fn name(&self) -> String {
+        if self.is_spaceship() {
+            format!("{} spaceship", self.velocity())
+        } else if self.is_still_life() {
+            "still life".to_string()
+        } else {
+            format!("p{} oscillator", self.period)
+        }
+    }
+
+    /// The analysis as pattern file comments.
+    pub fn comments(&self, generation: usize) -> Vec<String> {
+        let mut comments = vec![format!("Period: {}", self.period)];
+
+        if self.is_spaceship() {
+            comments.push(format!("Displacement: ({}, {})", self.dx, self.dy));
+            comments.push(format!("Velocity: {}", self.velocity()));
+        }
+
+        comments.push(format!("Heat: {:.2}", self.heat));
+        comments.push(format!("Found at generation {}", generation));
+
+        comments
+    }
+
+    pub fn describe(&self) -> String {
+        if self.is_spaceship() {
+            format!(
+                "spaceship {}, period {}, heat {:.1}",
+                self.velocity(),
+                self.period,
+                self.heat
+            )
+        } else if self.is_still_life() {
+            "still life".to_string()
+        } else {
+            format!("oscillator period {}, heat {:.1}", self.period, self.heat)
+        }
+    }
+}
+
+/// Runs the cells alone on an empty board under the rule, for up to twice
+/// `max_period` generations, and reports their period if they repeat.
+pub fn find_periodicity(
+    cells: &[(i32, i32)],
+    rule: Rule,
+    max_period: usize,
+) -> Option<Periodicity> {
+    if cells.is_empty() {
+        return None;
+    }
+
+    // Room for a light speed spaceship to travel for the whole run.
+    let margin = 2 * max_period as i32 + 2;
+    let (min_x, min_y, max_x, max_y) = cells.iter().fold(
+        (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+        |(min_x, min_y, max_x, max_y), (x, y)| {
+            (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+        },
+    );
+    let mut board = Board::empty(
+        (max_x - min_x + 1 + 2 * margin) as usize,
+        (max_y - min_y + 1 + 2 * margin) as usize,
+    );
+    let mut detector = PeriodDetector::new(max_period);
+
+    board.rule = rule;
+
+    for (x, y) in cells.iter() {
+        board.toggle(x - min_x + margin, y - min_y + margin);
+    }
+
+    detector.observe(&board);
+
+    while board.generation < 2 * max_period {
+        board.update_live_neighbor_counts();
+        board.step();
+
+        if let Some(periodicity) = detector.observe(&board) {
+            return Some(periodicity);
+        }
+    }
+
+    None
+}
+
+impl PeriodDetector {
+    pub fn new(max_period: usize) -> Self {
+        PeriodDetector {
+            max_period,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Bytes held by the snapshot history.
+    pub fn memory_usage(&self) -> usize {
+        self.history
+            .iter()
+            .map(|snapshot| {
+                std::mem::size_of::<Snapshot>()
+                    + snapshot.shape.capacity() * std::mem::size_of::<(i32, i32)>()
+            })
+            .sum()
+    }
+
+    /// Frees memory by forgetting the older half of the history. Periods
+    /// longer than what remains go undetected until it fills up again.
+    pub fn trim(&mut self) {
+        let keep = self.history.len() / 2;
+
+        self.history.drain(..self.history.len() - keep);
+    }
+
+    /// Forgets the history, e.g. after the board jumped to another state.
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// Records the board's current generation and reports the pattern's
+    /// period if it matches an earlier one.
+    pub fn observe(&mut self, board: &Board) -> Option<Periodicity> {
+        let live_cells = board.live_cells();
+
+        if live_cells.is_empty() {
+            self.history.clear();
+            return None;
+        }
+
+        let origin = live_cells
+            .iter()
+            .fold((i32::MAX, i32::MAX), |(x, y), cell| {
+                (x.min(cell.0), y.min(cell.1))
+            });
+        let shape: Vec<(i32, i32)> = live_cells
+            .iter()
+            .map(|(x, y)| (x - origin.0, y - origin.1))
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        shape.hash(&mut hasher);
+
+        let changes = match self.history.back() {
+            Some(previous) => count_changes(previous, origin, &shape),
+            None => 0,
+        };
+        let snapshot = Snapshot {
+            generation: board.generation,
+            origin,
+            shape,
+            hash: hasher.finish(),
+            changes,
+        };
+        let periodicity = self.find_match(&snapshot);
+
+        self.history.push_back(snapshot);
+
+        if self.history.len() > self.max_period {
+            self.history.pop_front();
+        }
+
+        periodicity
+    }
+
+    fn find_match(&self, snapshot: &Snapshot) -> Option<Periodicity> {
+        let (position, earlier) = self.history.iter().enumerate().rev().find(|(_, earlier)| {
+            earlier.hash == snapshot.hash && earlier.shape == snapshot.shape
+        })?;
+        let period = snapshot.generation - earlier.generation;
+
+        if period == 0 {
+            return None;
+        }
+
+        let changes: usize = self
+            .history
+            .iter()
+            .skip(position + 1)
+            .map(|later| later.changes)
+            .sum::<usize>()
+            + snapshot.changes;
+
+        Some(Periodicity {
+            period,
+            dx: snapshot.origin.0 - earlier.origin.0,
+            dy: snapshot.origin.1 - earlier.origin.1,
+            heat: changes as f64 / period as f64,
+        })
+    }
+}
+
+/// Counts the cells alive in exactly one of the two generations.
+fn count_changes(previous: &Snapshot, origin: (i32, i32), shape: &[(i32, i32)]) -> usize {
+    let absolute = |origin: (i32, i32), cells: &[(i32, i32)]| -> Vec<(i32, i32)> {
+        cells
+            .iter()
+            .map(|(x, y)| (x + origin.0, y + origin.1))
+            .collect()
+    };
+    let before = absolute(previous.origin, &previous.shape);
+    let after = absolute(origin, shape);
+    let survivors = after
+        .iter()
+        .filter(|cell| {
+            before
+                .binary_search_by(|other| compare(other, cell))
+                .is_ok()
+        })
+        .count();
+
+    (before.len() - survivors) + (after.len() - survivors)
+}
+
+/// Row-major ordering, matching the order live cells are collected in.
+fn compare(a: &(i32, i32), b: &(i32, i32)) -> std::cmp::Ordering {
+    (a.1, a.0).cmp(&(b.1, b.0))
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a.max(1)
+    } else {
+        gcd(b, a % b)
+    }
+}
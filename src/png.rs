@@ -0,0 +1,301 @@
+//! Reading PNG images into `RgbaImage`s and writing them back out. Every
+//! standard color type and bit depth can be read except interlaced images;
+//! 16-bit channels are truncated to 8 bits. Images are written as 8-bit
+//! RGBA.
+
+use crate::image::RgbaImage;
+use crate::zlib;
+use std::fs;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+pub fn load(path: &str) -> Result<RgbaImage, String> {
+    let bytes =
+        fs::read(path).map_err(|error| format!("failed to read image {}: {}", path, error))?;
+
+    decode(&bytes).map_err(|error| format!("failed to decode image {}: {}", path, error))
+}
+
+pub fn save(path: &str, image: &RgbaImage) -> Result<(), String> {
+    fs::write(path, encode(image))
+        .map_err(|error| format!("failed to write image {}: {}", path, error))
+}
+
+pub fn encode(image: &RgbaImage) -> Vec<u8> {
+    let row_bytes = image.width * 4;
+    let mut scanlines = Vec::with_capacity((row_bytes + 1) * image.height);
+
+    for row in image.pixels.chunks(row_bytes.max(1)).take(image.height) {
+        // No filter: LZ77 already finds the repetition in flat areas.
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, no filter method, not interlaced.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut bytes = SIGNATURE.to_vec();
+
+    write_chunk(&mut bytes, b"IHDR", &header);
+    write_chunk(&mut bytes, b"IDAT", &zlib::compress(&scanlines));
+    write_chunk(&mut bytes, b"IEND", &[]);
+
+    bytes
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(&crc32(&bytes[bytes.len() - data.len() - 4..]).to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+pub fn decode(bytes: &[u8]) -> Result<RgbaImage, String> {
+    if !bytes.starts_with(&SIGNATURE) {
+        return Err("not a PNG file".to_string());
+    }
+
+    let mut position = SIGNATURE.len();
+    let mut header = None;
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut transparency = Vec::new();
+    let mut data = Vec::new();
+
+    while position + 8 <= bytes.len() {
+        let length = read_u32(&bytes[position..]) as usize;
+        let kind = &bytes[position + 4..position + 8];
+        let chunk = bytes
+            .get(position + 8..position + 8 + length)
+            .ok_or("truncated chunk")?;
+
+        match kind {
+            b"IHDR" => header = Some(Header::parse(chunk)?),
+            b"PLTE" => {
+                palette = chunk
+                    .chunks_exact(3)
+                    .map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                    .collect()
+            }
+            b"tRNS" => transparency = chunk.to_vec(),
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+
+        // Skip the chunk and its CRC.
+        position += 12 + length;
+    }
+
+    let header = header.ok_or("missing IHDR chunk")?;
+
+    for (entry, alpha) in palette.iter_mut().zip(transparency.iter()) {
+        entry[3] = *alpha;
+    }
+
+    let raw = zlib::decompress(&data)?;
+    let scanlines = unfilter(&raw, &header)?;
+
+    header.to_rgba(&scanlines, &palette)
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(chunk: &[u8]) -> Result<Self, String> {
+        if chunk.len() < 13 {
+            return Err("truncated IHDR chunk".to_string());
+        }
+
+        let header = Header {
+            width: read_u32(&chunk[0..]) as usize,
+            height: read_u32(&chunk[4..]) as usize,
+            bit_depth: chunk[8],
+            color_type: chunk[9],
+        };
+
+        if chunk[12] != 0 {
+            return Err("interlaced PNGs are not supported".to_string());
+        }
+
+        let valid_depths: &[u8] = match header.color_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            other => return Err(format!("invalid color type {}", other)),
+        };
+
+        if !valid_depths.contains(&header.bit_depth) {
+            return Err(format!(
+                "invalid bit depth {} for color type {}",
+                header.bit_depth, header.color_type
+            ));
+        }
+
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    fn row_bytes(&self) -> usize {
+        (self.width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// Converts unfiltered scanlines to RGBA pixels.
+    fn to_rgba(&self, scanlines: &[u8], palette: &[[u8; 4]]) -> Result<RgbaImage, String> {
+        let mut image = RgbaImage::new(self.width, self.height, crate::theme::Rgb::BLACK);
+        let row_bytes = self.row_bytes();
+        let depth = self.bit_depth as usize;
+        let max = (1u32 << depth.min(8)) - 1;
+
+        for y in 0..self.height {
+            let row = &scanlines[y * row_bytes..(y + 1) * row_bytes];
+            // The `index`th sample of the row as stored, for depths under 8.
+            let packed_sample = |index: usize| {
+                let bit = index * depth;
+
+                (row[bit / 8] >> (8 - depth - bit % 8)) as u32 & max
+            };
+            // The `index`th sample of the row, scaled to 8 bits.
+            let sample = |index: usize| match depth {
+                8 => row[index],
+                16 => row[index * 2],
+                _ => (packed_sample(index) * 255 / max) as u8,
+            };
+
+            for x in 0..self.width {
+                let pixel = match self.color_type {
+                    0 => {
+                        let gray = sample(x);
+                        [gray, gray, gray, 255]
+                    }
+                    2 => [sample(x * 3), sample(x * 3 + 1), sample(x * 3 + 2), 255],
+                    3 => *palette
+                        .get(if depth == 8 {
+                            sample(x) as usize
+                        } else {
+                            packed_sample(x) as usize
+                        })
+                        .ok_or("palette index out of range")?,
+                    4 => {
+                        let gray = sample(x * 2);
+                        [gray, gray, gray, sample(x * 2 + 1)]
+                    }
+                    _ => [
+                        sample(x * 4),
+                        sample(x * 4 + 1),
+                        sample(x * 4 + 2),
+                        sample(x * 4 + 3),
+                    ],
+                };
+
+                image.set_pixel(x, y, pixel);
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+/// Reverses the per-scanline filters, returning the scanlines without their
+/// filter type bytes.
+fn unfilter(raw: &[u8], header: &Header) -> Result<Vec<u8>, String> {
+    let row_bytes = header.row_bytes();
+    let pixel_bytes = header.bits_per_pixel().div_ceil(8);
+
+    if raw.len() < (row_bytes + 1) * header.height {
+        return Err("image data is too short".to_string());
+    }
+
+    let mut output = vec![0u8; row_bytes * header.height];
+
+    for y in 0..header.height {
+        let filter = raw[y * (row_bytes + 1)];
+        let input = &raw[y * (row_bytes + 1) + 1..(y + 1) * (row_bytes + 1)];
+        let (previous_rows, current_rows) = output.split_at_mut(y * row_bytes);
+        let previous = if y == 0 {
+            None
+        } else {
+            Some(&previous_rows[(y - 1) * row_bytes..])
+        };
+        let current = &mut current_rows[..row_bytes];
+
+        for x in 0..row_bytes {
+            let left = if x >= pixel_bytes {
+                current[x - pixel_bytes]
+            } else {
+                0
+            };
+            let up = previous.map_or(0, |row| row[x]);
+            let up_left = match previous {
+                Some(row) if x >= pixel_bytes => row[x - pixel_bytes],
+                _ => 0,
+            };
+
+            current[x] = match filter {
+                0 => input[x],
+                1 => input[x].wrapping_add(left),
+                2 => input[x].wrapping_add(up),
+                3 => input[x].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                4 => input[x].wrapping_add(paeth(left, up, up_left)),
+                other => return Err(format!("invalid filter type {}", other)),
+            };
+        }
+    }
+
+    Ok(output)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
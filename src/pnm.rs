@@ -0,0 +1,40 @@
+//! Netpbm exports of the board, readable by nearly every image tool with no
+//! extra dependencies.
+
+use crate::board::{Board, CellState};
+use crate::trail::FadeCurve;
+
+/// Encodes the live cells as a binary PBM (`P4`), one bit per cell with 1
+/// for alive (black).
+pub fn encode_pbm(board: &Board) -> Vec<u8> {
+    let mut bytes = format!("P4\n{} {}\n", board.width, board.height).into_bytes();
+    let row_bytes = board.width.div_ceil(8);
+
+    for row in board.cells.chunks(board.width.max(1)) {
+        let mut packed = vec![0u8; row_bytes];
+
+        for (x, cell) in row.iter().enumerate() {
+            if cell.is_alive() {
+                packed[x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+
+        bytes.extend_from_slice(&packed);
+    }
+
+    bytes
+}
+
+/// Encodes the board as a binary PGM (`P5`): live cells black, dead cells
+/// white and dying cells the gray of the trail fade curve.
+pub fn encode_pgm(board: &Board, curve: &FadeCurve) -> Vec<u8> {
+    let mut bytes = format!("P5\n{} {}\n255\n", board.width, board.height).into_bytes();
+
+    bytes.extend(board.cells.iter().map(|cell| match cell.state {
+        CellState::Alive => 0,
+        CellState::Dying(cycles_left) => curve.intensity(cycles_left, board.trail_length),
+        _ => 255,
+    }));
+
+    bytes
+}
@@ -0,0 +1,141 @@
+//! Predecessor search: given a region of a board, look for any configuration
+//! that evolves into it in one generation under the board's rule.
+//!
+//! Only the cells inside the target region and the one-cell ring around it can
+//! affect the region's next generation, so the search is over that
+//! `(width + 2) x (height + 2)` area. If no assignment works, the target is a
+//! Garden of Eden.
+
+use crate::board::Board;
+use crate::region::Region;
+use crate::rule::Rule;
+
+/// The largest target region searched; beyond this the backtracking search
+/// can take impractically long.
+pub const MAX_REGION_CELLS: usize = 100;
+
+/// Searches for a predecessor of `region` on `board` under its rule. Ring
+/// cells beyond a dead edge must stay dead; regions whose ring crosses a
+/// wrapping or reflecting edge, and weighted rules, aren't searched. Returns
+/// the predecessor as a row-major `(width + 2) x (height + 2)` grid, or
+/// `None` if the target is a Garden of Eden.
+pub fn find_predecessor(board: &Board, region: &Region) -> Result<Option<Vec<bool>>, String> {
+    if board.weighted_rule.is_some() {
+        return Err("predecessor search doesn't support weighted rules".to_string());
+    }
+
+    let inside = |x: i32, y: i32| {
+        (0..board.width as i32).contains(&x) && (0..board.height as i32).contains(&y)
+    };
+
+    if !inside(region.x, region.y)
+        || !inside(
+            region.x + region.width as i32 - 1,
+            region.y + region.height as i32 - 1,
+        )
+    {
+        return Err("the predecessor region must lie inside the board".to_string());
+    }
+
+    let (width, height) = (region.width + 2, region.height + 2);
+    let mut dead = vec![false; width * height];
+
+    for j in 0..height {
+        for i in 0..width {
+            let (x, y) = (region.x - 1 + i as i32, region.y - 1 + j as i32);
+
+            if inside(x, y) {
+                continue;
+            }
+
+            match board.topology.wrap(x, y, board.width, board.height) {
+                None => dead[j * width + i] = true,
+                Some(_) => {
+                    return Err(format!(
+                        "predecessor search can't reach across {} edges; move the region off them",
+                        board.topology.name()
+                    ))
+                }
+            }
+        }
+    }
+
+    let target = board.alive_in(region);
+    let mut search = Search {
+        target: &target,
+        width: region.width,
+        rule: board.rule,
+        dead: &dead,
+        cells: vec![false; width * height],
+    };
+
+    Ok(search.assign(0).then_some(search.cells))
+}
+
+struct Search<'a> {
+    target: &'a [bool],
+    width: usize,
+    rule: Rule,
+    /// Predecessor cells beyond a dead edge, which can't be alive.
+    dead: &'a [bool],
+    cells: Vec<bool>,
+}
+
+impl Search<'_> {
+    /// Assigns predecessor cells in row-major order from `index` onwards,
+    /// backtracking as soon as a target cell's whole neighborhood is assigned
+    /// and evolves to the wrong state.
+    fn assign(&mut self, index: usize) -> bool {
+        if index == self.cells.len() {
+            return true;
+        }
+
+        let values: &[bool] = if self.dead[index] {
+            &[false]
+        } else {
+            &[false, true]
+        };
+
+        for &value in values {
+            self.cells[index] = value;
+
+            if self.consistent(index) && self.assign(index + 1) {
+                return true;
+            }
+        }
+
+        self.cells[index] = false;
+
+        false
+    }
+
+    /// Checks the one target cell whose neighborhood is completed by the
+    /// assignment at `index`, if any.
+    fn consistent(&self, index: usize) -> bool {
+        let stride = self.width + 2;
+        let (i, j) = (index % stride, index / stride);
+
+        if i < 2 || j < 2 {
+            return true;
+        }
+
+        let mut neighbor_count = 0;
+
+        for y in j - 2..=j {
+            for x in i - 2..=i {
+                if (x, y) != (i - 1, j - 1) && self.cells[y * stride + x] {
+                    neighbor_count += 1;
+                }
+            }
+        }
+
+        let alive = self.cells[(j - 1) * stride + (i - 1)];
+        let next = if alive {
+            self.rule.survives(neighbor_count)
+        } else {
+            self.rule.is_born(neighbor_count)
+        };
+
+        next == self.target[(j - 2) * self.width + (i - 2)]
+    }
+}
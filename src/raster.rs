@@ -0,0 +1,108 @@
+//! Software rendering of the board into an `RgbaImage`, for frontends and
+//! exports that have no GPU canvas to draw on.
+
+use crate::board::{Board, CellState};
+use crate::config::Config;
+use crate::image::RgbaImage;
+use crate::pattern::Pattern;
+
+/// Renders the whole board in the config's theme with each cell `cell_size`
+/// pixels across. Dead cells are left transparent so the image can be laid
+/// over a background or a board; every other off-screen rendering, from
+/// exports to thumbnails and previews, goes through here.
+pub fn render_to_image(board: &Board, config: &Config, cell_size: usize) -> RgbaImage {
+    let theme = &config.theme;
+    let cell_size = cell_size.max(1);
+    let (width, height) = (board.width * cell_size, board.height * cell_size);
+    let background = theme.background;
+    let mut image = RgbaImage {
+        width,
+        height,
+        pixels: [background.r, background.g, background.b, 0].repeat(width * height),
+    };
+
+    for (index, cell) in board.cells.iter().enumerate() {
+        let (color, sprite) = match cell.state {
+            CellState::Alive => (theme.alive, theme.alive_sprite.as_ref()),
+            CellState::Dying(cycles_left) => (
+                theme.dying_color(&config.trail_curve, cycles_left, board.trail_length),
+                theme.dying_sprite(cycles_left, board.trail_length),
+            ),
+            CellState::Poison => (theme.poison, None),
+            CellState::Dead => continue,
+        };
+        let (x, y) = board.index_to_coordinates(index);
+        let (left, top) = (x as i64 * cell_size as i64, y as i64 * cell_size as i64);
+
+        match sprite {
+            Some(sprite) => image.draw_scaled(sprite, left, top, cell_size, cell_size),
+            None => image.fill_rect(left, top, cell_size, cell_size, color),
+        }
+    }
+
+    image
+}
+
+/// Renders a pattern's bounding box with `render_to_image`.
+pub fn render_pattern(pattern: &Pattern, config: &Config, cell_size: usize) -> RgbaImage {
+    let (width, height) = pattern.size();
+    let (left, top) = pattern.origin();
+    let mut board = Board::empty(width, height);
+
+    board.stamp(pattern, -left, -top);
+
+    render_to_image(&board, config, cell_size)
+}
+
+/// The board cell drawn at pixel `(x, y)` of a `render_fit` image of the
+/// given size, or `None` for the background around the board.
+pub fn fit_cell(
+    board: &Board,
+    width: usize,
+    height: usize,
+    (x, y): (i32, i32),
+) -> Option<(i32, i32)> {
+    let cell_size = (width / board.width.max(1)).min(height / board.height.max(1));
+    let scale = if cell_size > 0 {
+        cell_size as f64
+    } else {
+        (width as f64 / board.width as f64).min(height as f64 / board.height as f64)
+    };
+    let fit_width = (board.width as f64 * scale) as usize;
+    let fit_height = (board.height as f64 * scale) as usize;
+    let column = ((x - ((width - fit_width) / 2) as i32) as f64 / scale).floor() as i32;
+    let row = ((y - ((height - fit_height) / 2) as i32) as f64 / scale).floor() as i32;
+
+    ((0..board.width as i32).contains(&column) && (0..board.height as i32).contains(&row))
+        .then_some((column, row))
+}
+
+/// Renders the whole board centered in a `width` by `height` pixel image on
+/// the theme's background, with cells as large as fit. Boards with more
+/// cells than pixels are shrunk by nearest-neighbor sampling, so lone cells
+/// may drop out.
+pub fn render_fit(board: &Board, config: &Config, width: usize, height: usize) -> RgbaImage {
+    let cell_size = (width / board.width.max(1)).min(height / board.height.max(1));
+    let board_image = render_to_image(board, config, cell_size);
+    let (fit_width, fit_height) = if cell_size > 0 {
+        (board_image.width, board_image.height)
+    } else {
+        let scale = (width as f64 / board.width as f64).min(height as f64 / board.height as f64);
+
+        (
+            (board.width as f64 * scale) as usize,
+            (board.height as f64 * scale) as usize,
+        )
+    };
+    let mut image = RgbaImage::new(width, height, config.theme.background);
+
+    image.draw_scaled(
+        &board_image,
+        ((width - fit_width) / 2) as i64,
+        ((height - fit_height) / 2) as i64,
+        fit_width,
+        fit_height,
+    );
+
+    image
+}
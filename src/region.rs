@@ -0,0 +1,51 @@
+/// A rectangular area of the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    pub fn new(x: i32, y: i32, width: usize, height: usize) -> Self {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Parses `<x>,<y>,<width>x<height>`, with a nonzero width and height.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("expected <x>,<y>,<width>x<height>: {}", text);
+        let fields: Vec<&str> = text.split(',').collect();
+
+        match fields.as_slice() {
+            [x, y, size] => {
+                let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+                let region = Region::new(
+                    x.parse().map_err(|_| invalid())?,
+                    y.parse().map_err(|_| invalid())?,
+                    width.parse().map_err(|_| invalid())?,
+                    height.parse().map_err(|_| invalid())?,
+                );
+
+                if region.width == 0 || region.height == 0 {
+                    return Err(format!("region needs a nonzero width and height: {}", text));
+                }
+
+                Ok(region)
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as i32
+            && y < self.y + self.height as i32
+    }
+}
@@ -0,0 +1,444 @@
+use crate::board::Board;
+use crate::bookmark::{Bookmarks, BOOKMARK_SLOTS};
+use crate::engine::Engine;
+use crate::pattern::Pattern;
+use crate::region::Region;
+use crate::rle;
+use crate::rule::Rule;
+use crate::topology::Topology;
+use crate::trail::DyingPolicy;
+use std::fs;
+
+/// A user action that can be recorded and replayed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    Toggle {
+        x: i32,
+        y: i32,
+    },
+    /// Paints or clears poison.
+    Poison {
+        x: i32,
+        y: i32,
+    },
+    Stamp {
+        pattern: String,
+        x: i32,
+        y: i32,
+    },
+    /// Places arbitrary cells, e.g. from a pasted pattern, relative to
+    /// `(x, y)`.
+    Paste {
+        cells: Vec<(i32, i32)>,
+        x: i32,
+        y: i32,
+    },
+    /// Kills every live or dying cell in a region, e.g. before a sandbox is
+    /// written back over it.
+    Clear(Region),
+    /// Freezes every cell in a region, see `Cell::frozen`.
+    Freeze(Region),
+    Unfreeze(Region),
+    Speed(u32),
+    TrailLength(usize),
+    Rule(Rule),
+    /// Saves the board in a bookmark slot.
+    SaveBookmark(usize),
+    /// Replaces the board with the one saved in a bookmark slot.
+    RestoreBookmark(usize),
+    Resize {
+        width: usize,
+        height: usize,
+    },
+}
+
+/// An input along with when it happened. Playback is keyed on `generation`
+/// so it is deterministic; `elapsed_ms` is kept for reference only. Events at
+/// generation 0 describe the starting settings and are applied before the
+/// first step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayEvent {
+    pub generation: usize,
+    pub elapsed_ms: u128,
+    pub input: Input,
+}
+
+/// The board a replay was recorded on, so playback starts from the same
+/// one. Every field is optional, since replays saved before they were
+/// recorded have none of them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplaySettings {
+    pub size: Option<(usize, usize)>,
+    pub rule: Option<Rule>,
+    pub topology: Option<Topology>,
+    pub engine: Option<Engine>,
+    pub dying_policy: Option<DyingPolicy>,
+    /// The starting board's `state_hash`, which covers the pattern or soup
+    /// it was filled with.
+    pub start: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    pub seed: Option<u64>,
+    pub settings: ReplaySettings,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Input {
+    /// Applies the input to the board. Frontend-only inputs such as speed
+    /// changes leave the board untouched.
+    pub fn apply(&self, board: &mut Board, bookmarks: &mut Bookmarks) -> Result<(), String> {
+        match self {
+            Input::Toggle { x, y } => board.toggle(*x, *y),
+            Input::Poison { x, y } => board.toggle_poison(*x, *y),
+            Input::Stamp { pattern, x, y } => {
+                let pattern = Pattern::builtin(pattern)
+                    .ok_or_else(|| format!("unknown pattern: {}", pattern))?;
+                board.stamp(&pattern, *x, *y);
+            }
+            Input::Paste { cells, x, y } => {
+                board.stamp(&Pattern::new("paste", cells.clone()), *x, *y)
+            }
+            Input::Clear(region) => board.clear(region),
+            Input::Freeze(region) => board.set_frozen(region, true),
+            Input::Unfreeze(region) => board.set_frozen(region, false),
+            Input::TrailLength(trail_length) => board.set_trail_length(*trail_length),
+            Input::Rule(rule) => board.rule = *rule,
+            Input::SaveBookmark(slot) => bookmarks.save(*slot, board)?,
+            // Restoring a slot that was never saved leaves the board as it
+            // is rather than ending the run.
+            Input::RestoreBookmark(slot) if !bookmarks.is_saved(*slot) => {
+                crate::warn!("bookmark {} is empty; not restoring it", slot)
+            }
+            Input::RestoreBookmark(slot) => bookmarks.restore(*slot, board)?,
+            Input::Resize { width, height } => board.resize(*width, *height),
+            Input::Speed(_) => {}
+        }
+
+        Ok(())
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            Input::Toggle { x, y } => format!("toggle {} {}", x, y),
+            Input::Poison { x, y } => format!("poison {} {}", x, y),
+            Input::Stamp { pattern, x, y } => format!("stamp {} {} {}", pattern, x, y),
+            // The RLE body starts at the cells' top left corner, so the
+            // offset moves into the position.
+            Input::Paste { cells, x, y } => {
+                let (left, top) = Pattern::new("paste", cells.clone()).origin();
+
+                format!("paste {} {} {}", x + left, y + top, rle::encode_body(cells))
+            }
+            Input::Clear(region) => format!("clear {}", region_fields(region)),
+            Input::Freeze(region) => format!("freeze {}", region_fields(region)),
+            Input::Unfreeze(region) => format!("unfreeze {}", region_fields(region)),
+            Input::Speed(fps) => format!("speed {}", fps),
+            Input::TrailLength(trail_length) => format!("trail {}", trail_length),
+            Input::Rule(rule) => format!("rule {}", rule.name()),
+            Input::SaveBookmark(slot) => format!("bookmark {}", slot),
+            Input::RestoreBookmark(slot) => format!("restore {}", slot),
+            Input::Resize { width, height } => format!("resize {} {}", width, height),
+        }
+    }
+
+    fn from_fields(fields: &[&str]) -> Result<Self, String> {
+        match fields {
+            ["toggle", x, y] => Ok(Input::Toggle {
+                x: parse_field(x)?,
+                y: parse_field(y)?,
+            }),
+            ["poison", x, y] => Ok(Input::Poison {
+                x: parse_field(x)?,
+                y: parse_field(y)?,
+            }),
+            ["stamp", pattern, x, y] => Ok(Input::Stamp {
+                pattern: pattern.to_string(),
+                x: parse_field(x)?,
+                y: parse_field(y)?,
+            }),
+            ["paste", x, y, body] => Ok(Input::Paste {
+                cells: rle::decode_body(body)?,
+                x: parse_field(x)?,
+                y: parse_field(y)?,
+            }),
+            ["clear", region @ ..] => Ok(Input::Clear(parse_region(region)?)),
+            ["freeze", region @ ..] => Ok(Input::Freeze(parse_region(region)?)),
+            ["unfreeze", region @ ..] => Ok(Input::Unfreeze(parse_region(region)?)),
+            ["speed", fps] => match parse_field(fps)? {
+                0 => Err("speed must be at least 1".to_string()),
+                fps => Ok(Input::Speed(fps)),
+            },
+            ["trail", trail_length] => Ok(Input::TrailLength(parse_field(trail_length)?)),
+            ["rule", rule] => Ok(Input::Rule(Rule::parse(rule)?)),
+            ["bookmark", slot] => Ok(Input::SaveBookmark(parse_slot(slot)?)),
+            ["restore", slot] => Ok(Input::RestoreBookmark(parse_slot(slot)?)),
+            ["resize", width, height] => Ok(Input::Resize {
+                width: parse_field(width)?,
+                height: parse_field(height)?,
+            }),
+            _ => Err(format!("unrecognized input: {}", fields.join(" "))),
+        }
+    }
+}
+
+impl ReplaySettings {
+    /// The settings of a board about to be recorded.
+    pub fn of(board: &Board) -> Self {
+        ReplaySettings {
+            size: Some((board.width, board.height)),
+            rule: Some(board.rule),
+            topology: Some(board.topology),
+            engine: Some(board.engine),
+            dying_policy: Some(board.dying_policy),
+            start: Some(board.state_hash()),
+        }
+    }
+
+    /// Puts the recorded rule, topology, engine and dying policy on the
+    /// board, and checks it has the recorded size and starting cells, which
+    /// can't be changed afterwards.
+    pub fn apply(&self, board: &mut Board) -> Result<(), String> {
+        if let Some((width, height)) = self.size {
+            if (board.width, board.height) != (width, height) {
+                return Err(format!(
+                    "the replay was recorded on a {}x{} board, not {}x{}; play it back with --board {}x{}",
+                    width, height, board.width, board.height, width, height
+                ));
+            }
+        }
+
+        if let Some(rule) = self.rule {
+            if board.rule != rule {
+                crate::info!("playing back under the recorded rule {}", rule.name());
+                board.rule = rule;
+            }
+        }
+
+        if let Some(topology) = self.topology {
+            if board.topology != topology {
+                crate::info!("playing back on the recorded topology {}", topology.name());
+                board.topology = topology;
+            }
+        }
+
+        if let Some(engine) = self.engine {
+            board.engine = engine;
+        }
+
+        if let Some(dying_policy) = self.dying_policy {
+            if board.dying_policy != dying_policy {
+                crate::info!(
+                    "playing back with the recorded dying policy {}",
+                    dying_policy.name()
+                );
+                board.dying_policy = dying_policy;
+            }
+        }
+
+        match self.start {
+            Some(start) if board.state_hash() != start => Err(
+                "the replay was recorded from a different starting board; play it back with the same --pattern options"
+                    .to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+
+        if let Some((width, height)) = self.size {
+            lines.push(format!("board {} {}", width, height));
+        }
+        if let Some(rule) = self.rule {
+            lines.push(format!("rule {}", rule.name()));
+        }
+        if let Some(topology) = self.topology {
+            lines.push(format!("topology {}", topology.name()));
+        }
+        if let Some(engine) = self.engine {
+            lines.push(format!("engine {}", engine.name()));
+        }
+        if let Some(dying_policy) = self.dying_policy {
+            lines.push(format!("dying {}", dying_policy.name()));
+        }
+        if let Some(start) = self.start {
+            lines.push(format!("start {:016x}", start));
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+impl Replay {
+    pub fn new(seed: Option<u64>) -> Self {
+        Replay {
+            seed,
+            settings: ReplaySettings::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// A new replay for a run starting from `board`, recording its settings.
+    pub fn recording(seed: Option<u64>, board: &Board) -> Self {
+        Replay {
+            settings: ReplaySettings::of(board),
+            ..Replay::new(seed)
+        }
+    }
+
+    /// Approximate bytes held by the recorded events.
+    pub fn memory_usage(&self) -> usize {
+        let pasted_cells: usize = self
+            .events
+            .iter()
+            .map(|event| match &event.input {
+                Input::Paste { cells, .. } => cells.capacity() * std::mem::size_of::<(i32, i32)>(),
+                _ => 0,
+            })
+            .sum();
+
+        self.events.capacity() * std::mem::size_of::<ReplayEvent>() + pasted_cells
+    }
+
+    pub fn record(&mut self, generation: usize, elapsed_ms: u128, input: Input) {
+        self.events.push(ReplayEvent {
+            generation,
+            elapsed_ms,
+            input,
+        });
+    }
+
+    /// Returns the inputs that were applied at the given generation.
+    pub fn events_at(&self, generation: usize) -> impl Iterator<Item = &ReplayEvent> {
+        self.events
+            .iter()
+            .filter(move |event| event.generation == generation)
+    }
+
+    /// Serializes the replay as one line per event:
+    /// `<generation> <elapsed_ms> <input...>`, preceded by an optional
+    /// `seed <n>` line and the recorded settings, such as `board <w> <h>`
+    /// and `rule <rule>`.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        if let Some(seed) = self.seed {
+            text.push_str(&format!("seed {}\n", seed));
+        }
+
+        text.push_str(&self.settings.to_text());
+
+        for event in self.events.iter() {
+            text.push_str(&format!(
+                "{} {} {}\n",
+                event.generation,
+                event.elapsed_ms,
+                event.input.to_line()
+            ));
+        }
+
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut replay = Replay::default();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let context = |error: String| format!("line {}: {}", line_number + 1, error);
+
+            match fields.as_slice() {
+                [] => {}
+                ["seed", seed] => replay.seed = Some(parse_field(seed).map_err(context)?),
+                ["board", width, height] => {
+                    replay.settings.size = Some((
+                        parse_field(width).map_err(context)?,
+                        parse_field(height).map_err(context)?,
+                    ))
+                }
+                ["rule", rule] => replay.settings.rule = Some(Rule::parse(rule).map_err(context)?),
+                ["topology", topology] => {
+                    replay.settings.topology = Some(Topology::parse(topology).map_err(context)?)
+                }
+                ["engine", engine] => {
+                    replay.settings.engine = Some(Engine::parse(engine).map_err(context)?)
+                }
+                ["dying", dying_policy] => {
+                    replay.settings.dying_policy =
+                        Some(DyingPolicy::parse(dying_policy).map_err(context)?)
+                }
+                ["start", start] => {
+                    replay.settings.start = Some(
+                        u64::from_str_radix(start, 16)
+                            .map_err(|_| context(format!("invalid board hash: {}", start)))?,
+                    )
+                }
+                [generation, elapsed_ms, input @ ..] => replay.events.push(ReplayEvent {
+                    generation: parse_field(generation).map_err(context)?,
+                    elapsed_ms: parse_field(elapsed_ms).map_err(context)?,
+                    input: Input::from_fields(input).map_err(context)?,
+                }),
+                _ => return Err(context(format!("malformed event: {}", line))),
+            }
+        }
+
+        Ok(replay)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        crate::info!("loading replay {}", path);
+
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read replay {}: {}", path, error))?;
+
+        Self::parse(&text)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        crate::info!("saving {} replay events to {}", self.events.len(), path);
+
+        fs::write(path, self.to_text())
+            .map_err(|error| format!("failed to write replay {}: {}", path, error))
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str) -> Result<T, String> {
+    field
+        .parse()
+        .map_err(|_| format!("invalid number: {}", field))
+}
+
+/// Parses a bookmark slot, 0 to 9.
+pub(crate) fn parse_slot(field: &str) -> Result<usize, String> {
+    match parse_field(field)? {
+        slot if slot < BOOKMARK_SLOTS => Ok(slot),
+        slot => Err(format!(
+            "bookmark slot must be 0 to {}: {}",
+            BOOKMARK_SLOTS - 1,
+            slot
+        )),
+    }
+}
+
+/// A region as the `x y width height` fields of an input line.
+fn region_fields(region: &Region) -> String {
+    format!(
+        "{} {} {} {}",
+        region.x, region.y, region.width, region.height
+    )
+}
+
+fn parse_region(fields: &[&str]) -> Result<Region, String> {
+    match fields {
+        [x, y, width, height] => Ok(Region::new(
+            parse_field(x)?,
+            parse_field(y)?,
+            parse_field(width)?,
+            parse_field(height)?,
+        )),
+        _ => Err(format!("expected x y width height: {}", fields.join(" "))),
+    }
+}
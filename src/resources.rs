@@ -0,0 +1,119 @@
+/// Whether each cell draws on its own patch of resource or every cell
+/// shares one pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ResourceScope {
+    /// Live cells deplete the ground under them, so crowded areas starve
+    /// while others recover.
+    #[default]
+    Local,
+    /// Live cells drain a single pool, which acts as a soft population cap:
+    /// once it runs low, births fail everywhere until it refills.
+    Global,
+}
+
+impl ResourceScope {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "local" => Ok(ResourceScope::Local),
+            "global" => Ok(ResourceScope::Global),
+            _ => Err(format!("unknown resource scope: {}", text)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResourceScope::Local => "local",
+            ResourceScope::Global => "global",
+        }
+    }
+}
+
+/// How the resource field behaves. Levels run from 0.0 (exhausted) to 1.0
+/// (full), and every cell starts full.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResourceRule {
+    pub scope: ResourceScope,
+    /// Resource a live cell uses up each generation.
+    pub consumption: f32,
+    /// Resource regained each generation, up to full.
+    pub regeneration: f32,
+    /// Level a cell needs to be born; below it, births the rule allows fail.
+    pub birth_threshold: f32,
+}
+
+impl Default for ResourceRule {
+    fn default() -> Self {
+        ResourceRule {
+            scope: ResourceScope::default(),
+            consumption: 0.1,
+            regeneration: 0.02,
+            birth_threshold: 0.5,
+        }
+    }
+}
+
+/// A resource level under every cell, or one shared level for a global
+/// field, turning the automaton into a simple ecology: life spends what it
+/// sits on and can only spread where enough is left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceField {
+    pub rule: ResourceRule,
+    levels: Vec<f32>,
+}
+
+impl ResourceField {
+    /// A full field for a board of `cell_count` cells.
+    pub fn new(rule: ResourceRule, cell_count: usize) -> Self {
+        let length = match rule.scope {
+            ResourceScope::Local => cell_count,
+            ResourceScope::Global => 1,
+        };
+
+        ResourceField {
+            rule,
+            levels: vec![1.0; length],
+        }
+    }
+
+    /// The resource available to the cell at `index`.
+    pub fn level(&self, index: usize) -> f32 {
+        match self.rule.scope {
+            ResourceScope::Local => self.levels[index],
+            ResourceScope::Global => self.levels[0],
+        }
+    }
+
+    pub fn allows_birth(&self, index: usize) -> bool {
+        self.level(index) >= self.rule.birth_threshold
+    }
+
+    /// Regenerates every level and depletes those under live cells, given
+    /// whether each cell is alive in cell order. A global pool is depleted
+    /// by the fraction of the board that is alive.
+    pub fn update(&mut self, alive: impl ExactSizeIterator<Item = bool>) {
+        let rule = self.rule;
+
+        match rule.scope {
+            ResourceScope::Local => {
+                for (level, alive) in self.levels.iter_mut().zip(alive) {
+                    let used = if alive { rule.consumption } else { 0.0 };
+
+                    *level = ((*level + rule.regeneration).min(1.0) - used).max(0.0);
+                }
+            }
+            ResourceScope::Global => {
+                let cell_count = alive.len().max(1);
+                let density = alive.filter(|alive| *alive).count() as f32 / cell_count as f32;
+                let level = &mut self.levels[0];
+
+                *level =
+                    ((*level + rule.regeneration).min(1.0) - rule.consumption * density).max(0.0);
+            }
+        }
+    }
+
+    /// Bytes used by the levels.
+    pub fn memory_usage(&self) -> usize {
+        self.levels.capacity() * std::mem::size_of::<f32>()
+    }
+}
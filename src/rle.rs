@@ -0,0 +1,261 @@
+//! Reading and writing patterns in the run length encoded (RLE) format used by
+//! Golly and the LifeWiki.
+
+use crate::pattern::Pattern;
+
+/// RLE lines are conventionally kept under 70 characters.
+const MAX_LINE_LENGTH: usize = 70;
+const DEFAULT_RULE: &str = "B3/S23";
+/// The most live cells a body may decode to, so a few bytes of pasted or
+/// downloaded RLE can't ask for gigabytes of cells.
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// Encodes the pattern as RLE, writing its name, author and comments as `#N`,
+/// `#O` and `#C` lines.
+pub fn encode(pattern: &Pattern) -> String {
+    let mut text = format!("#N {}\n", pattern.name);
+
+    if let Some(author) = &pattern.author {
+        text.push_str(&format!("#O {}\n", author));
+    }
+
+    for comment in pattern.comments.iter() {
+        text.push_str(&format!("#C {}\n", comment));
+    }
+
+    let (min_x, min_y, max_x, max_y) = bounds(&pattern.cells);
+    let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+
+    text.push_str(&format!(
+        "x = {}, y = {}, rule = {}\n",
+        width,
+        height,
+        pattern.rule.as_deref().unwrap_or(DEFAULT_RULE)
+    ));
+
+    let mut line = String::new();
+
+    for item in body_runs(&pattern.cells) {
+        if line.len() + item.len() > MAX_LINE_LENGTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+
+        line.push_str(&item);
+    }
+
+    text.push_str(&line);
+    text.push('\n');
+
+    text
+}
+
+/// Encodes just the cells as a single line RLE body, without header or line
+/// wrapping.
+pub fn encode_body(cells: &[(i32, i32)]) -> String {
+    body_runs(cells).concat()
+}
+
+fn body_runs(cells: &[(i32, i32)]) -> Vec<String> {
+    let (min_x, min_y, max_x, max_y) = bounds(cells);
+    let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+    let mut rows = vec![vec![false; width as usize]; height as usize];
+
+    for (x, y) in cells.iter() {
+        rows[(y - min_y) as usize][(x - min_x) as usize] = true;
+    }
+
+    let mut runs = Vec::new();
+    let mut blank_rows = 0;
+
+    for (index, row) in rows.iter().enumerate() {
+        let row_runs = encode_row(row);
+
+        if row_runs.is_empty() && index + 1 < rows.len() {
+            blank_rows += 1;
+            continue;
+        }
+
+        if index > 0 {
+            runs.push(run(blank_rows + 1, '$'));
+        }
+
+        runs.extend(row_runs);
+        blank_rows = 0;
+    }
+
+    runs.push("!".to_string());
+
+    runs
+}
+/// Decodes an RLE pattern, keeping its `#N` name, `#O` author and `#C`/`#c`
+/// comments. Other `#` lines are ignored.
+pub fn decode(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::new("", Vec::new());
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let mut header_seen = None;
+
+    for line in lines.by_ref() {
+        if let Some(metadata) = line.strip_prefix('#') {
+            let mut chars = metadata.chars();
+            let tag = chars.next();
+            let value = chars.as_str().trim().to_string();
+
+            match tag {
+                Some('N') => pattern.name = value,
+                Some('O') => pattern.author = Some(value),
+                Some('C') | Some('c') => pattern.comments.push(value),
+                _ => {}
+            }
+        } else if line.starts_with('x') {
+            let (size, rule) = parse_header(line)?;
+
+            pattern.rule = rule;
+            header_seen = Some(size);
+            break;
+        } else {
+            return Err(format!("expected RLE header, found: {}", line));
+        }
+    }
+
+    let size = header_seen.ok_or("missing RLE header")?;
+
+    pattern.cells = decode_runs(&lines.collect::<Vec<&str>>().concat(), Some(size))?;
+
+    Ok(pattern)
+}
+
+/// Decodes an RLE body into live cell coordinates, stopping at `!`. Bodies
+/// with more than `MAX_CELLS` live cells are rejected.
+pub fn decode_body(body: &str) -> Result<Vec<(i32, i32)>, String> {
+    decode_runs(body, None)
+}
+
+/// Decodes a body like `decode_body`, also rejecting live cells outside the
+/// header's `size`, if there is one.
+fn decode_runs(body: &str, size: Option<(i32, i32)>) -> Result<Vec<(i32, i32)>, String> {
+    let too_far = || "RLE runs go past the largest coordinate".to_string();
+    let mut cells = Vec::new();
+    let (mut x, mut y): (i32, i32) = (0, 0);
+    let mut count = String::new();
+
+    for c in body.chars() {
+        match c {
+            '0'..='9' => count.push(c),
+            'b' | '.' | 'o' | 'A' | '$' | '!' => {
+                let length: i32 = if count.is_empty() {
+                    1
+                } else {
+                    count
+                        .parse()
+                        .map_err(|_| format!("invalid run length: {}", count))?
+                };
+                count.clear();
+
+                match c {
+                    'b' | '.' => x = x.checked_add(length).ok_or_else(too_far)?,
+                    'o' | 'A' => {
+                        let end = x.checked_add(length).ok_or_else(too_far)?;
+
+                        if let Some((width, height)) =
+                            size.filter(|(width, height)| end > *width || y >= *height)
+                        {
+                            return Err(format!(
+                                "RLE runs go past the header's {}x{} size",
+                                width, height
+                            ));
+                        }
+
+                        if cells.len() + length as usize > MAX_CELLS {
+                            return Err(format!(
+                                "RLE pattern has more than {} live cells",
+                                MAX_CELLS
+                            ));
+                        }
+
+                        cells.extend((x..end).map(|column| (column, y)));
+                        x = end;
+                    }
+                    '$' => {
+                        y = y.checked_add(length).ok_or_else(too_far)?;
+                        x = 0;
+                    }
+                    _ => break,
+                }
+            }
+            c if c.is_whitespace() => {}
+            _ => return Err(format!("unexpected character in RLE body: {}", c)),
+        }
+    }
+
+    Ok(cells)
+}
+
+/// Parses `x = <w>, y = <h>[, rule = <rule>]`, returning the size and the
+/// rule if given.
+fn parse_header(line: &str) -> Result<((i32, i32), Option<String>), String> {
+    let malformed = || format!("malformed RLE header: {}", line);
+    let (mut width, mut height, mut rule) = (None, None, None);
+
+    for field in line.split(',') {
+        let (key, value) = field.split_once('=').ok_or_else(malformed)?;
+        let value = value.trim();
+
+        match key.trim() {
+            "x" => width = Some(value.parse::<i32>().map_err(|_| malformed())?),
+            "y" => height = Some(value.parse::<i32>().map_err(|_| malformed())?),
+            "rule" => rule = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok((
+        (width.ok_or_else(malformed)?, height.ok_or_else(malformed)?),
+        rule,
+    ))
+}
+
+/// Runs for one row, dropping trailing dead cells.
+fn encode_row(row: &[bool]) -> Vec<String> {
+    let mut runs = Vec::new();
+    let mut index = 0;
+    let end = row
+        .iter()
+        .rposition(|alive| *alive)
+        .map_or(0, |last| last + 1);
+
+    while index < end {
+        let alive = row[index];
+        let length = row[index..end]
+            .iter()
+            .take_while(|other| **other == alive)
+            .count();
+
+        runs.push(run(length, if alive { 'o' } else { 'b' }));
+        index += length;
+    }
+
+    runs
+}
+
+fn run(length: usize, tag: char) -> String {
+    if length == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", length, tag)
+    }
+}
+
+fn bounds(cells: &[(i32, i32)]) -> (i32, i32, i32, i32) {
+    if cells.is_empty() {
+        return (0, 0, 0, 0);
+    }
+
+    cells.iter().fold(
+        (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+        |(min_x, min_y, max_x, max_y), (x, y)| {
+            (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+        },
+    )
+}
@@ -0,0 +1,77 @@
+/// A life-like rule in B/S notation: a dead cell is born when its number of
+/// live neighbors is one of the birth counts, and a live cell survives when
+/// it is one of the survival counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    /// Bit `n` is set when a dead cell with `n` live neighbors is born.
+    pub birth: u16,
+    /// Bit `n` is set when a live cell with `n` live neighbors survives.
+    pub survive: u16,
+}
+
+/// Well-known rules, in the order the settings panel cycles through them:
+/// Conway's Life, HighLife, Seeds, Day & Night, Morley and Replicator.
+pub const RULE_PRESETS: &[&str] = &[
+    "B3/S23",
+    "B36/S23",
+    "B2/S",
+    "B3678/S34678",
+    "B368/S245",
+    "B1357/S1357",
+];
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+impl Rule {
+    /// Conway's Life, B3/S23.
+    pub const CONWAY: Rule = Rule {
+        birth: 1 << 3,
+        survive: (1 << 2) | (1 << 3),
+    };
+
+    /// Parses a rulestring such as `B3/S23` or `b36/s23`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("expected a B/S rule such as B3/S23: {}", text);
+        let (birth, survive) = text.split_once('/').ok_or_else(invalid)?;
+        let counts = |digits: &str, prefix: char| {
+            let digits = digits
+                .strip_prefix(prefix)
+                .or_else(|| digits.strip_prefix(prefix.to_ascii_lowercase()))
+                .ok_or_else(invalid)?;
+
+            digits.chars().try_fold(0u16, |mask, c| match c {
+                '0'..='8' => Ok(mask | 1 << (c as u16 - '0' as u16)),
+                _ => Err(invalid()),
+            })
+        };
+
+        Ok(Rule {
+            birth: counts(birth, 'B')?,
+            survive: counts(survive, 'S')?,
+        })
+    }
+
+    /// The rulestring `parse` accepts for this rule.
+    pub fn name(&self) -> String {
+        let digits = |mask: u16| -> String {
+            (0..=8)
+                .filter(|count| mask & (1 << count) != 0)
+                .map(|count| char::from(b'0' + count as u8))
+                .collect()
+        };
+
+        format!("B{}/S{}", digits(self.birth), digits(self.survive))
+    }
+
+    pub fn is_born(&self, neighbor_count: usize) -> bool {
+        self.birth & (1 << neighbor_count) != 0
+    }
+
+    pub fn survives(&self, neighbor_count: usize) -> bool {
+        self.survive & (1 << neighbor_count) != 0
+    }
+}
@@ -0,0 +1,180 @@
+//! Surveys the space of B/S rules for interesting behavior.
+//!
+//! Where `evolve` breeds its way toward good rules, this runs every rule it
+//! is given, either all of them or a random sample, on the same standard
+//! soups and ranks them. Each soup is watched for how long it stays active
+//! before settling, whether its population stays bounded instead of filling
+//! the board, and whether spaceships come out of it.
+
+use crate::board::Board;
+use crate::collide::clusters;
+use crate::period::{find_periodicity, PeriodDetector};
+use crate::rng::Rng;
+use crate::rule::Rule;
+use crate::stats::GenerationStats;
+
+/// Number of B/S rules without B0, which would fill empty space every
+/// generation; `rules` enumerates them when asked for all.
+pub const RULE_COUNT: usize = 1 << 17;
+
+/// Side length of the standard soups, in cells.
+const SOUP_SIZE: usize = 64;
+const SOUP_DENSITY: f64 = 0.3;
+/// Standard soups run per rule; the soups are the same for every rule so
+/// scores compare.
+const SOUPS_PER_RULE: u64 = 3;
+/// Longest a soup is run for. Soups still active by then count as having a
+/// transient of at least this long.
+const MAX_GENERATIONS: usize = 500;
+/// Longest period recognized for a soup settling.
+const MAX_PERIOD: usize = 16;
+/// Longest period recognized for spaceships. Most small spaceships have
+/// periods of 4 or less, and checking objects is the slow part of a survey.
+const MAX_SHIP_PERIOD: usize = 8;
+/// Objects smaller or bigger than these aren't checked for being
+/// spaceships.
+const MIN_SHIP_CELLS: usize = 3;
+const MAX_SHIP_CELLS: usize = 40;
+/// How far past the starting soup's population a soup may grow and still
+/// count as bounded.
+const BOUNDED_GROWTH: f64 = 1.5;
+/// Density above which a soup counts as chaotic rather than structured.
+const CHAOS_DENSITY: f64 = 0.25;
+/// Generations between looks for spaceships among a soup's objects.
+const SHIP_CHECK_EVERY: usize = 50;
+
+/// How a rule behaved on the standard soups.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Survey {
+    pub rule: Rule,
+    /// How interesting the rule looks, from 0 to 1; see `score`.
+    pub score: f64,
+    /// Average generations the soups ran before settling or dying out.
+    pub transient: f64,
+    /// Fraction of soups whose population stayed within `BOUNDED_GROWTH`
+    /// of the starting soup's without dying out.
+    pub bounded: f64,
+    /// Fraction of soups a spaceship was seen in.
+    pub spaceships: f64,
+    /// Average final density and 2x2 block entropy, from `GenerationStats`.
+    pub density: f64,
+    pub entropy: f64,
+}
+
+/// The rules to survey: every rule without B0 if `samples` is `None`,
+/// otherwise that many distinct ones drawn at random from the seed.
+pub fn rules(samples: Option<usize>, seed: u64) -> Vec<Rule> {
+    let from_index = |index: usize| Rule {
+        birth: (index as u16 & 0xff) << 1,
+        survive: (index >> 8) as u16,
+    };
+    let samples = match samples {
+        Some(samples) if samples < RULE_COUNT => samples,
+        _ => return (0..RULE_COUNT).map(from_index).collect(),
+    };
+    let mut rng = Rng::new(seed);
+    let mut picked = vec![false; RULE_COUNT];
+    let mut rules = Vec::with_capacity(samples);
+
+    while rules.len() < samples {
+        let index = rng.next_u64() as usize % RULE_COUNT;
+
+        if !picked[index] {
+            picked[index] = true;
+            rules.push(from_index(index));
+        }
+    }
+
+    rules
+}
+
+/// Runs the rule on the standard soups and averages what they did.
+pub fn survey(rule: Rule) -> Survey {
+    let soups: Vec<SoupSurvey> = (1..=SOUPS_PER_RULE)
+        .map(|seed| survey_soup(rule, seed))
+        .collect();
+    let average = |value: &dyn Fn(&SoupSurvey) -> f64| {
+        soups.iter().map(value).sum::<f64>() / soups.len() as f64
+    };
+    let mut survey = Survey {
+        rule,
+        score: 0.0,
+        transient: average(&|soup| soup.transient as f64),
+        bounded: average(&|soup| soup.bounded as u8 as f64),
+        spaceships: average(&|soup| soup.spaceships as u8 as f64),
+        density: average(&|soup| soup.stats.density),
+        entropy: average(&|soup| soup.stats.entropy),
+    };
+
+    survey.score = score(&survey);
+    survey
+}
+
+/// The fraction of `MAX_GENERATIONS` the soups stay active, discounted as
+/// they approach chaos, then halved unless their growth stays bounded and
+/// spaceships appear, a quarter for each. Bounded growth alone is common
+/// among rules that die out quickly, so it only adds to long transients.
+fn score(survey: &Survey) -> f64 {
+    let longevity = survey.transient / MAX_GENERATIONS as f64;
+    let structure = (1.0 - survey.density / CHAOS_DENSITY).max(0.0);
+
+    longevity * structure * (2.0 + survey.bounded + survey.spaceships) / 4.0
+}
+
+/// What one soup did.
+struct SoupSurvey {
+    transient: usize,
+    bounded: bool,
+    spaceships: bool,
+    /// Statistics of the last generation run.
+    stats: GenerationStats,
+}
+
+fn survey_soup(rule: Rule, seed: u64) -> SoupSurvey {
+    let mut board = Board::seeded(SOUP_SIZE, SOUP_SIZE, seed, SOUP_DENSITY);
+    let mut detector = PeriodDetector::new(MAX_PERIOD);
+    let population_cap = (board.population() as f64 * BOUNDED_GROWTH) as usize;
+    let mut stats = GenerationStats::measure(&board, &board.alive_mask());
+    let mut bounded = true;
+    let mut spaceships = false;
+
+    board.rule = rule;
+
+    while board.generation < MAX_GENERATIONS {
+        let previously_alive = board.alive_mask();
+
+        board.update_live_neighbor_counts();
+        board.step();
+        stats = GenerationStats::measure(&board, &previously_alive);
+        bounded &= stats.population <= population_cap;
+
+        if !spaceships
+            && stats.density < CHAOS_DENSITY
+            && board.generation.is_multiple_of(SHIP_CHECK_EVERY)
+        {
+            spaceships = has_spaceship(&board);
+        }
+
+        if stats.population == 0 || detector.observe(&board).is_some() {
+            break;
+        }
+    }
+
+    SoupSurvey {
+        transient: board.generation,
+        bounded: bounded && stats.population > 0,
+        spaceships: spaceships || has_spaceship(&board),
+        stats,
+    }
+}
+
+/// Whether any of the board's small objects moves on its own.
+fn has_spaceship(board: &Board) -> bool {
+    clusters(&board.live_cells())
+        .iter()
+        .filter(|cluster| (MIN_SHIP_CELLS..=MAX_SHIP_CELLS).contains(&cluster.len()))
+        .any(|cluster| {
+            find_periodicity(cluster, board.rule, MAX_SHIP_PERIOD)
+                .is_some_and(|periodicity| periodicity.is_spaceship())
+        })
+}
@@ -0,0 +1,210 @@
+use crate::board::{Board, SOUP_DENSITY};
+use crate::pattern::Pattern;
+use crate::region::Region;
+use crate::replay::{parse_slot, Input};
+use crate::rule::Rule;
+use std::fs;
+
+/// Something a script does when the board reaches a generation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptCommand {
+    /// An input, applied as if the user had made it.
+    Input(Input),
+    /// Stop stepping; the user resumes.
+    Pause,
+    /// Save an image of the board, to the given path or one named after the
+    /// generation.
+    Screenshot(Option<String>),
+    /// Run the board straight on to a later generation.
+    Goto(usize),
+    /// Replace the board's cells with a catalog pattern, or one read from a
+    /// file, centered on it.
+    Load(String),
+    /// Replace the board's cells with a random soup.
+    Seed { seed: u64, density: f64 },
+}
+
+impl ScriptCommand {
+    /// Parses one command, as written after `at gen <n>:` in a script or
+    /// typed into the window's `:` console.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = text.split_whitespace().collect();
+
+        parse_command(&fields)
+    }
+
+    /// The inputs that carry out a `Load` or `Seed` on the board: clearing
+    /// it, which keeps poison and frozen cells, then pasting the new cells,
+    /// so the swap is recorded and replayed like any other edit. Other
+    /// commands have none.
+    pub fn replacement(&self, board: &Board) -> Result<Vec<Input>, String> {
+        let (cells, x, y) = match self {
+            ScriptCommand::Load(name) => {
+                let pattern = match Pattern::builtin(name) {
+                    Some(pattern) => pattern,
+                    None => Pattern::load(name)?,
+                };
+                let (left, top) = pattern.origin();
+                let (width, height) = pattern.size();
+
+                (
+                    pattern.cells,
+                    (board.width as i32 - width as i32) / 2 - left,
+                    (board.height as i32 - height as i32) / 2 - top,
+                )
+            }
+            ScriptCommand::Seed { seed, density } => (
+                Board::seeded(board.width, board.height, *seed, *density).live_cells(),
+                0,
+                0,
+            ),
+            _ => return Ok(Vec::new()),
+        };
+
+        Ok(vec![
+            Input::Clear(Region::new(0, 0, board.width, board.height)),
+            Input::Paste { cells, x, y },
+        ])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptAction {
+    pub generation: usize,
+    pub command: ScriptCommand,
+}
+
+/// Timed commands read from a `--script-actions` file, one per line:
+///
+/// ```text
+/// at gen 100: stamp glider at 40,40
+/// at gen 500: pause
+/// at gen 500: screenshot
+/// at gen 600: seed 42 density 0.3
+/// at gen 900: goto 5000
+/// ```
+///
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Script {
+    pub actions: Vec<ScriptAction>,
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut script = Script::default();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let context = |error: String| format!("line {}: {}", line_number + 1, error);
+            let (when, command) = line
+                .strip_prefix("at gen ")
+                .and_then(|rest| rest.split_once(':'))
+                .ok_or_else(|| context(format!("expected at gen <n>: <command>: {}", line)))?;
+            let generation = when
+                .trim()
+                .parse()
+                .map_err(|_| context(format!("invalid generation: {}", when)))?;
+            script.actions.push(ScriptAction {
+                generation,
+                command: ScriptCommand::parse(command).map_err(context)?,
+            });
+        }
+
+        Ok(script)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        crate::info!("loading script {}", path);
+
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read script {}: {}", path, error))?;
+
+        Self::parse(&text)
+    }
+
+    pub fn actions_at(&self, generation: usize) -> impl Iterator<Item = &ScriptAction> {
+        self.actions
+            .iter()
+            .filter(move |action| action.generation == generation)
+    }
+}
+
+fn parse_command(fields: &[&str]) -> Result<ScriptCommand, String> {
+    let input = match fields {
+        ["pause"] => return Ok(ScriptCommand::Pause),
+        ["screenshot"] => return Ok(ScriptCommand::Screenshot(None)),
+        ["screenshot", path] => return Ok(ScriptCommand::Screenshot(Some(path.to_string()))),
+        ["goto", generation] => return Ok(ScriptCommand::Goto(parse_number(generation)?)),
+        ["load", pattern] => return Ok(ScriptCommand::Load(pattern.to_string())),
+        ["seed", seed] => {
+            return Ok(ScriptCommand::Seed {
+                seed: parse_number(seed)?,
+                density: SOUP_DENSITY,
+            })
+        }
+        ["seed", seed, "density", density] => {
+            let density: f64 = parse_number(density)?;
+
+            if !(0.0..=1.0).contains(&density) {
+                return Err(format!("density must be between 0 and 1: {}", density));
+            }
+
+            return Ok(ScriptCommand::Seed {
+                seed: parse_number(seed)?,
+                density,
+            });
+        }
+        ["stamp", pattern, "at", position] => {
+            if Pattern::builtin(pattern).is_none() {
+                return Err(format!("unknown pattern: {}", pattern));
+            }
+
+            let (x, y) = parse_position(position)?;
+
+            Input::Stamp {
+                pattern: pattern.to_string(),
+                x,
+                y,
+            }
+        }
+        ["toggle", position] => {
+            let (x, y) = parse_position(position)?;
+            Input::Toggle { x, y }
+        }
+        ["poison", position] => {
+            let (x, y) = parse_position(position)?;
+            Input::Poison { x, y }
+        }
+        ["speed", fps] => match parse_number(fps)? {
+            0 => return Err("speed must be at least 1".to_string()),
+            fps => Input::Speed(fps),
+        },
+        ["trail", trail_length] => Input::TrailLength(parse_number(trail_length)?),
+        ["rule", rule] => Input::Rule(Rule::parse(rule)?),
+        ["bookmark", slot] => Input::SaveBookmark(parse_slot(slot)?),
+        ["restore", slot] => Input::RestoreBookmark(parse_slot(slot)?),
+        _ => return Err(format!("unknown command: {}", fields.join(" "))),
+    };
+
+    Ok(ScriptCommand::Input(input))
+}
+
+/// Parses an `<x>,<y>` cell position.
+fn parse_position(text: &str) -> Result<(i32, i32), String> {
+    let (x, y) = text
+        .split_once(',')
+        .ok_or_else(|| format!("expected <x>,<y>: {}", text))?;
+
+    Ok((parse_number(x)?, parse_number(y)?))
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("invalid number: {}", text))
+}
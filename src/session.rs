@@ -0,0 +1,261 @@
+use crate::board::{Board, Cell, CellState};
+use crate::config::Smoothing;
+use crate::pattern::Pattern;
+use crate::region::Region;
+use crate::rle;
+use crate::rule::Rule;
+use crate::topology::Topology;
+use std::fs;
+
+/// Where sessions are saved when no other path is given.
+pub const DEFAULT_SESSION_PATH: &str = "game_of_life.session";
+
+/// The GUI state saved alongside the board so a session can be picked up
+/// where it left off.
+///
+/// Sessions are saved as `key = value` lines like the config file. Live cells
+/// are stored as `<x> <y> <RLE body>`, dying cells as a list of
+/// `<x>,<y>,<cycles left>`, so trails survive a restore too, poison as a
+/// list of `<x>,<y>` and frozen cells like live ones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Session {
+    /// Camera position in cells and zoom factor.
+    pub camera: (i32, i32, f32),
+    pub fps: u32,
+    pub smoothing: Smoothing,
+}
+
+impl Session {
+    pub fn to_text(&self, board: &Board) -> String {
+        let live_cells = board.live_cells();
+        let frozen_cells = board.frozen_cells();
+        let dying: Vec<String> = board
+            .cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, cell)| match cell.state {
+                CellState::Dying(cycles_left) => {
+                    let (x, y) = board.index_to_coordinates(index);
+                    Some(format!("{},{},{}", x, y, cycles_left))
+                }
+                _ => None,
+            })
+            .collect();
+        let poison: Vec<String> = board
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_poison())
+            .map(|(index, _)| {
+                let (x, y) = board.index_to_coordinates(index);
+                format!("{},{}", x, y)
+            })
+            .collect();
+        let mut lines = vec![
+            format!("generation = {}", board.generation),
+            format!("board = {}x{}", board.width, board.height),
+            format!("topology = {}", board.topology.name()),
+            format!("rule = {}", board.rule.name()),
+            format!("trail_length = {}", board.trail_length),
+            format!(
+                "camera = {} {} {}",
+                self.camera.0, self.camera.1, self.camera.2
+            ),
+            format!("fps = {}", self.fps),
+            format!("smoothing = {}", self.smoothing.name()),
+        ];
+
+        if !live_cells.is_empty() {
+            lines.push(format!("alive = {}", cells_field(&live_cells)));
+        }
+
+        if !dying.is_empty() {
+            lines.push(format!("dying = {}", dying.join(" ")));
+        }
+
+        if !poison.is_empty() {
+            lines.push(format!("poison = {}", poison.join(" ")));
+        }
+
+        if !frozen_cells.is_empty() {
+            lines.push(format!("frozen = {}", cells_field(&frozen_cells)));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn parse(text: &str) -> Result<(Board, Self), String> {
+        let mut generation = 0;
+        let mut size = None;
+        let mut topology = Topology::default();
+        let mut rule = Rule::default();
+        let mut trail_length = None;
+        let mut camera = (0, 0, 1.0);
+        let mut fps = None;
+        let mut smoothing = Smoothing::default();
+        let mut alive = None;
+        let mut dying: Vec<(usize, usize, usize)> = Vec::new();
+        let mut poison: Vec<(usize, usize)> = Vec::new();
+        let mut frozen = None;
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let context = |error: String| format!("line {}: {}", line_number + 1, error);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| context(format!("expected key = value: {}", line)))?;
+            let value = value.trim();
+            let fields: Vec<&str> = value.split_whitespace().collect();
+
+            match (key.trim(), fields.as_slice()) {
+                ("generation", [number]) => generation = parse_field(number).map_err(context)?,
+                ("board", [board_size]) => {
+                    let (width, height) = board_size
+                        .split_once('x')
+                        .ok_or_else(|| context(format!("invalid board size: {}", value)))?;
+                    size = Some((
+                        parse_field(width).map_err(context)?,
+                        parse_field(height).map_err(context)?,
+                    ));
+                }
+                ("topology", [name]) => topology = Topology::parse(name).map_err(context)?,
+                ("rule", [name]) => rule = Rule::parse(name).map_err(context)?,
+                ("trail_length", [number]) => {
+                    trail_length = Some(parse_field(number).map_err(context)?)
+                }
+                ("camera", [x, y, zoom]) => {
+                    camera = (
+                        parse_field(x).map_err(context)?,
+                        parse_field(y).map_err(context)?,
+                        parse_field(zoom).map_err(context)?,
+                    )
+                }
+                ("fps", [number]) => fps = Some(parse_field(number).map_err(context)?),
+                ("smoothing", [name]) => smoothing = Smoothing::parse(name).map_err(context)?,
+                ("alive", [x, y, body]) => {
+                    alive = Some((
+                        parse_field(x).map_err(context)?,
+                        parse_field(y).map_err(context)?,
+                        rle::decode_body(body).map_err(context)?,
+                    ))
+                }
+                ("frozen", [x, y, body]) => {
+                    frozen = Some((
+                        parse_field::<i32>(x).map_err(context)?,
+                        parse_field::<i32>(y).map_err(context)?,
+                        rle::decode_body(body).map_err(context)?,
+                    ))
+                }
+                ("dying", cells) => {
+                    for cell in cells.iter() {
+                        match cell.split(',').collect::<Vec<_>>().as_slice() {
+                            [x, y, cycles_left] => dying.push((
+                                parse_field(x).map_err(context)?,
+                                parse_field(y).map_err(context)?,
+                                parse_field(cycles_left).map_err(context)?,
+                            )),
+                            _ => return Err(context(format!("invalid dying cell: {}", cell))),
+                        }
+                    }
+                }
+                ("poison", cells) => {
+                    for cell in cells.iter() {
+                        match cell.split(',').collect::<Vec<_>>().as_slice() {
+                            [x, y] => poison.push((
+                                parse_field(x).map_err(context)?,
+                                parse_field(y).map_err(context)?,
+                            )),
+                            _ => return Err(context(format!("invalid poison cell: {}", cell))),
+                        }
+                    }
+                }
+                (key, _) => return Err(context(format!("invalid session entry: {}", key))),
+            }
+        }
+
+        let (width, height) = size.ok_or("session has no board size")?;
+        let mut board = Board::empty(width, height);
+
+        board.generation = generation;
+        board.topology = topology;
+        board.rule = rule;
+
+        if let Some(trail_length) = trail_length {
+            board.set_trail_length(trail_length);
+        }
+
+        if let Some((x, y, cells)) = alive {
+            board.stamp(&Pattern::new("session", cells), x, y);
+        }
+
+        for (x, y, cycles_left) in dying {
+            if x < width && y < height {
+                board.cells[y * width + x].state = CellState::Dying(cycles_left);
+            }
+        }
+
+        for (x, y) in poison {
+            if x < width && y < height {
+                board.cells[y * width + x] = Cell::poison();
+            }
+        }
+
+        // Frozen cells ignore edits, so they are frozen once everything else
+        // is in place.
+        if let Some((x, y, cells)) = frozen {
+            for (dx, dy) in cells {
+                board.set_frozen(&Region::new(x + dx, y + dy, 1, 1), true);
+            }
+        }
+
+        let session = Session {
+            camera,
+            fps: fps.ok_or("session has no fps")?,
+            smoothing,
+        };
+
+        Ok((board, session))
+    }
+
+    pub fn load(path: &str) -> Result<(Board, Self), String> {
+        crate::info!("loading session {}", path);
+
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read session {}: {}", path, error))?;
+
+        Self::parse(&text)
+    }
+
+    /// Saves the session, writing to a temporary file first so a crash
+    /// mid-write leaves the previous session intact.
+    pub fn save(&self, board: &Board, path: &str) -> Result<(), String> {
+        crate::debug!("saving session to {}", path);
+
+        let temporary_path = format!("{}.tmp", path);
+
+        fs::write(&temporary_path, self.to_text(board))
+            .and_then(|_| fs::rename(&temporary_path, path))
+            .map_err(|error| format!("failed to write session {}: {}", path, error))
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str) -> Result<T, String> {
+    field
+        .parse()
+        .map_err(|_| format!("invalid number: {}", field))
+}
+
+/// Cells as the `<x> <y> <RLE body>` of a session entry, with `(x, y)` their
+/// top left corner.
+fn cells_field(cells: &[(i32, i32)]) -> String {
+    let (origin_x, origin_y) = cells.iter().fold((i32::MAX, i32::MAX), |(x, y), cell| {
+        (x.min(cell.0), y.min(cell.1))
+    });
+
+    format!("{} {} {}", origin_x, origin_y, rle::encode_body(cells))
+}
@@ -0,0 +1,171 @@
+use crate::board::Board;
+use std::collections::VecDeque;
+
+/// Counts describing one generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub population: usize,
+    /// Cells that came alive in the step to this generation.
+    pub births: usize,
+    /// Cells that stopped being alive in the step to this generation.
+    pub deaths: usize,
+    /// Fraction of cells alive.
+    pub density: f64,
+    /// Fraction of cells that changed state in the last step.
+    pub change_rate: f64,
+    /// Shannon entropy of the board's 2x2 blocks, normalized to 0.0 (every
+    /// block the same) to 1.0 (all 16 block patterns equally common).
+    pub entropy: f64,
+}
+
+impl GenerationStats {
+    /// Measures the board, comparing it with which cells were alive before
+    /// the last step.
+    pub fn measure(board: &Board, previously_alive: &[bool]) -> Self {
+        let mut stats = GenerationStats {
+            generation: board.generation,
+            ..GenerationStats::default()
+        };
+
+        for (cell, was_alive) in board.cells.iter().zip(previously_alive.iter()) {
+            match (was_alive, cell.is_alive()) {
+                (false, true) => stats.births += 1,
+                (true, false) => stats.deaths += 1,
+                _ => {}
+            }
+
+            if cell.is_alive() {
+                stats.population += 1;
+            }
+        }
+
+        let cell_count = board.cells.len().max(1) as f64;
+
+        stats.density = stats.population as f64 / cell_count;
+        stats.change_rate = (stats.births + stats.deaths) as f64 / cell_count;
+        stats.entropy = block_entropy(board);
+
+        stats
+    }
+
+    pub const CSV_HEADER: &'static str =
+        "generation,population,births,deaths,density,change_rate,entropy";
+
+    /// Parses a row written by `to_csv_row`.
+    pub fn parse_csv_row(row: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = row.trim().split(',').collect();
+        let invalid = || format!("invalid stats row: {}", row);
+
+        match fields.as_slice() {
+            [generation, population, births, deaths, density, change_rate, entropy] => {
+                Ok(GenerationStats {
+                    generation: generation.parse().map_err(|_| invalid())?,
+                    population: population.parse().map_err(|_| invalid())?,
+                    births: births.parse().map_err(|_| invalid())?,
+                    deaths: deaths.parse().map_err(|_| invalid())?,
+                    density: density.parse().map_err(|_| invalid())?,
+                    change_rate: change_rate.parse().map_err(|_| invalid())?,
+                    entropy: entropy.parse().map_err(|_| invalid())?,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The statistics as a row matching `CSV_HEADER`.
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{:.6},{:.6},{:.6}",
+            self.generation,
+            self.population,
+            self.births,
+            self.deaths,
+            self.density,
+            self.change_rate,
+            self.entropy
+        )
+    }
+}
+
+/// Normalized Shannon entropy of the distribution of live-cell patterns in
+/// the board's non-overlapping 2x2 blocks.
+fn block_entropy(board: &Board) -> f64 {
+    let mut counts = [0usize; 16];
+    let alive = |x: usize, y: usize| board.cells[y * board.width + x].is_alive() as usize;
+
+    for y in (0..board.height.saturating_sub(1)).step_by(2) {
+        for x in (0..board.width.saturating_sub(1)).step_by(2) {
+            let block = alive(x, y)
+                | alive(x + 1, y) << 1
+                | alive(x, y + 1) << 2
+                | alive(x + 1, y + 1) << 3;
+
+            counts[block] += 1;
+        }
+    }
+
+    let total: usize = counts.iter().sum();
+
+    if total == 0 {
+        return 0.0;
+    }
+
+    let entropy: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / total as f64;
+
+            -probability * probability.log2()
+        })
+        .sum();
+
+    // Four cells per block give at most 4 bits.
+    entropy / 4.0
+}
+
+/// The most recent generations' statistics, oldest first.
+#[derive(Clone, Debug)]
+pub struct StatsHistory {
+    capacity: usize,
+    samples: VecDeque<GenerationStats>,
+}
+
+impl StatsHistory {
+    pub fn new(capacity: usize) -> Self {
+        StatsHistory {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn record(&mut self, stats: GenerationStats) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(stats);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &GenerationStats> {
+        self.samples.iter()
+    }
+
+    /// Bytes held by the recorded samples.
+    pub fn memory_usage(&self) -> usize {
+        self.samples.capacity() * std::mem::size_of::<GenerationStats>()
+    }
+
+    /// Frees memory by forgetting the older half of the samples.
+    pub fn trim(&mut self) {
+        let keep = self.samples.len() / 2;
+
+        self.samples.drain(..self.samples.len() - keep);
+        self.samples.shrink_to_fit();
+    }
+
+    pub fn latest(&self) -> Option<&GenerationStats> {
+        self.samples.back()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::board::{Board, CellState};
+use crate::config::Config;
+
+/// Side of each cell in SVG user units.
+const CELL_SIZE: usize = 10;
+
+/// Renders the board as an SVG image with one `rect` per cell, in the
+/// config's theme colors. Dying cells are included when `include_trail` is
+/// set.
+pub fn encode(board: &Board, config: &Config, include_trail: bool) -> String {
+    let theme = &config.theme;
+    let (width, height) = (board.width * CELL_SIZE, board.height * CELL_SIZE);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        width, height, width, height
+    );
+
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width,
+        height,
+        theme.background.to_hex()
+    ));
+
+    for (index, cell) in board.cells.iter().enumerate() {
+        let color = match cell.state {
+            CellState::Alive => theme.alive,
+            CellState::Poison => theme.poison,
+            CellState::Dying(cycles_left) if include_trail => {
+                theme.dying_color(&config.trail_curve, cycles_left, board.trail_length)
+            }
+            _ => continue,
+        };
+        let (x, y) = board.index_to_coordinates(index);
+
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            x as usize * CELL_SIZE,
+            y as usize * CELL_SIZE,
+            CELL_SIZE,
+            CELL_SIZE,
+            color.to_hex()
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
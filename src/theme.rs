@@ -0,0 +1,251 @@
+use crate::image::RgbaImage;
+use crate::trail::{fade_progress, fade_stage, interpolate_stops, FadeCurve};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    pub position: f32,
+    pub color: Rgb,
+}
+
+/// Colors used to draw the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub background: Rgb,
+    pub alive: Rgb,
+    pub poison: Rgb,
+    /// When set, dying cells are colored by interpolating these stops over
+    /// their fade progress instead of using the grayscale fade curve.
+    pub dying_gradient: Option<Vec<ColorStop>>,
+    /// Image drawn for each live cell instead of a flat `alive` square.
+    pub alive_sprite: Option<RgbaImage>,
+    /// Images drawn for dying cells instead of flat squares, one per stage of
+    /// the fade from freshly dead to gone.
+    pub dying_sprites: Vec<RgbaImage>,
+    /// Whether `dying_sprites` are generated hatching, see `hatch_dying`.
+    pub dying_hatch: bool,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    pub const fn gray(intensity: u8) -> Self {
+        Rgb::new(intensity, intensity, intensity)
+    }
+
+    /// Parses a `#rrggbb` hex color.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let hex = text
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6)
+            .ok_or_else(|| format!("expected #rrggbb color: {}", text))?;
+        let channel = |range: std::ops::Range<usize>| {
+            u8::from_str_radix(&hex[range], 16).map_err(|_| format!("invalid color: {}", text))
+        };
+
+        Ok(Rgb::new(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+    }
+
+    /// Formats the color as `#rrggbb`, the form `parse` accepts.
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    pub fn lerp(self, other: Rgb, t: f32) -> Rgb {
+        let channel = |from: u8, to: u8| (from as f32 + t * (to as f32 - from as f32)) as u8;
+
+        Rgb::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+        )
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: Rgb::WHITE,
+            alive: Rgb::BLACK,
+            poison: Rgb::new(160, 32, 240),
+            dying_gradient: None,
+            alive_sprite: None,
+            dying_sprites: Vec::new(),
+            dying_hatch: false,
+        }
+    }
+}
+
+/// Names of the built-in themes, in the order the settings panel cycles
+/// through them.
+pub const THEME_PRESETS: &[&str] = &[
+    "light",
+    "dark",
+    "amber",
+    "phosphor",
+    "okabe-ito",
+    "viridis",
+    "cividis",
+    "high-contrast",
+];
+
+/// Pixels across a generated hatching sprite.
+const HATCH_SIZE: usize = 8;
+
+/// Stages of the fade that get their own hatching pattern.
+const HATCH_STAGES: usize = 4;
+
+impl Theme {
+    /// A built-in theme by name: `light` (the default), `dark`, `amber`,
+    /// `phosphor`, the colorblind-safe `okabe-ito`, `viridis` and `cividis`,
+    /// or `high-contrast`, which also hatches dying cells.
+    pub fn preset(name: &str) -> Result<Self, String> {
+        let (background, alive, poison, gradient) = match name {
+            "light" => return Ok(Theme::default()),
+            "dark" => ("#101018", "#e0e0e0", "#a020f0", "0:#808088 1:#101018"),
+            "amber" => ("#1a1000", "#ffb000", "#a020f0", "0:#a05000 1:#1a1000"),
+            "phosphor" => ("#001000", "#40ff40", "#a020f0", "0:#109010 1:#001000"),
+            // Blue, orange and vermillion from the Okabe-Ito palette stay
+            // apart under every common color vision deficiency.
+            "okabe-ito" => ("#ffffff", "#0072b2", "#d55e00", "0:#e69f00 1:#ffffff"),
+            // Sequential colormaps that change in lightness as well as hue,
+            // so the trail reads the same with any color vision.
+            "viridis" => (
+                "#440154",
+                "#fde725",
+                "#ffffff",
+                "0:#5ec962 0.35:#21918c 0.7:#3b528b 1:#440154",
+            ),
+            "cividis" => (
+                "#00204d",
+                "#ffea46",
+                "#ffffff",
+                "0:#a69d75 0.5:#575d6d 1:#00204d",
+            ),
+            "high-contrast" => ("#000000", "#ffffff", "#ffff00", "0:#00ffff 1:#004040"),
+            _ => return Err(format!("unknown theme: {}", name)),
+        };
+        let mut theme = Theme {
+            background: Rgb::parse(background)?,
+            alive: Rgb::parse(alive)?,
+            poison: Rgb::parse(poison)?,
+            dying_gradient: Some(parse_gradient(gradient)?),
+            ..Theme::default()
+        };
+
+        if name == "high-contrast" {
+            theme.hatch_dying();
+        }
+
+        Ok(theme)
+    }
+
+    /// Replaces the dying sprites with hatching in the theme's colors, a
+    /// different pattern for each stage of the fade, so dying cells can be
+    /// told apart by more than their shade: cross-hatched when freshly dead,
+    /// then diagonal lines, sparser lines and finally dots. Call it again
+    /// after changing the colors.
+    pub fn hatch_dying(&mut self) {
+        self.dying_hatch = true;
+        self.dying_sprites = (0..HATCH_STAGES)
+            .map(|stage| {
+                let progress = stage as f32 / HATCH_STAGES as f32;
+                let color = match &self.dying_gradient {
+                    Some(stops) => gradient_color(stops, progress),
+                    None => self.alive.lerp(self.background, progress / 2.0),
+                };
+
+                hatching(stage, color)
+            })
+            .collect();
+    }
+
+    pub fn dying_color(&self, curve: &FadeCurve, cycles_left: usize, trail_length: usize) -> Rgb {
+        match &self.dying_gradient {
+            Some(stops) => gradient_color(stops, fade_progress(cycles_left, trail_length)),
+            None => Rgb::gray(curve.intensity(cycles_left, trail_length)),
+        }
+    }
+
+    /// The sprite for a dying cell at its stage of the fade, if the theme
+    /// has dying sprites.
+    pub fn dying_sprite(&self, cycles_left: usize, trail_length: usize) -> Option<&RgbaImage> {
+        let stage = fade_stage(cycles_left, trail_length, self.dying_sprites.len());
+
+        self.dying_sprites.get(stage)
+    }
+}
+
+/// Parses gradient stops written as `<position>:#rrggbb ...`.
+pub fn parse_gradient(text: &str) -> Result<Vec<ColorStop>, String> {
+    let mut stops = text
+        .split_whitespace()
+        .map(|word| {
+            let (position, color) = word
+                .split_once(':')
+                .ok_or_else(|| format!("invalid gradient stop: {}", word))?;
+
+            Ok(ColorStop {
+                position: position
+                    .parse()
+                    .map_err(|_| format!("invalid stop position: {}", position))?,
+                color: Rgb::parse(color)?,
+            })
+        })
+        .collect::<Result<Vec<ColorStop>, String>>()?;
+
+    if stops.is_empty() {
+        return Err("gradient needs at least one stop".to_string());
+    }
+
+    stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+    Ok(stops)
+}
+
+/// A `HATCH_SIZE` square sprite with the given stage's pattern drawn in
+/// `color` on a transparent ground. Every pattern covers the top-left
+/// pixel, so cells drawn a pixel across still show up.
+fn hatching(stage: usize, color: Rgb) -> RgbaImage {
+    let mut image = RgbaImage::new(HATCH_SIZE, HATCH_SIZE, color);
+
+    for y in 0..HATCH_SIZE {
+        for x in 0..HATCH_SIZE {
+            let rising = (x + y).is_multiple_of(3);
+            let falling = (x + HATCH_SIZE - y).is_multiple_of(3);
+            let covered = match stage {
+                0 => rising || falling,
+                1 => rising,
+                2 => (x + HATCH_SIZE - y).is_multiple_of(4),
+                _ => x.is_multiple_of(4) && y.is_multiple_of(4),
+            };
+
+            if !covered {
+                image.pixels[(y * HATCH_SIZE + x) * 4 + 3] = 0;
+            }
+        }
+    }
+
+    image
+}
+
+fn gradient_color(stops: &[ColorStop], progress: f32) -> Rgb {
+    interpolate_stops(
+        stops,
+        progress,
+        |stop| stop.position,
+        |stop| stop.color,
+        Rgb::lerp,
+    )
+}
@@ -0,0 +1,157 @@
+/// What lies beyond a pair of opposite board edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boundary {
+    /// Cells beyond the edges are permanently dead, so patterns crossing
+    /// them lose the neighbors they would have had and usually break up.
+    Dead,
+    /// Each edge connects to the opposite one.
+    Wrap,
+    /// Each edge connects to the opposite one, mirrored along the other axis.
+    MirroredWrap,
+    /// Each edge connects to the opposite one, shifted along the other axis
+    /// by the given number of cells for every crossing.
+    ShiftedWrap(i32),
+    /// Cells beyond the edges mirror the cells just inside them, as if each
+    /// edge were a mirror, so patterns reaching them meet their own mirror
+    /// image.
+    Reflect,
+}
+
+impl Boundary {
+    /// Maps a coordinate onto an axis `length` cells long, or `None` if it
+    /// falls beyond a dead edge.
+    fn resolve(&self, value: i32, length: i32) -> Option<i32> {
+        match self {
+            _ if (0..length).contains(&value) => Some(value),
+            Boundary::Dead => None,
+            Boundary::Wrap | Boundary::MirroredWrap | Boundary::ShiftedWrap(_) => {
+                Some(value.rem_euclid(length))
+            }
+            Boundary::Reflect => {
+                let value = value.rem_euclid(length * 2);
+
+                Some(if value < length {
+                    value
+                } else {
+                    length * 2 - 1 - value
+                })
+            }
+        }
+    }
+}
+
+/// How the edges of the board connect, which determines the neighbors of
+/// cells along the border.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Topology {
+    /// Every edge is dead.
+    #[default]
+    Plane,
+    /// Both axes wrap around.
+    Torus,
+    /// Both axes wrap around, and crossing the bottom edge comes back in at
+    /// the top shifted right by the given number of cells (left crossing the
+    /// top). Oscillator and spaceship searches use these to fit patterns
+    /// whose symmetry doesn't line up with a plain torus.
+    ShiftedTorus(i32),
+    /// The left and right edges wrap; the top and bottom are dead.
+    Cylinder,
+    /// The left and right edges wrap; crossing the top or bottom edge wraps
+    /// to the other side mirrored horizontally.
+    KleinBottle,
+    /// Every edge reflects.
+    Reflecting,
+}
+
+impl Topology {
+    /// Parses a topology name, with shifted tori written `torus+<k>` or
+    /// `torus-<k>`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if let Some(shift) = text
+            .strip_prefix("torus")
+            .filter(|shift| shift.starts_with('+') || shift.starts_with('-'))
+        {
+            return shift
+                .parse()
+                .map(Topology::ShiftedTorus)
+                .map_err(|_| format!("invalid torus shift: {}", text));
+        }
+
+        match text {
+            "plane" => Ok(Topology::Plane),
+            "torus" => Ok(Topology::Torus),
+            "cylinder" => Ok(Topology::Cylinder),
+            "klein" => Ok(Topology::KleinBottle),
+            "reflect" => Ok(Topology::Reflecting),
+            _ => Err(format!("unknown topology: {}", text)),
+        }
+    }
+
+    /// The name `parse` accepts for this topology.
+    pub fn name(&self) -> String {
+        match self {
+            Topology::Plane => "plane".to_string(),
+            Topology::Torus => "torus".to_string(),
+            Topology::ShiftedTorus(shift) => format!("torus{:+}", shift),
+            Topology::Cylinder => "cylinder".to_string(),
+            Topology::KleinBottle => "klein".to_string(),
+            Topology::Reflecting => "reflect".to_string(),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Topology::Plane => Topology::Torus,
+            Topology::Torus | Topology::ShiftedTorus(_) => Topology::Cylinder,
+            Topology::Cylinder => Topology::KleinBottle,
+            Topology::KleinBottle => Topology::Reflecting,
+            Topology::Reflecting => Topology::Plane,
+        }
+    }
+
+    /// The boundaries of the left and right edges and of the top and bottom
+    /// edges.
+    pub fn boundaries(&self) -> (Boundary, Boundary) {
+        match self {
+            Topology::Plane => (Boundary::Dead, Boundary::Dead),
+            Topology::Torus => (Boundary::Wrap, Boundary::Wrap),
+            Topology::ShiftedTorus(shift) => (Boundary::Wrap, Boundary::ShiftedWrap(*shift)),
+            Topology::Cylinder => (Boundary::Wrap, Boundary::Dead),
+            Topology::KleinBottle => (Boundary::Wrap, Boundary::MirroredWrap),
+            Topology::Reflecting => (Boundary::Reflect, Boundary::Reflect),
+        }
+    }
+
+    /// Maps possibly out-of-bounds neighbor coordinates onto the board, or
+    /// `None` if they fall beyond a dead edge.
+    pub fn wrap(&self, x: i32, y: i32, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (width, height) = (width as i32, height as i32);
+        let (horizontal, vertical) = self.boundaries();
+        let x = match vertical {
+            Boundary::MirroredWrap if y.div_euclid(height) % 2 != 0 => width - 1 - x,
+            Boundary::ShiftedWrap(shift) => x + shift * y.div_euclid(height),
+            _ => x,
+        };
+
+        Some((
+            horizontal.resolve(x, width)? as usize,
+            vertical.resolve(y, height)? as usize,
+        ))
+    }
+
+    /// Maps the coordinates of an edit, such as a stamp or a toggle, onto the
+    /// board like `wrap`, except that edits beyond a reflecting edge are
+    /// dropped: the mirrored cells are only seen, not there.
+    pub fn place(&self, x: i32, y: i32, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (horizontal, vertical) = self.boundaries();
+        let reflected = |boundary: Boundary, value: i32, length: usize| {
+            boundary == Boundary::Reflect && !(0..length as i32).contains(&value)
+        };
+
+        if reflected(horizontal, x, width) || reflected(vertical, y, height) {
+            return None;
+        }
+
+        self.wrap(x, y, width, height)
+    }
+}
@@ -0,0 +1,159 @@
+/// How the gray intensity of a dying cell's trail changes as it fades.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum FadeCurve {
+    Linear,
+    #[default]
+    Exponential,
+    /// Intensity stops keyed on fade progress, where 0.0 is a cell that just
+    /// died and 1.0 is one that is about to disappear.
+    Gradient(Vec<GradientStop>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    pub position: f32,
+    pub intensity: u8,
+}
+
+impl FadeCurve {
+    /// Returns the gray level (0 is black, 255 is white) for a dying cell with
+    /// `cycles_left` out of `trail_length` cycles remaining.
+    pub fn intensity(&self, cycles_left: usize, trail_length: usize) -> u8 {
+        let remaining = 1.0 - fade_progress(cycles_left, trail_length);
+
+        match self {
+            FadeCurve::Linear => ((1.0 - remaining) * 255.0) as u8,
+            FadeCurve::Exponential => ((-0.25 * remaining).exp() * 255.0) as u8,
+            FadeCurve::Gradient(stops) => interpolate_stops(
+                stops,
+                1.0 - remaining,
+                |stop| stop.position,
+                |stop| stop.intensity,
+                |from, to, t| (from as f32 + t * (to as f32 - from as f32)) as u8,
+            ),
+        }
+    }
+
+    /// Parses `linear`, `exponential`, or `gradient <position>:<intensity> ...`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut words = text.split_whitespace();
+
+        match words.next() {
+            Some("linear") => Ok(FadeCurve::Linear),
+            Some("exponential") => Ok(FadeCurve::Exponential),
+            Some("gradient") => {
+                let mut stops = words
+                    .map(|word| {
+                        let (position, intensity) = word
+                            .split_once(':')
+                            .ok_or_else(|| format!("invalid gradient stop: {}", word))?;
+
+                        Ok(GradientStop {
+                            position: position
+                                .parse()
+                                .map_err(|_| format!("invalid stop position: {}", position))?,
+                            intensity: intensity
+                                .parse()
+                                .map_err(|_| format!("invalid stop intensity: {}", intensity))?,
+                        })
+                    })
+                    .collect::<Result<Vec<GradientStop>, String>>()?;
+
+                if stops.is_empty() {
+                    return Err("gradient needs at least one stop".to_string());
+                }
+
+                stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+                Ok(FadeCurve::Gradient(stops))
+            }
+            _ => Err(format!("unknown fade curve: {}", text)),
+        }
+    }
+}
+
+/// How cells on a dying trail take part in the rule.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DyingPolicy {
+    /// Dying cells count as dead neighbors but can be born again before
+    /// their trail runs out, as they always have here.
+    #[default]
+    Dead,
+    /// Dying cells count as live neighbors, and can be born again.
+    Alive,
+    /// Dying cells count as dead neighbors and can't be born again until
+    /// their trail has run out, as in the Generations family of rules.
+    Decay,
+}
+
+impl DyingPolicy {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "dead" => Ok(DyingPolicy::Dead),
+            "alive" => Ok(DyingPolicy::Alive),
+            "decay" => Ok(DyingPolicy::Decay),
+            _ => Err(format!("unknown dying policy: {}", text)),
+        }
+    }
+
+    /// The name `parse` accepts for this policy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DyingPolicy::Dead => "dead",
+            DyingPolicy::Alive => "alive",
+            DyingPolicy::Decay => "decay",
+        }
+    }
+}
+
+/// How far along its trail a dying cell is, from 0.0 (just died) to 1.0
+/// (about to disappear).
+pub fn fade_progress(cycles_left: usize, trail_length: usize) -> f32 {
+    if trail_length == 0 {
+        1.0
+    } else {
+        1.0 - (cycles_left.min(trail_length) as f32) / (trail_length as f32)
+    }
+}
+
+/// Which of `stages` equal steps of the fade a dying cell is in, from 0 to
+/// `stages - 1`.
+pub fn fade_stage(cycles_left: usize, trail_length: usize, stages: usize) -> usize {
+    let stage = (fade_progress(cycles_left, trail_length) * stages as f32) as usize;
+
+    stage.min(stages.saturating_sub(1))
+}
+
+/// Interpolates between the two stops surrounding `progress`, clamping to the
+/// first and last stops. `stops` must be sorted by position.
+pub(crate) fn interpolate_stops<S, T: Copy>(
+    stops: &[S],
+    progress: f32,
+    position: impl Fn(&S) -> f32,
+    value: impl Fn(&S) -> T,
+    lerp: impl Fn(T, T, f32) -> T,
+) -> T {
+    let first = &stops[0];
+    let last = &stops[stops.len() - 1];
+
+    if progress <= position(first) {
+        return value(first);
+    }
+
+    for pair in stops.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+
+        if progress <= position(to) {
+            let span = position(to) - position(from);
+            let t = if span > 0.0 {
+                (progress - position(from)) / span
+            } else {
+                1.0
+            };
+
+            return lerp(value(from), value(to), t);
+        }
+    }
+
+    value(last)
+}
@@ -0,0 +1,87 @@
+/// Largest weight a neighbor may have, which keeps every weighted sum small
+/// enough for the birth and survival bit masks.
+pub const MAX_WEIGHT: usize = 15;
+
+/// A weighted life rule, as in MCell's Weighted Life: each of the eight
+/// neighbor positions counts with its own weight, and a cell is born or
+/// survives when the weighted sum of its live neighbors is one of the birth
+/// or survival sums. With every weight 1 it is an ordinary B/S rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WeightedRule {
+    /// Weights of the neighbors to the northwest, north, northeast, west,
+    /// east, southwest, south and southeast.
+    pub weights: [usize; 8],
+    /// Bit `n` is set when a dead cell with a weighted sum of `n` is born.
+    pub birth: u128,
+    /// Bit `n` is set when a live cell with a weighted sum of `n` survives.
+    pub survive: u128,
+}
+
+impl Default for WeightedRule {
+    /// Conway's Life with every weight 1.
+    fn default() -> Self {
+        WeightedRule {
+            weights: [1; 8],
+            birth: 1 << 3,
+            survive: (1 << 2) | (1 << 3),
+        }
+    }
+}
+
+impl WeightedRule {
+    /// Parses the 3x3 weight table as three `/`-separated rows, e.g.
+    /// `1 2 1 / 2 . 2 / 1 2 1`, where the center is written as `.`.
+    pub fn parse_weights(text: &str) -> Result<[usize; 8], String> {
+        let invalid = || {
+            format!(
+                "expected three rows of weights 0-{} such as 1 2 1 / 2 . 2 / 1 2 1: {}",
+                MAX_WEIGHT, text
+            )
+        };
+        let cells: Vec<&str> = text
+            .split('/')
+            .flat_map(|row| row.split_whitespace())
+            .collect();
+
+        if cells.len() != 9 || cells[4] != "." {
+            return Err(invalid());
+        }
+
+        let mut weights = [0; 8];
+
+        for (weight, cell) in weights
+            .iter_mut()
+            .zip(cells.iter().enumerate().filter(|(index, _)| *index != 4))
+        {
+            *weight = cell
+                .1
+                .parse()
+                .ok()
+                .filter(|weight| *weight <= MAX_WEIGHT)
+                .ok_or_else(invalid)?;
+        }
+
+        Ok(weights)
+    }
+
+    /// Parses a list of weighted sums such as `3 5 6` into a bit mask.
+    pub fn parse_sums(text: &str) -> Result<u128, String> {
+        text.split_whitespace()
+            .try_fold(0, |mask, sum| match sum.parse::<usize>() {
+                Ok(sum) if sum <= MAX_WEIGHT * 8 => Ok(mask | 1 << sum),
+                _ => Err(format!(
+                    "weighted sums must be 0-{}: {}",
+                    MAX_WEIGHT * 8,
+                    sum
+                )),
+            })
+    }
+
+    pub fn is_born(&self, weighted_sum: usize) -> bool {
+        self.birth & (1 << weighted_sum) != 0
+    }
+
+    pub fn survives(&self, weighted_sum: usize) -> bool {
+        self.survive & (1 << weighted_sum) != 0
+    }
+}
@@ -0,0 +1,143 @@
+//! The run loop for window frontends built on `frontend::Surface`, which
+//! draw with the software rasterizer the exports use. It covers watching,
+//! editing and recording a run with the controls `frontend::Controls`
+//! documents; the tools, panels and overlays are SDL2 only.
+
+use crate::cli::Options;
+use crate::headless::MAX_PERIOD;
+use crate::output::RunSummary;
+use crate::{initial_board, load_playback};
+use game_of_life::frontend::{Control, Controls, Surface};
+use game_of_life::{debug, info, raster, Board, Bookmarks, Input, PeriodDetector, Replay};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_FPS: u32 = 60;
+const RENDER_FPS: u32 = 60;
+/// How long to sleep between looking for events, when there is nothing to
+/// do sooner.
+const IDLE: Duration = Duration::from_millis(2);
+
+/// Runs the board in the window until it is closed, playing back
+/// `--replay` and saving `--record` as the SDL2 frontend does.
+pub fn run(
+    surface: &mut impl Surface,
+    frontend: &'static str,
+    options: &Options,
+) -> Result<(), String> {
+    if options.dimensions == 3 {
+        return Err(format!("the {} frontend only shows 2D boards", frontend));
+    }
+
+    let (playback, seed) = load_playback(options)?;
+    let mut board = initial_board(options, seed);
+
+    if let Some(replay) = &playback {
+        replay.settings.apply(&mut board)?;
+    }
+
+    let mut bookmarks = Bookmarks::new();
+    let mut recording = Replay::recording(seed, &board);
+    let mut detector = PeriodDetector::new(MAX_PERIOD);
+    let mut periodicity = None;
+    let mut summary = RunSummary::start(frontend, &board);
+    let mut controls = Controls::new(
+        options.window_width,
+        options.window_height,
+        DEFAULT_FPS,
+        playback.is_some(),
+    );
+    let started_at = Instant::now();
+    let mut last_step_at = Instant::now();
+    let mut last_draw_at = Instant::now();
+    let mut dirty = true;
+
+    if let Some(replay) = &playback {
+        for event in replay.events_at(0) {
+            apply_input(&mut board, &mut bookmarks, &mut controls, &event.input)?;
+        }
+    }
+
+    'running: loop {
+        let mut step_once = false;
+
+        for event in surface.poll_events()? {
+            debug!("{} event: {:?}", frontend, event);
+
+            match controls.handle(&event, &board, &bookmarks) {
+                Control::None => {}
+                Control::Quit => break 'running,
+                Control::Step => step_once = true,
+                Control::Redraw => dirty = true,
+                Control::Input(input) => {
+                    apply_input(&mut board, &mut bookmarks, &mut controls, &input)?;
+                    recording.record(board.generation, started_at.elapsed().as_millis(), input);
+                    dirty = true;
+                }
+            }
+        }
+
+        let step_interval = Duration::from_secs(1) / controls.fps;
+
+        if step_once || (!controls.paused && last_step_at.elapsed() >= step_interval) {
+            board.step_n(1);
+            last_step_at = Instant::now();
+            periodicity = detector.observe(&board);
+            summary.record(board.population());
+            dirty = true;
+
+            if let Some(replay) = &playback {
+                for event in replay.events_at(board.generation) {
+                    apply_input(&mut board, &mut bookmarks, &mut controls, &event.input)?;
+                }
+            }
+        }
+
+        if dirty && last_draw_at.elapsed() >= Duration::from_secs(1) / RENDER_FPS {
+            let image =
+                raster::render_fit(&board, &options.config, controls.width, controls.height);
+
+            surface.present(&image)?;
+            surface.set_title(&title(&board, controls.paused))?;
+            last_draw_at = Instant::now();
+            dirty = false;
+        }
+
+        thread::sleep(IDLE);
+    }
+
+    info!(
+        "closing the {} window at generation {}",
+        frontend, board.generation
+    );
+
+    if let Some(path) = &options.record {
+        recording.save(path)?;
+    }
+
+    summary.finish(&board, periodicity.as_ref(), options)
+}
+
+fn apply_input(
+    board: &mut Board,
+    bookmarks: &mut Bookmarks,
+    controls: &mut Controls,
+    input: &Input,
+) -> Result<(), String> {
+    debug!("generation {}: {:?}", board.generation, input);
+
+    if let Input::Speed(fps) = input {
+        controls.fps = *fps;
+    }
+
+    input.apply(board, bookmarks)
+}
+
+fn title(board: &Board, paused: bool) -> String {
+    format!(
+        "Rusty Game of Life - generation {}, population {}{}",
+        board.generation,
+        board.population(),
+        if paused { " (paused)" } else { "" }
+    )
+}
@@ -0,0 +1,172 @@
+use crate::board::Board;
+use crate::bookmark::{Bookmarks, BOOKMARK_SLOTS};
+use crate::config::Config;
+use crate::session::Session;
+use crate::stats::GenerationStats;
+use crate::theme::Theme;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where workspaces are kept when no other directory is given.
+pub const DEFAULT_WORKSPACE_DIR: &str = "workspaces";
+
+/// Everything about an ongoing experiment, saved under a name so several can
+/// be juggled at once.
+///
+/// Each workspace is a directory holding the board, camera and rule as a
+/// `session` file, each saved bookmark as a `bookmark-<slot>` session file,
+/// the theme colors as config lines in `theme` and the stats history as
+/// `stats.csv`. Theme sprites are not saved.
+#[derive(Clone)]
+pub struct Workspace {
+    pub board: Board,
+    pub session: Session,
+    pub theme: Theme,
+    pub bookmarks: Bookmarks,
+    /// Recent generations' statistics, oldest first.
+    pub stats: Vec<GenerationStats>,
+}
+
+impl Workspace {
+    /// Names of the workspaces saved in `directory`, sorted. A missing
+    /// directory has none.
+    pub fn list(directory: &str) -> Result<Vec<String>, String> {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().join("session").is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+
+        names.sort();
+
+        Ok(names)
+    }
+
+    pub fn save(&self, directory: &str, name: &str) -> Result<(), String> {
+        let path = workspace_path(directory, name)?;
+        let write = |file: &str, text: String| {
+            fs::write(path.join(file), text)
+                .map_err(|error| format!("failed to write workspace {}: {}", name, error))
+        };
+
+        crate::info!("saving workspace {}", name);
+
+        fs::create_dir_all(&path)
+            .map_err(|error| format!("failed to create workspace {}: {}", name, error))?;
+
+        write("session", self.session.to_text(&self.board))?;
+        write("theme", theme_to_text(&self.theme))?;
+        write(
+            "stats.csv",
+            std::iter::once(GenerationStats::CSV_HEADER.to_string())
+                .chain(self.stats.iter().map(GenerationStats::to_csv_row))
+                .map(|line| line + "\n")
+                .collect(),
+        )?;
+
+        for slot in 0..BOOKMARK_SLOTS {
+            let file = format!("bookmark-{}", slot);
+
+            match self.bookmarks.saved().find(|(saved, _)| *saved == slot) {
+                Some((_, bookmark)) => write(&file, self.session.to_text(&bookmark.board))?,
+                None if path.join(&file).exists() => fs::remove_file(path.join(&file))
+                    .map_err(|error| format!("failed to write workspace {}: {}", name, error))?,
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn load(directory: &str, name: &str) -> Result<Self, String> {
+        let path = workspace_path(directory, name)?;
+        let read = |file: &str| {
+            fs::read_to_string(path.join(file))
+                .map_err(|error| format!("failed to read workspace {}: {}", name, error))
+        };
+        let context = |file: &str| {
+            let name = name.to_string();
+            let file = file.to_string();
+
+            move |error: String| format!("workspace {} {}: {}", name, file, error)
+        };
+
+        crate::info!("loading workspace {}", name);
+
+        let (board, session) = Session::parse(&read("session")?).map_err(context("session"))?;
+        let theme = Config::parse(&read("theme")?)
+            .map_err(context("theme"))?
+            .theme;
+        let stats = read("stats.csv")?
+            .lines()
+            .skip(1)
+            .map(GenerationStats::parse_csv_row)
+            .collect::<Result<_, _>>()
+            .map_err(context("stats.csv"))?;
+        let mut bookmarks = Bookmarks::new();
+
+        for slot in 0..BOOKMARK_SLOTS {
+            let file = format!("bookmark-{}", slot);
+
+            if path.join(&file).exists() {
+                let (bookmark, _) = Session::parse(&read(&file)?).map_err(context(&file))?;
+
+                bookmarks.save(slot, &bookmark)?;
+            }
+        }
+
+        Ok(Workspace {
+            board,
+            session,
+            theme,
+            bookmarks,
+            stats,
+        })
+    }
+}
+
+/// The directory of the named workspace. Names are limited to letters,
+/// digits, `-` and `_` so they can't point outside `directory`.
+fn workspace_path(directory: &str, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid {
+        return Err(format!(
+            "workspace names may only use letters, digits, - and _: {}",
+            name
+        ));
+    }
+
+    Ok(Path::new(directory).join(name))
+}
+
+/// The theme's colors as config lines.
+fn theme_to_text(theme: &Theme) -> String {
+    let mut lines = vec![
+        format!("theme.background = {}", theme.background.to_hex()),
+        format!("theme.alive = {}", theme.alive.to_hex()),
+        format!("theme.poison = {}", theme.poison.to_hex()),
+    ];
+
+    if let Some(stops) = &theme.dying_gradient {
+        let stops: Vec<String> = stops
+            .iter()
+            .map(|stop| format!("{}:{}", stop.position, stop.color.to_hex()))
+            .collect();
+
+        lines.push(format!("theme.dying_gradient = {}", stops.join(" ")));
+    }
+
+    if theme.dying_hatch {
+        lines.push("theme.dying_hatch = true".to_string());
+    }
+
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,688 @@
+//! A `Surface` on an X11 window, behind the `x11` feature. It speaks just
+//! enough of the X11 wire protocol to open a window, draw images into it and
+//! read the keyboard and mouse, directly over the display's socket, so no
+//! system libraries are needed. Displays are reached over a Unix socket, or
+//! over TCP on platforms without them.
+
+use crate::frontend::{Button, Key, Surface, WindowEvent};
+use crate::image::RgbaImage;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+const CREATE_WINDOW: u8 = 1;
+const MAP_WINDOW: u8 = 8;
+const INTERN_ATOM: u8 = 16;
+const CHANGE_PROPERTY: u8 = 18;
+const CREATE_GC: u8 = 55;
+const PUT_IMAGE: u8 = 72;
+const GET_KEYBOARD_MAPPING: u8 = 101;
+
+const ATOM: u32 = 4;
+const STRING: u32 = 31;
+const WM_NAME: u32 = 39;
+
+const KEY_PRESS_MASK: u32 = 0x1;
+const BUTTON_PRESS_MASK: u32 = 0x4;
+const EXPOSURE_MASK: u32 = 0x8000;
+const STRUCTURE_NOTIFY_MASK: u32 = 0x20000;
+const SHIFT_MASK: u16 = 0x1;
+const CONTROL_MASK: u16 = 0x4;
+
+const XK_ESCAPE: u32 = 0xff1b;
+const XK_KP_ADD: u32 = 0xffab;
+const XK_KP_SUBTRACT: u32 = 0xffad;
+const XK_KP_DECIMAL: u32 = 0xffae;
+const XK_KP_0: u32 = 0xffb0;
+const XK_KP_9: u32 = 0xffb9;
+
+/// A window on the X server named by `$DISPLAY`.
+pub struct X11Window {
+    connection: Connection,
+    window: u32,
+    gc: u32,
+}
+
+impl X11Window {
+    pub fn open(width: usize, height: usize, title: &str) -> Result<Self, String> {
+        let mut connection = Connection::open()?;
+        let (window, gc) = connection.create_window(width, height, title)?;
+
+        Ok(X11Window {
+            connection,
+            window,
+            gc,
+        })
+    }
+}
+
+impl Surface for X11Window {
+    fn poll_events(&mut self) -> Result<Vec<WindowEvent>, String> {
+        self.connection.poll_events()
+    }
+
+    fn present(&mut self, image: &RgbaImage) -> Result<(), String> {
+        self.connection.put_image(self.window, self.gc, image)
+    }
+
+    fn set_title(&mut self, title: &str) -> Result<(), String> {
+        self.connection.set_title(self.window, title)
+    }
+}
+
+enum Stream {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Stream {
+    fn connect(display: &str, host: &str, number: u16) -> io::Result<Self> {
+        match host {
+            #[cfg(unix)]
+            "" | "unix" => {
+                UnixStream::connect(format!("/tmp/.X11-unix/X{}", number)).map(Stream::Unix)
+            }
+            #[cfg(unix)]
+            path if path.starts_with('/') => UnixStream::connect(display).map(Stream::Unix),
+            #[cfg(not(unix))]
+            "" | "unix" => TcpStream::connect(("localhost", 6000 + number)).map(Stream::Tcp),
+            host => TcpStream::connect((host, 6000 + number)).map(Stream::Tcp),
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_nonblocking(nonblocking),
+            Stream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buffer),
+            Stream::Tcp(stream) => stream.read(buffer),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buffer),
+            Stream::Tcp(stream) => stream.write(buffer),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+            Stream::Tcp(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A connection to the X server named by `$DISPLAY`, drawing on its first
+/// screen. Only true color screens with 32 bits per pixel are supported,
+/// which is what every current server offers.
+struct Connection {
+    stream: Stream,
+    id_base: u32,
+    id_shift: u32,
+    next_id: u32,
+    root: u32,
+    root_depth: u8,
+    black_pixel: u32,
+    /// Red, green and blue masks of the root visual.
+    masks: [u32; 3],
+    big_endian_images: bool,
+    max_request_bytes: usize,
+    min_keycode: u8,
+    max_keycode: u8,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<u32>,
+    wm_protocols: u32,
+    wm_delete_window: u32,
+    /// Bytes read from the server that don't make a whole message yet.
+    unread: Vec<u8>,
+}
+
+impl Connection {
+    fn open() -> Result<Self, String> {
+        let display = env::var("DISPLAY").map_err(|_| "DISPLAY is not set".to_string())?;
+        let (host, number) = parse_display(&display)?;
+
+        crate::debug!("connecting to X display {}", display);
+
+        let stream = Stream::connect(&display, &host, number)
+            .map_err(|error| format!("failed to connect to X display {}: {}", display, error))?;
+
+        let mut connection = Self::set_up(stream, xauthority_cookie(number))?;
+
+        connection.wm_protocols = connection.intern_atom("WM_PROTOCOLS")?;
+        connection.wm_delete_window = connection.intern_atom("WM_DELETE_WINDOW")?;
+        connection.load_keyboard_mapping()?;
+
+        Ok(connection)
+    }
+
+    fn set_up(mut stream: Stream, cookie: Option<Vec<u8>>) -> Result<Self, String> {
+        let (name, data) = match &cookie {
+            Some(cookie) => (&b"MIT-MAGIC-COOKIE-1"[..], &cookie[..]),
+            None => (&b""[..], &b""[..]),
+        };
+        let mut request = vec![b'l', 0];
+
+        request.extend(11u16.to_le_bytes());
+        request.extend(0u16.to_le_bytes());
+        request.extend((name.len() as u16).to_le_bytes());
+        request.extend((data.len() as u16).to_le_bytes());
+        request.extend([0, 0]);
+        extend_padded(&mut request, name);
+        extend_padded(&mut request, data);
+        stream.write_all(&request).map_err(io_error)?;
+
+        let mut header = [0; 8];
+        stream.read_exact(&mut header).map_err(io_error)?;
+
+        let mut reply = vec![0; u16_at(&header, 6) as usize * 4];
+        stream.read_exact(&mut reply).map_err(io_error)?;
+
+        if header[0] != 1 {
+            let reason = &reply[..(header[1] as usize).min(reply.len())];
+
+            return Err(format!(
+                "the X server refused the connection: {}",
+                String::from_utf8_lossy(reason).trim()
+            ));
+        }
+
+        // Offsets below are from the start of the reply after its 8 byte
+        // header, as laid out in the protocol's connection setup.
+        let id_base = u32_at(&reply, 4);
+        let id_mask = u32_at(&reply, 8);
+        let vendor_length = u16_at(&reply, 16) as usize;
+        let max_request_bytes = u16_at(&reply, 18) as usize * 4;
+        let format_count = reply[21] as usize;
+        let big_endian_images = reply[22] == 1;
+        let min_keycode = reply[26];
+        let formats = 32 + padded(vendor_length);
+        let screen = formats + format_count * 8;
+        let root_depth = reply[screen + 38];
+        let bits_per_pixel = (0..format_count)
+            .map(|index| &reply[formats + index * 8..])
+            .find(|format| format[0] == root_depth)
+            .map(|format| format[1]);
+
+        if bits_per_pixel != Some(32) {
+            return Err(format!(
+                "the X screen's depth {} isn't drawn at 32 bits per pixel",
+                root_depth
+            ));
+        }
+
+        let root_visual = u32_at(&reply, screen + 32);
+        let masks = visual_masks(&reply[screen + 40..], reply[screen + 39], root_visual)
+            .ok_or("the X screen's visual isn't true color")?;
+
+        Ok(Connection {
+            stream,
+            id_base,
+            id_shift: id_mask.trailing_zeros(),
+            next_id: 1,
+            root: u32_at(&reply, screen),
+            root_depth,
+            black_pixel: u32_at(&reply, screen + 12),
+            masks,
+            big_endian_images,
+            max_request_bytes,
+            min_keycode,
+            max_keycode: reply[27],
+            keysyms_per_keycode: 0,
+            keysyms: Vec::new(),
+            wm_protocols: 0,
+            wm_delete_window: 0,
+            unread: Vec::new(),
+        })
+    }
+
+    /// Opens a window on the root, titled `title`, and a graphics context
+    /// to draw into it with. Returns the window and the context.
+    fn create_window(
+        &mut self,
+        width: usize,
+        height: usize,
+        title: &str,
+    ) -> Result<(u32, u32), String> {
+        let window = self.allocate_id();
+        let gc = self.allocate_id();
+        let mut body = Vec::new();
+
+        body.extend(window.to_le_bytes());
+        body.extend(self.root.to_le_bytes());
+        body.extend([0; 4]);
+        body.extend((width as u16).to_le_bytes());
+        body.extend((height as u16).to_le_bytes());
+        // No border, input and output, the parent's visual.
+        body.extend(0u16.to_le_bytes());
+        body.extend(1u16.to_le_bytes());
+        body.extend(0u32.to_le_bytes());
+        // Background pixel, then event mask.
+        body.extend(0x802u32.to_le_bytes());
+        body.extend(self.black_pixel.to_le_bytes());
+        body.extend(
+            (KEY_PRESS_MASK | BUTTON_PRESS_MASK | EXPOSURE_MASK | STRUCTURE_NOTIFY_MASK)
+                .to_le_bytes(),
+        );
+        self.request(CREATE_WINDOW, 0, &body)?;
+
+        self.set_title(window, title)?;
+        self.change_property(
+            window,
+            self.wm_protocols,
+            ATOM,
+            32,
+            &self.wm_delete_window.to_le_bytes(),
+        )?;
+
+        let mut body = Vec::new();
+
+        body.extend(gc.to_le_bytes());
+        body.extend(window.to_le_bytes());
+        body.extend(0u32.to_le_bytes());
+        self.request(CREATE_GC, 0, &body)?;
+        self.request(MAP_WINDOW, 0, &window.to_le_bytes())?;
+
+        Ok((window, gc))
+    }
+
+    fn set_title(&mut self, window: u32, title: &str) -> Result<(), String> {
+        self.change_property(window, WM_NAME, STRING, 8, title.as_bytes())
+    }
+
+    /// Draws the image with its top left corner at the window's, in strips
+    /// small enough for the server's request size limit.
+    fn put_image(&mut self, window: u32, gc: u32, image: &RgbaImage) -> Result<(), String> {
+        let row_bytes = image.width * 4;
+        let rows_per_strip = ((self.max_request_bytes - 24) / row_bytes.max(1)).max(1);
+
+        for top in (0..image.height).step_by(rows_per_strip) {
+            let rows = rows_per_strip.min(image.height - top);
+            let mut body = Vec::with_capacity(20 + rows * row_bytes);
+
+            body.extend(window.to_le_bytes());
+            body.extend(gc.to_le_bytes());
+            body.extend((image.width as u16).to_le_bytes());
+            body.extend((rows as u16).to_le_bytes());
+            body.extend(0i16.to_le_bytes());
+            body.extend((top as i16).to_le_bytes());
+            body.extend([0, self.root_depth, 0, 0]);
+
+            for pixel in image.pixels[top * row_bytes..(top + rows) * row_bytes].chunks_exact(4) {
+                let value = self
+                    .masks
+                    .iter()
+                    .zip(pixel)
+                    .fold(0, |value, (mask, channel)| {
+                        value | scale_to_mask(*channel, *mask)
+                    });
+
+                if self.big_endian_images {
+                    body.extend(value.to_be_bytes());
+                } else {
+                    body.extend(value.to_le_bytes());
+                }
+            }
+
+            // ZPixmap, the only format that takes whole pixels.
+            self.request(PUT_IMAGE, 2, &body)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads whatever the server has sent since the last poll without
+    /// waiting for more.
+    fn poll_events(&mut self) -> Result<Vec<WindowEvent>, String> {
+        let mut buffer = [0; 4096];
+
+        self.stream.set_nonblocking(true).map_err(io_error)?;
+
+        let read = loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => break Err("the X server closed the connection".to_string()),
+                Ok(count) => self.unread.extend(&buffer[..count]),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => break Err(io_error(error)),
+            }
+        };
+
+        self.stream.set_nonblocking(false).map_err(io_error)?;
+        read?;
+
+        let mut events = Vec::new();
+
+        while let Some(message) = self.next_message() {
+            if let Some(event) = self.parse_event(&message) {
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Takes the next whole message off the unread bytes. Replies and
+    /// generic events carry a length past the usual 32 bytes.
+    fn next_message(&mut self) -> Option<Vec<u8>> {
+        if self.unread.len() < 32 {
+            return None;
+        }
+
+        let length = match self.unread[0] & 0x7f {
+            1 | 35 => 32 + u32_at(&self.unread, 4) as usize * 4,
+            _ => 32,
+        };
+
+        if self.unread.len() < length {
+            return None;
+        }
+
+        Some(self.unread.drain(..length).collect())
+    }
+
+    fn parse_event(&self, message: &[u8]) -> Option<WindowEvent> {
+        match message[0] & 0x7f {
+            0 => {
+                crate::warn!("X error {} from request {}", message[1], message[10]);
+                None
+            }
+            2 => {
+                let state = u16_at(message, 28);
+
+                Some(WindowEvent::Key {
+                    key: keysym_key(self.keysym(message[1], state & SHIFT_MASK != 0))?,
+                    ctrl: state & CONTROL_MASK != 0,
+                })
+            }
+            4 => Some(WindowEvent::Click {
+                button: match message[1] {
+                    1 => Button::Left,
+                    3 => Button::Right,
+                    _ => return None,
+                },
+                x: i16_at(message, 24) as i32,
+                y: i16_at(message, 26) as i32,
+            }),
+            12 => Some(WindowEvent::Expose),
+            22 => Some(WindowEvent::Resize {
+                width: u16_at(message, 20) as usize,
+                height: u16_at(message, 22) as usize,
+            }),
+            33 if u32_at(message, 8) == self.wm_protocols
+                && u32_at(message, 12) == self.wm_delete_window =>
+            {
+                Some(WindowEvent::Close)
+            }
+            _ => None,
+        }
+    }
+
+    /// The keysym a keycode produces, the shifted one if shift is held and
+    /// the key has one.
+    fn keysym(&self, keycode: u8, shifted: bool) -> u32 {
+        let first = keycode.saturating_sub(self.min_keycode) as usize * self.keysyms_per_keycode;
+        let symbol = |column: usize| {
+            (column < self.keysyms_per_keycode)
+                .then(|| self.keysyms.get(first + column).copied())
+                .flatten()
+                .unwrap_or(0)
+        };
+
+        match symbol(1) {
+            shifted_symbol if shifted && shifted_symbol != 0 => shifted_symbol,
+            _ => symbol(0),
+        }
+    }
+
+    fn intern_atom(&mut self, name: &str) -> Result<u32, String> {
+        let mut body = Vec::new();
+
+        body.extend((name.len() as u16).to_le_bytes());
+        body.extend([0, 0]);
+        extend_padded(&mut body, name.as_bytes());
+        self.request(INTERN_ATOM, 0, &body)?;
+
+        Ok(u32_at(&self.reply()?, 8))
+    }
+
+    fn load_keyboard_mapping(&mut self) -> Result<(), String> {
+        let count = self.max_keycode - self.min_keycode + 1;
+
+        self.request(GET_KEYBOARD_MAPPING, 0, &[self.min_keycode, count, 0, 0])?;
+
+        let reply = self.reply()?;
+
+        self.keysyms_per_keycode = reply[1] as usize;
+        self.keysyms = reply[32..]
+            .chunks_exact(4)
+            .map(|keysym| u32_at(keysym, 0))
+            .collect();
+
+        Ok(())
+    }
+
+    fn change_property(
+        &mut self,
+        window: u32,
+        property: u32,
+        kind: u32,
+        format: u8,
+        data: &[u8],
+    ) -> Result<(), String> {
+        let mut body = Vec::new();
+
+        body.extend(window.to_le_bytes());
+        body.extend(property.to_le_bytes());
+        body.extend(kind.to_le_bytes());
+        body.extend([format, 0, 0, 0]);
+        body.extend(((data.len() * 8 / format as usize) as u32).to_le_bytes());
+        extend_padded(&mut body, data);
+
+        // Mode 0 replaces the property.
+        self.request(CHANGE_PROPERTY, 0, &body)
+    }
+
+    /// Waits for the reply to the request just sent, keeping any events that
+    /// arrive first for the next poll.
+    fn reply(&mut self) -> Result<Vec<u8>, String> {
+        let mut buffer = [0; 4096];
+        let mut events = Vec::new();
+
+        let reply = loop {
+            match self.next_message() {
+                Some(message) if message[0] == 0 => {
+                    break Err(format!(
+                        "X error {} from request {}",
+                        message[1], message[10]
+                    ))
+                }
+                Some(message) if message[0] == 1 => break Ok(message),
+                Some(message) => events.push(message),
+                None => {
+                    let count = self.stream.read(&mut buffer).map_err(io_error)?;
+
+                    if count == 0 {
+                        break Err("the X server closed the connection".to_string());
+                    }
+
+                    self.unread.extend(&buffer[..count]);
+                }
+            }
+        };
+
+        let rest = std::mem::take(&mut self.unread);
+
+        self.unread = events.concat();
+        self.unread.extend(rest);
+
+        reply
+    }
+
+    fn request(&mut self, opcode: u8, data: u8, body: &[u8]) -> Result<(), String> {
+        let mut request = vec![opcode, data];
+
+        request.extend((((4 + padded(body.len())) / 4) as u16).to_le_bytes());
+        extend_padded(&mut request, body);
+
+        self.stream.write_all(&request).map_err(io_error)
+    }
+
+    fn allocate_id(&mut self) -> u32 {
+        let id = self.id_base | (self.next_id << self.id_shift);
+
+        self.next_id += 1;
+
+        id
+    }
+}
+
+/// The key a keysym stands for, if the controls use it. Latin-1 keysyms are
+/// the characters themselves; keypad keys count as the characters on them.
+fn keysym_key(keysym: u32) -> Option<Key> {
+    match keysym {
+        XK_ESCAPE => Some(Key::Escape),
+        XK_KP_ADD => Some(Key::Char('+')),
+        XK_KP_SUBTRACT => Some(Key::Char('-')),
+        XK_KP_DECIMAL => Some(Key::Char('.')),
+        XK_KP_0..=XK_KP_9 => char::from_digit(keysym - XK_KP_0, 10).map(Key::Char),
+        0x20..=0x7e => Some(Key::Char(keysym as u8 as char)),
+        _ => None,
+    }
+}
+
+/// Splits a display name like `:0`, `:1.0`, `unix:0` or `host:10.0` into
+/// its host and display number.
+fn parse_display(display: &str) -> Result<(String, u16), String> {
+    let (host, rest) = display
+        .rsplit_once(':')
+        .ok_or_else(|| format!("invalid DISPLAY: {}", display))?;
+    let number = rest.split('.').next().unwrap_or(rest);
+
+    Ok((
+        host.to_string(),
+        number
+            .parse()
+            .map_err(|_| format!("invalid DISPLAY: {}", display))?,
+    ))
+}
+
+/// The MIT-MAGIC-COOKIE-1 for the display from `$XAUTHORITY` or
+/// `~/.Xauthority`, if there is one. Entries are a big-endian family
+/// followed by length-prefixed address, display number, name and data.
+fn xauthority_cookie(display: u16) -> Option<Vec<u8>> {
+    let path = env::var("XAUTHORITY").ok().or_else(|| {
+        env::var("HOME")
+            .ok()
+            .map(|home| format!("{}/.Xauthority", home))
+    })?;
+    let bytes = fs::read(path).ok()?;
+    let display = display.to_string();
+    let mut offset = 0;
+    let field = |offset: &mut usize| -> Option<Vec<u8>> {
+        let length = u16::from_be_bytes([*bytes.get(*offset)?, *bytes.get(*offset + 1)?]) as usize;
+        let value = bytes.get(*offset + 2..*offset + 2 + length)?.to_vec();
+
+        *offset += 2 + length;
+        Some(value)
+    };
+
+    while offset + 2 <= bytes.len() {
+        offset += 2;
+
+        let _address = field(&mut offset)?;
+        let number = field(&mut offset)?;
+        let name = field(&mut offset)?;
+        let data = field(&mut offset)?;
+
+        if name == b"MIT-MAGIC-COOKIE-1" && (number.is_empty() || number == display.as_bytes()) {
+            return Some(data);
+        }
+    }
+
+    None
+}
+
+/// The red, green and blue masks of the visual, found among the screen's
+/// allowed depths, if it is a true color one.
+fn visual_masks(depths: &[u8], depth_count: u8, visual: u32) -> Option<[u32; 3]> {
+    let mut offset = 0;
+
+    for _ in 0..depth_count {
+        let visual_count = u16_at(depths, offset + 2) as usize;
+
+        for index in 0..visual_count {
+            let entry = &depths[offset + 8 + index * 24..];
+
+            // Class 4 is TrueColor.
+            if u32_at(entry, 0) == visual && entry[4] == 4 {
+                return Some([u32_at(entry, 8), u32_at(entry, 12), u32_at(entry, 16)]);
+            }
+        }
+
+        offset += 8 + visual_count * 24;
+    }
+
+    None
+}
+
+/// An 8 bit channel scaled into the bits of `mask`.
+fn scale_to_mask(channel: u8, mask: u32) -> u32 {
+    let bits = mask.count_ones();
+    let value = if bits >= 8 {
+        (channel as u32) << (bits - 8)
+    } else {
+        channel as u32 >> (8 - bits)
+    };
+
+    (value << mask.trailing_zeros()) & mask
+}
+
+fn padded(length: usize) -> usize {
+    length.div_ceil(4) * 4
+}
+
+fn extend_padded(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend(bytes);
+    buffer.resize(buffer.len() + padded(bytes.len()) - bytes.len(), 0);
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn i16_at(bytes: &[u8], offset: usize) -> i16 {
+    u16_at(bytes, offset) as i16
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn io_error(error: io::Error) -> String {
+    format!("X connection failed: {}", error)
+}
@@ -0,0 +1,477 @@
+//! Just enough of zlib (RFC 1950) and DEFLATE (RFC 1951) to read and write
+//! PNG image data.
+
+/// Reads bits least significant first, as DEFLATE packs them.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        let mut value = 0;
+
+        for index in 0..count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("unexpected end of compressed data")?;
+
+            value |= ((byte as u32 >> self.bit) & 1) << index;
+            self.bit += 1;
+
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+/// Writes bits least significant first, as DEFLATE packs them.
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.output.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which DEFLATE stores most significant bit
+    /// first.
+    fn code(&mut self, code: u32, length: u32) {
+        self.bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.output.push(self.buffer as u8);
+        }
+
+        self.output
+    }
+}
+
+/// A canonical Huffman code, stored as symbol counts per code length and
+/// the symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+
+        counts[0] = 0;
+
+        let mut offsets = [0; 16];
+
+        for length in 1..16 {
+            offsets[length] = offsets[length - 1] + counts[length - 1];
+        }
+
+        let mut symbols = vec![0; lengths.len()];
+
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+
+            let count = self.counts[length] as i32;
+
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+
+        Err("invalid Huffman code".to_string())
+    }
+}
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored in dynamic blocks.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Distance back the compressor searches for matches.
+const WINDOW_SIZE: usize = 32768;
+/// Previous occurrences of a 3-byte prefix checked for each match.
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: u32 = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// Compresses data into a zlib stream: a single DEFLATE block using the
+/// fixed Huffman codes and greedy LZ77 matching. That suits images with
+/// large flat areas well without the bookkeeping of dynamic codes.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        output: vec![0x78, 0x01],
+        buffer: 0,
+        count: 0,
+    };
+    let mut matcher = Matcher {
+        data,
+        head: vec![usize::MAX; 1 << HASH_BITS],
+        previous: vec![usize::MAX; WINDOW_SIZE],
+    };
+    let mut position = 0;
+
+    // Final block with fixed codes.
+    writer.bits(1, 1);
+    writer.bits(1, 2);
+
+    while position < data.len() {
+        let (length, distance) = matcher.longest_match(position);
+
+        if length >= MIN_MATCH {
+            write_match(&mut writer, length, distance);
+
+            for offset in 0..length {
+                matcher.insert(position + offset);
+            }
+
+            position += length;
+        } else {
+            write_literal(&mut writer, data[position] as u32);
+            matcher.insert(position);
+            position += 1;
+        }
+    }
+
+    write_literal(&mut writer, 256);
+
+    let mut output = writer.finish();
+
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+/// Chains of earlier positions sharing each 3-byte prefix, for finding LZ77
+/// matches.
+struct Matcher<'a> {
+    data: &'a [u8],
+    /// Latest position of each prefix hash.
+    head: Vec<usize>,
+    /// The position before each one with the same hash, indexed modulo the
+    /// window size.
+    previous: Vec<usize>,
+}
+
+impl<'a> Matcher<'a> {
+    fn hash(&self, position: usize) -> usize {
+        let bytes = &self.data[position..position + MIN_MATCH];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        (value.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+    }
+
+    fn insert(&mut self, position: usize) {
+        if position + MIN_MATCH <= self.data.len() {
+            let hash = self.hash(position);
+
+            self.previous[position % WINDOW_SIZE] = self.head[hash];
+            self.head[hash] = position;
+        }
+    }
+
+    /// Finds the longest earlier match for the data at `position`, returning
+    /// its length and distance.
+    fn longest_match(&self, position: usize) -> (usize, usize) {
+        if position + MIN_MATCH > self.data.len() {
+            return (0, 0);
+        }
+
+        let data = self.data;
+        let limit = (data.len() - position).min(MAX_MATCH);
+        let mut candidate = self.head[self.hash(position)];
+        let mut best = (0, 0);
+
+        for _ in 0..MAX_CHAIN {
+            if candidate == usize::MAX || position - candidate > WINDOW_SIZE {
+                break;
+            }
+
+            let length = (0..limit)
+                .take_while(|offset| data[candidate + offset] == data[position + offset])
+                .count();
+
+            if length > best.0 {
+                best = (length, position - candidate);
+
+                if length == limit {
+                    break;
+                }
+            }
+
+            let next = self.previous[candidate % WINDOW_SIZE];
+
+            if next == usize::MAX || next >= candidate {
+                break;
+            }
+
+            candidate = next;
+        }
+
+        best
+    }
+}
+
+/// Writes a literal byte or the end of block symbol (256) with the fixed
+/// code.
+fn write_literal(writer: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => writer.code(0x30 + symbol, 8),
+        144..=255 => writer.code(0x190 + symbol - 144, 9),
+        256..=279 => writer.code(symbol - 256, 7),
+        _ => writer.code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASES
+        .iter()
+        .rposition(|base| *base as usize <= length)
+        .expect("match is at least 3 long");
+
+    write_literal(writer, 257 + index as u32);
+    writer.bits(
+        (length - LENGTH_BASES[index] as usize) as u32,
+        LENGTH_EXTRA_BITS[index] as u32,
+    );
+
+    let index = DISTANCE_BASES
+        .iter()
+        .rposition(|base| *base as usize <= distance)
+        .expect("distance is at least 1");
+
+    writer.code(index as u32, 5);
+    writer.bits(
+        (distance - DISTANCE_BASES[index] as usize) as u32,
+        DISTANCE_EXTRA_BITS[index] as u32,
+    );
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+
+        a %= 65521;
+        b %= 65521;
+    }
+
+    b << 16 | a
+}
+
+/// Decompresses a zlib stream. The trailing checksum is not verified.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 2 || !(data[0] as u16 * 256 + data[1] as u16).is_multiple_of(31) {
+        return Err("invalid zlib header".to_string());
+    }
+
+    if data[0] & 0x0f != 8 {
+        return Err("unsupported zlib compression method".to_string());
+    }
+
+    if data[1] & 0x20 != 0 {
+        return Err("zlib preset dictionaries are not supported".to_string());
+    }
+
+    inflate(&data[2..])
+}
+
+/// Decompresses raw DEFLATE data.
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        bit: 0,
+    };
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+
+        match reader.bits(2)? {
+            0 => inflate_stored(&mut reader, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err("invalid DEFLATE block type".to_string()),
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn inflate_stored(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), String> {
+    reader.align_to_byte();
+
+    let start = reader.position;
+    let header = reader
+        .data
+        .get(start..start + 4)
+        .ok_or("unexpected end of compressed data")?;
+    let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+    let bytes = reader
+        .data
+        .get(start + 4..start + 4 + length)
+        .ok_or("unexpected end of compressed data")?;
+
+    output.extend_from_slice(bytes);
+    reader.position = start + 4 + length;
+
+    Ok(())
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    let mut code_lengths = [0; 19];
+
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*index] = reader.bits(3)? as u8;
+    }
+
+    let code_length_code = Huffman::new(&code_lengths);
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+
+    while lengths.len() < literal_count + distance_count {
+        let (repeated, times) = match code_length_code.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths.last().ok_or("repeat with no previous length")?,
+                3 + reader.bits(2)?,
+            ),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+
+        lengths.extend(std::iter::repeat_n(repeated, times as usize));
+    }
+
+    if lengths.len() != literal_count + distance_count {
+        return Err("code lengths overrun".to_string());
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                let length = *LENGTH_BASES.get(index).ok_or("invalid length code")? as usize
+                    + reader.bits(LENGTH_EXTRA_BITS[index] as u32)? as usize;
+                let index = distances.decode(reader)? as usize;
+                let distance = *DISTANCE_BASES.get(index).ok_or("invalid distance code")? as usize
+                    + reader.bits(DISTANCE_EXTRA_BITS[index] as u32)? as usize;
+
+                if distance > output.len() {
+                    return Err("distance reaches before start of data".to_string());
+                }
+
+                let start = output.len() - distance;
+
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+        }
+    }
+}
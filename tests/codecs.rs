@@ -0,0 +1,129 @@
+use game_of_life::rng::Rng;
+use game_of_life::{png, zlib, RgbaImage};
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap())
+        .collect()
+}
+
+/// Bytes drawn from the first `alphabet` values, so low alphabets give the
+/// compressor repeats to find and 256 gives it none.
+fn random_bytes(rng: &mut Rng, length: usize, alphabet: u64) -> Vec<u8> {
+    (0..length)
+        .map(|_| (rng.next_u64() % alphabet) as u8)
+        .collect()
+}
+
+#[test]
+fn zlib_round_trips_random_data() {
+    let mut rng = Rng::new(7);
+
+    for length in [0, 1, 2, 100, 4096, 70_000] {
+        for alphabet in [2, 16, 256] {
+            let data = random_bytes(&mut rng, length, alphabet);
+
+            assert_eq!(
+                zlib::decompress(&zlib::compress(&data)).as_ref(),
+                Ok(&data),
+                "{} bytes from an alphabet of {}",
+                length,
+                alphabet
+            );
+        }
+    }
+}
+
+#[test]
+fn zlib_decodes_streams_from_another_encoder() {
+    // Written by Python's zlib, with a stored, a fixed Huffman and a dynamic
+    // Huffman block.
+    let vectors: [(&str, &[u8]); 3] = [
+        (
+            "7801010c00f3ff73746f72656420626c6f636b1f8004bd",
+            b"stored block",
+        ),
+        ("78dacb48cdc9c9d75128cf2fca4901001d540489", b"hello, world"),
+        (
+            "78da6dced10d83400c03d0553c40c50e8c12388b8b4413e92e40bb7d5321413ffaebe8c51eed8d\
+             557762e6bae2d0a888c3e10d511b79de8cbad4c9b7d6d1b7b667d4078c290ba5fccabf64e2ec4f\
+             76c85d943a8d4765bbc38ea2845abef9fa5760a1b149a8db03620572a1ab3817859c43860fa778\
+             497b",
+            b"Any live cell with two or three live neighbours survives. Any dead cell with \
+              three live neighbours becomes a live cell. All other live cells die in the next \
+              generation, and all other dead cells stay dead.",
+        ),
+    ];
+
+    for (compressed, data) in vectors {
+        assert_eq!(zlib::decompress(&from_hex(compressed)).unwrap(), data);
+    }
+}
+
+#[test]
+fn png_round_trips_random_images() {
+    let mut rng = Rng::new(11);
+
+    for (width, height) in [(1, 1), (7, 3), (64, 40)] {
+        let image = RgbaImage {
+            width,
+            height,
+            pixels: random_bytes(&mut rng, width * height * 4, 256),
+        };
+
+        assert_eq!(png::decode(&png::encode(&image)), Ok(image));
+    }
+}
+
+#[test]
+fn png_decodes_images_from_another_encoder() {
+    // An RGBA image whose rows use the Sub, Up, Average and Paeth filters.
+    let rgba = png::decode(&from_hex(
+        "89504e470d0a1a0a0000000d49484452000000030000000408060000004b2d85070000003049444154\
+         78da6364f8cffa3fe008432310d7337131d83402713d8866e66f88ddaffb48ae1188eb5920320c4019\
+         864600a1f80e692e8c70b20000000049454e44ae426082",
+    ))
+    .unwrap();
+
+    assert_eq!((rgba.width, rgba.height), (3, 4));
+
+    for y in 0..4 {
+        for x in 0..3 {
+            let alpha = if (x + y) % 2 == 0 { 255 } else { 128 };
+
+            assert_eq!(
+                rgba.pixel(x, y),
+                [
+                    (x * 80 + y * 10) as u8,
+                    (255 - x * 60) as u8,
+                    (y * 60 + 5) as u8,
+                    alpha
+                ],
+                "pixel ({}, {})",
+                x,
+                y
+            );
+        }
+    }
+
+    // A 2-bit palette image, with transparency for the first three entries.
+    let palette = png::decode(&from_hex(
+        "89504e470d0a1a0a0000000d4948445200000005000000020203000000ed04fece0000000c504c5445\
+         000000ff000000ff000000ff9bc013dc0000000374524e5300ff8084eaba8c0000000e4944415478da\
+         6390766078d2000003d501c0bc2fd2a80000000049454e44ae426082",
+    ))
+    .unwrap();
+    let colors = [
+        [0, 0, 0, 0],
+        [255, 0, 0, 255],
+        [0, 255, 0, 128],
+        [0, 0, 255, 255],
+    ];
+
+    for (y, row) in [[0, 1, 2, 3, 1], [3, 2, 1, 0, 2]].iter().enumerate() {
+        for (x, index) in row.iter().enumerate() {
+            assert_eq!(palette.pixel(x, y), colors[*index], "pixel ({}, {})", x, y);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use game_of_life::{pnm, svg, Board, Config};
+
+#[test]
+fn pbm_packs_rows_into_bytes_with_the_first_cell_in_the_high_bit() {
+    let mut board = Board::empty(10, 3);
+
+    for (x, y) in [(0, 0), (7, 0), (8, 0), (9, 2)] {
+        board.toggle(x, y);
+    }
+
+    let mut expected = b"P4\n10 3\n".to_vec();
+    expected.extend_from_slice(&[0x81, 0x80, 0x00, 0x00, 0x00, 0x40]);
+
+    assert_eq!(pnm::encode_pbm(&board), expected);
+}
+
+#[test]
+fn svg_draws_a_rect_per_cell_in_the_theme_colors() {
+    let mut board = Board::empty(3, 2);
+
+    // The lone cell dies into the trail, then a live and a poisoned cell
+    // join it.
+    board.set_trail_length(2);
+    board.toggle(0, 0);
+    board.step_n(1);
+    board.toggle(2, 1);
+    board.toggle_poison(1, 0);
+
+    let header = "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"20\" viewBox=\"0 0 30 20\" shape-rendering=\"crispEdges\">\n\
+                  <rect width=\"30\" height=\"20\" fill=\"#ffffff\"/>\n";
+    let dying = "<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" fill=\"#c6c6c6\"/>\n";
+    let rest = "<rect x=\"10\" y=\"0\" width=\"10\" height=\"10\" fill=\"#a020f0\"/>\n\
+                <rect x=\"20\" y=\"10\" width=\"10\" height=\"10\" fill=\"#000000\"/>\n\
+                </svg>\n";
+
+    assert_eq!(
+        svg::encode(&board, &Config::default(), true),
+        format!("{}{}{}", header, dying, rest)
+    );
+    assert_eq!(
+        svg::encode(&board, &Config::default(), false),
+        format!("{}{}", header, rest)
+    );
+}
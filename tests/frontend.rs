@@ -0,0 +1,369 @@
+use game_of_life::frontend::{Button, Control, Controls, Key, WindowEvent, MAX_FPS};
+use game_of_life::{Board, Bookmarks, Input};
+
+fn key(character: char) -> WindowEvent {
+    WindowEvent::Key {
+        key: Key::Char(character),
+        ctrl: false,
+    }
+}
+
+#[test]
+fn controls_turn_window_events_into_recordable_inputs() {
+    let board = Board::empty(8, 6);
+    let mut bookmarks = Bookmarks::new();
+    let mut controls = Controls::new(80, 60, 60, false);
+
+    assert_eq!(
+        controls.handle(&key('.'), &board, &bookmarks),
+        Control::None
+    );
+    assert_eq!(
+        controls.handle(&key(' '), &board, &bookmarks),
+        Control::Redraw
+    );
+    assert!(controls.paused);
+    assert_eq!(
+        controls.handle(&key('.'), &board, &bookmarks),
+        Control::Step
+    );
+    assert_eq!(
+        controls.handle(&key('+'), &board, &bookmarks),
+        Control::Input(Input::Speed(120))
+    );
+    controls.fps = MAX_FPS;
+    assert_eq!(
+        controls.handle(&key('='), &board, &bookmarks),
+        Control::Input(Input::Speed(MAX_FPS))
+    );
+    assert_eq!(
+        controls.handle(&key(']'), &board, &bookmarks),
+        Control::Input(Input::TrailLength(board.trail_length + 1))
+    );
+    assert_eq!(
+        controls.handle(
+            &WindowEvent::Click {
+                button: Button::Left,
+                x: 25,
+                y: 15,
+            },
+            &board,
+            &bookmarks
+        ),
+        Control::Input(Input::Toggle { x: 2, y: 1 })
+    );
+    assert_eq!(
+        controls.handle(
+            &WindowEvent::Click {
+                button: Button::Right,
+                x: 79,
+                y: 59,
+            },
+            &board,
+            &bookmarks
+        ),
+        Control::Input(Input::Poison { x: 7, y: 5 })
+    );
+
+    assert_eq!(
+        controls.handle(&key('3'), &board, &bookmarks),
+        Control::None
+    );
+    assert_eq!(
+        controls.handle(
+            &WindowEvent::Key {
+                key: Key::Char('3'),
+                ctrl: true,
+            },
+            &board,
+            &bookmarks
+        ),
+        Control::Input(Input::SaveBookmark(3))
+    );
+    bookmarks.save(3, &board).unwrap();
+    assert_eq!(
+        controls.handle(&key('3'), &board, &bookmarks),
+        Control::Input(Input::RestoreBookmark(3))
+    );
+
+    assert_eq!(
+        controls.handle(
+            &WindowEvent::Resize {
+                width: 16,
+                height: 12,
+            },
+            &board,
+            &bookmarks
+        ),
+        Control::Redraw
+    );
+    assert_eq!(
+        controls.handle(
+            &WindowEvent::Click {
+                button: Button::Left,
+                x: 15,
+                y: 11,
+            },
+            &board,
+            &bookmarks
+        ),
+        Control::Input(Input::Toggle { x: 7, y: 5 })
+    );
+    assert_eq!(
+        controls.handle(&key('q'), &board, &bookmarks),
+        Control::Quit
+    );
+    assert_eq!(
+        controls.handle(
+            &WindowEvent::Key {
+                key: Key::Escape,
+                ctrl: false,
+            },
+            &board,
+            &bookmarks
+        ),
+        Control::Quit
+    );
+}
+
+#[test]
+fn replays_leave_only_the_viewing_controls() {
+    let board = Board::empty(8, 6);
+    let bookmarks = Bookmarks::new();
+    let mut controls = Controls::new(80, 60, 60, true);
+
+    assert_eq!(
+        controls.handle(&key(' '), &board, &bookmarks),
+        Control::Redraw
+    );
+    assert_eq!(
+        controls.handle(&key('.'), &board, &bookmarks),
+        Control::Step
+    );
+
+    for event in [
+        key('+'),
+        key('['),
+        WindowEvent::Key {
+            key: Key::Char('1'),
+            ctrl: true,
+        },
+        WindowEvent::Click {
+            button: Button::Left,
+            x: 40,
+            y: 30,
+        },
+    ] {
+        assert_eq!(controls.handle(&event, &board, &bookmarks), Control::None);
+    }
+
+    assert_eq!(
+        controls.handle(&WindowEvent::Close, &board, &bookmarks),
+        Control::Quit
+    );
+}
+
+/// Plays an X server on a Unix socket: answers the connection setup, the
+/// atoms and the keyboard mapping the window asks for, then sends a few
+/// events, and returns the opcodes and image pixels it was sent.
+#[cfg(all(feature = "x11", unix))]
+fn fake_x_server(listener: std::os::unix::net::UnixListener) -> (Vec<u8>, usize) {
+    use std::io::{Read, Write};
+
+    let (mut client, _) = listener.accept().unwrap();
+    let mut setup = [0; 12];
+
+    client.read_exact(&mut setup).unwrap();
+
+    let auth_length = |offset: usize| {
+        (u16::from_le_bytes([setup[offset], setup[offset + 1]]) as usize).div_ceil(4) * 4
+    };
+    let mut auth = vec![0; auth_length(6) + auth_length(8)];
+
+    client.read_exact(&mut auth).unwrap();
+
+    let mut body = Vec::new();
+    let vendor = b"fake";
+
+    body.extend(1u32.to_le_bytes());
+    body.extend(0x0400_0000u32.to_le_bytes());
+    body.extend(0x001f_ffffu32.to_le_bytes());
+    body.extend(0u32.to_le_bytes());
+    body.extend((vendor.len() as u16).to_le_bytes());
+    body.extend(4096u16.to_le_bytes());
+    // One screen and one format, little-endian images, then bitmap
+    // details, keycodes 8 to 255 and padding.
+    body.extend([1, 1, 0, 0, 32, 32, 8, 255, 0, 0, 0, 0]);
+    body.extend(vendor);
+    body.extend([24, 32, 32, 0, 0, 0, 0, 0]);
+    // The screen: root, colormap, white and black pixels, input masks,
+    // sizes, installed maps, root visual, backing stores, save unders,
+    // root depth and one allowed depth.
+    for value in [0x100u32, 0x20, 0xff_ffff, 0, 0] {
+        body.extend(value.to_le_bytes());
+    }
+    for value in [1920u16, 1080, 500, 300, 1, 1] {
+        body.extend(value.to_le_bytes());
+    }
+    body.extend(0x21u32.to_le_bytes());
+    body.extend([0, 0, 24, 1]);
+    // Depth 24 with one true color visual.
+    body.extend([24, 0, 1, 0, 0, 0, 0, 0]);
+    body.extend(0x21u32.to_le_bytes());
+    body.extend([4, 8]);
+    body.extend(256u16.to_le_bytes());
+    for mask in [0xff_0000u32, 0xff00, 0xff, 0] {
+        body.extend(mask.to_le_bytes());
+    }
+
+    let mut reply = vec![1, 0];
+
+    reply.extend(11u16.to_le_bytes());
+    reply.extend(0u16.to_le_bytes());
+    reply.extend(((body.len() / 4) as u16).to_le_bytes());
+    reply.extend(body);
+    client.write_all(&reply).unwrap();
+
+    let event = |code: u8, detail: u8, x: i16, y: i16, state: u16| {
+        let mut event = vec![code, detail, 0, 0];
+
+        event.extend([0; 20]);
+        event.extend(x.to_le_bytes());
+        event.extend(y.to_le_bytes());
+        event.extend(state.to_le_bytes());
+        event.extend([1, 0]);
+        event
+    };
+    let (mut opcodes, mut pixels, mut sequence, mut atoms) = (Vec::new(), 0, 0u16, 300u32);
+
+    loop {
+        let mut header = [0; 4];
+
+        if client.read_exact(&mut header).is_err() {
+            return (opcodes, pixels);
+        }
+
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize * 4;
+        let mut request = vec![0; length - 4];
+
+        client.read_exact(&mut request).unwrap();
+        sequence += 1;
+        opcodes.push(header[0]);
+
+        match header[0] {
+            // InternAtom
+            16 => {
+                let mut reply = vec![1, 0];
+
+                reply.extend(sequence.to_le_bytes());
+                reply.extend(0u32.to_le_bytes());
+                reply.extend(atoms.to_le_bytes());
+                reply.extend([0; 20]);
+                client.write_all(&reply).unwrap();
+                atoms += 1;
+            }
+            // GetKeyboardMapping: two keysyms a keycode, with `1` on
+            // keycode 10, `q` on 24 and space on 65.
+            101 => {
+                let (first, count) = (request[0] as usize, request[1] as usize);
+                let mut keysyms = vec![0u32; count * 2];
+
+                keysyms[(10 - first) * 2] = '1' as u32;
+                keysyms[(10 - first) * 2 + 1] = '!' as u32;
+                keysyms[(24 - first) * 2] = 'q' as u32;
+                keysyms[(65 - first) * 2] = ' ' as u32;
+
+                let mut reply = vec![1, 2];
+
+                reply.extend(sequence.to_le_bytes());
+                reply.extend((keysyms.len() as u32).to_le_bytes());
+                reply.extend([0; 24]);
+                reply.extend(keysyms.iter().flat_map(|keysym| keysym.to_le_bytes()));
+                client.write_all(&reply).unwrap();
+
+                for event in [
+                    event(2, 10, 0, 0, 0x4),
+                    event(2, 65, 0, 0, 0),
+                    event(4, 1, 20, 15, 0),
+                    event(4, 2, 20, 15, 0),
+                    event(2, 24, 0, 0, 0),
+                ] {
+                    client.write_all(&event).unwrap();
+                }
+            }
+            // PutImage
+            72 => {
+                let width = u16::from_le_bytes([request[8], request[9]]) as usize;
+                let height = u16::from_le_bytes([request[10], request[11]]) as usize;
+
+                assert_eq!(request.len(), 20 + width * height * 4);
+                pixels += width * height;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(feature = "x11", unix))]
+#[test]
+fn x11_windows_draw_frames_and_report_input() {
+    use game_of_life::frontend::Surface;
+    use game_of_life::x11::X11Window;
+    use game_of_life::{Rgb, RgbaImage};
+    use std::time::{Duration, Instant};
+
+    let directory = std::env::temp_dir().join(format!("gol-x11-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let display = directory.join("X:0");
+    let listener = std::os::unix::net::UnixListener::bind(&display).unwrap();
+    let server = std::thread::spawn(move || fake_x_server(listener));
+
+    std::env::set_var("DISPLAY", &display);
+    std::env::set_var("XAUTHORITY", directory.join("missing"));
+
+    let mut window = X11Window::open(40, 30, "test").unwrap();
+    let started_at = Instant::now();
+    let mut events = Vec::new();
+
+    window
+        .present(&RgbaImage::new(40, 30, Rgb { r: 0, g: 0, b: 0 }))
+        .unwrap();
+
+    while events.len() < 4 && started_at.elapsed() < Duration::from_secs(5) {
+        events.extend(window.poll_events().unwrap());
+    }
+
+    assert_eq!(
+        events,
+        vec![
+            WindowEvent::Key {
+                key: Key::Char('1'),
+                ctrl: true,
+            },
+            WindowEvent::Key {
+                key: Key::Char(' '),
+                ctrl: false,
+            },
+            WindowEvent::Click {
+                button: Button::Left,
+                x: 20,
+                y: 15,
+            },
+            WindowEvent::Key {
+                key: Key::Char('q'),
+                ctrl: false,
+            },
+        ]
+    );
+
+    drop(window);
+
+    let (opcodes, pixels) = server.join().unwrap();
+
+    // CreateWindow, CreateGC and MapWindow, then the image.
+    assert!([1, 55, 8, 72].iter().all(|opcode| opcodes.contains(opcode)));
+    assert_eq!(pixels, 40 * 30);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
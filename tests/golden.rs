@@ -0,0 +1,43 @@
+use game_of_life::golden::{Golden, GOLDEN_PATTERNS};
+use game_of_life::Engine;
+
+/// Cell updates above which a golden pattern is too slow to run in an
+/// unoptimized build.
+const QUICK_CELL_UPDATES: usize = 10_000_000;
+
+fn cell_updates(golden: &Golden) -> usize {
+    golden.size * golden.size * golden.generation
+}
+
+fn check(golden: &Golden) {
+    for engine in [Engine::Naive, Engine::Incremental] {
+        let mut board = golden.board(engine).expect("golden patterns parse");
+
+        board.step_n(golden.generation);
+
+        assert_eq!(
+            board.population(),
+            golden.population,
+            "{} on the {} engine",
+            golden.name,
+            engine.name()
+        );
+    }
+}
+
+#[test]
+fn golden_patterns_reach_their_known_populations() {
+    GOLDEN_PATTERNS
+        .iter()
+        .filter(|golden| cell_updates(golden) <= QUICK_CELL_UPDATES)
+        .for_each(check);
+}
+
+#[test]
+#[ignore = "slow without optimizations; run with cargo test --release -- --ignored"]
+fn long_golden_patterns_reach_their_known_populations() {
+    GOLDEN_PATTERNS
+        .iter()
+        .filter(|golden| cell_updates(golden) > QUICK_CELL_UPDATES)
+        .for_each(check);
+}
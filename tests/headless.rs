@@ -0,0 +1,95 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A scratch directory for one test's files.
+fn scratch(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("game-of-life-{}-{}", name, std::process::id()));
+
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn run_headless(arguments: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_game_of_life"))
+        .args(["--headless", "--board", "40x40", "--generations", "12"])
+        .args(arguments)
+        .output()
+        .expect("the binary runs");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn scripted_runs_replay_to_the_same_board_with_the_script_still_given() {
+    let directory = scratch("scripted-replay");
+    let path = |file: &str| directory.join(file).to_str().unwrap().to_string();
+
+    // Toggling twice undoes the toggle, so inputs made once by the replay
+    // and again by the script would end somewhere else.
+    fs::write(
+        path("script.txt"),
+        "at gen 0: stamp glider at 5,5\nat gen 3: toggle 20,20\nat gen 6: toggle 21,20\n",
+    )
+    .unwrap();
+
+    let script = path("script.txt");
+    let recording = path("run.replay");
+    let recorded = path("recorded.rle");
+    let replayed = path("replayed.rle");
+
+    run_headless(&[
+        "--seed",
+        "1",
+        "--script-actions",
+        &script,
+        "--record",
+        &recording,
+        "--save-rle",
+        &recorded,
+    ]);
+    run_headless(&[
+        "--replay",
+        &recording,
+        "--script-actions",
+        &script,
+        "--save-rle",
+        &replayed,
+    ]);
+
+    let replay = fs::read_to_string(&recording).unwrap();
+    assert!(replay.contains("toggle 20 20"), "{}", replay);
+    assert_eq!(
+        fs::read_to_string(&recorded).unwrap(),
+        fs::read_to_string(&replayed).unwrap()
+    );
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn boards_and_lattices_need_every_dimension_nonzero() {
+    for arguments in [
+        ["--board", "0x40"],
+        ["--lattice", "8x0x8"],
+        ["--lattice", "8x8x0"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_game_of_life"))
+            .args(["--headless", "--dimensions", "3", "--generations", "1"])
+            .args(arguments)
+            .output()
+            .expect("the binary runs");
+
+        assert!(!output.status.success(), "{:?} was accepted", arguments);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("nonzero"),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use game_of_life::script::ScriptAction;
+use game_of_life::{Input, Region, Replay, Rule, Script, ScriptCommand, SOUP_DENSITY};
+
+fn parse_input(line: &str) -> Result<Input, String> {
+    Replay::parse(&format!("3 120 {}", line)).map(|replay| replay.events[0].input.clone())
+}
+
+#[test]
+fn script_commands_parse_into_what_they_do() {
+    let cases = [
+        ("pause", ScriptCommand::Pause),
+        ("screenshot", ScriptCommand::Screenshot(None)),
+        (
+            "screenshot shot.png",
+            ScriptCommand::Screenshot(Some("shot.png".to_string())),
+        ),
+        ("goto 5000", ScriptCommand::Goto(5000)),
+        ("load gosper", ScriptCommand::Load("gosper".to_string())),
+        (
+            "seed 42",
+            ScriptCommand::Seed {
+                seed: 42,
+                density: SOUP_DENSITY,
+            },
+        ),
+        (
+            "seed 42 density 0.3",
+            ScriptCommand::Seed {
+                seed: 42,
+                density: 0.3,
+            },
+        ),
+        (
+            "stamp glider at 40,-3",
+            ScriptCommand::Input(Input::Stamp {
+                pattern: "glider".to_string(),
+                x: 40,
+                y: -3,
+            }),
+        ),
+        (
+            "toggle 1,2",
+            ScriptCommand::Input(Input::Toggle { x: 1, y: 2 }),
+        ),
+        (
+            "poison 3,4",
+            ScriptCommand::Input(Input::Poison { x: 3, y: 4 }),
+        ),
+        ("speed 30", ScriptCommand::Input(Input::Speed(30))),
+        ("trail 5", ScriptCommand::Input(Input::TrailLength(5))),
+        (
+            "rule B36/S23",
+            ScriptCommand::Input(Input::Rule(Rule::parse("B36/S23").unwrap())),
+        ),
+        ("bookmark 2", ScriptCommand::Input(Input::SaveBookmark(2))),
+        ("restore 9", ScriptCommand::Input(Input::RestoreBookmark(9))),
+    ];
+
+    for (text, command) in cases {
+        assert_eq!(ScriptCommand::parse(text), Ok(command), "{}", text);
+    }
+}
+
+#[test]
+fn script_commands_refuse_bad_arguments() {
+    for text in [
+        "",
+        "jump",
+        "pause now",
+        "goto -1",
+        "seed 1 density 1.5",
+        "seed 1 density lots",
+        "stamp nothing at 1,1",
+        "stamp glider at 1",
+        "toggle 1;2",
+        "speed 0",
+        "rule conway",
+        "bookmark 10",
+        "restore x",
+    ] {
+        assert!(ScriptCommand::parse(text).is_err(), "{:?} parsed", text);
+    }
+}
+
+#[test]
+fn scripts_parse_timed_commands_and_report_the_bad_line() {
+    let script = Script::parse("# warm up\n\nat gen 10: pause\n  at gen 20:goto 40  \n").unwrap();
+
+    assert_eq!(
+        script.actions,
+        vec![
+            ScriptAction {
+                generation: 10,
+                command: ScriptCommand::Pause,
+            },
+            ScriptAction {
+                generation: 20,
+                command: ScriptCommand::Goto(40),
+            },
+        ]
+    );
+
+    for (text, line) in [
+        ("at gen 1: pause\nat 2: pause\n", "line 2"),
+        ("at gen x: pause\n", "line 1"),
+        ("\nat gen 1: toggle\n", "line 2"),
+    ] {
+        let error = Script::parse(text).unwrap_err();
+
+        assert!(error.starts_with(line), "{}", error);
+    }
+}
+
+#[test]
+fn replay_inputs_parse_into_what_they_do() {
+    let cases = [
+        ("toggle 1 -2", Input::Toggle { x: 1, y: -2 }),
+        ("poison 3 4", Input::Poison { x: 3, y: 4 }),
+        (
+            "stamp glider 5 6",
+            Input::Stamp {
+                pattern: "glider".to_string(),
+                x: 5,
+                y: 6,
+            },
+        ),
+        (
+            "paste 7 8 2o$bo!",
+            Input::Paste {
+                cells: vec![(0, 0), (1, 0), (1, 1)],
+                x: 7,
+                y: 8,
+            },
+        ),
+        ("clear 0 1 2 3", Input::Clear(Region::new(0, 1, 2, 3))),
+        ("freeze 4 5 6 7", Input::Freeze(Region::new(4, 5, 6, 7))),
+        ("unfreeze 4 5 6 7", Input::Unfreeze(Region::new(4, 5, 6, 7))),
+        ("speed 60", Input::Speed(60)),
+        ("trail 4", Input::TrailLength(4)),
+        ("rule B3/S23", Input::Rule(Rule::parse("B3/S23").unwrap())),
+        ("bookmark 0", Input::SaveBookmark(0)),
+        ("restore 9", Input::RestoreBookmark(9)),
+        (
+            "resize 80 60",
+            Input::Resize {
+                width: 80,
+                height: 60,
+            },
+        ),
+    ];
+
+    for (line, input) in cases {
+        assert_eq!(parse_input(line), Ok(input), "{}", line);
+    }
+}
+
+#[test]
+fn replay_inputs_refuse_bad_fields() {
+    for line in [
+        "toggle 1",
+        "toggle one 2",
+        "stamp glider 1",
+        "paste 1 2 3q!",
+        "clear 0 1 2",
+        "speed 0",
+        "trail -1",
+        "rule B3",
+        "bookmark 10",
+        "resize 80",
+        "teleport 1 2",
+    ] {
+        let error = parse_input(line).unwrap_err();
+
+        assert!(error.starts_with("line 1: "), "{}: {}", line, error);
+    }
+}
@@ -0,0 +1,671 @@
+use game_of_life::period::find_periodicity;
+use game_of_life::predecessor::find_predecessor;
+use game_of_life::rng::Rng;
+use game_of_life::rule_search::{self, RULE_COUNT};
+use game_of_life::{
+    rle, Board, Bookmarks, Cell, CellState, ChunkIndex, DyingPolicy, Engine, Input, Pattern,
+    Periodicity, Region, Replay, ResourceField, ResourceRule, Rule, ScriptCommand, Topology,
+    WeightedRule, SOUP_DENSITY,
+};
+
+const TOPOLOGIES: [Topology; 6] = [
+    Topology::Plane,
+    Topology::Torus,
+    Topology::ShiftedTorus(3),
+    Topology::Cylinder,
+    Topology::KleinBottle,
+    Topology::Reflecting,
+];
+
+#[test]
+fn engines_agree_on_random_soups() {
+    for seed in 0..8 {
+        for topology in TOPOLOGIES {
+            let mut naive = Board::seeded(48, 32, seed, 0.35);
+            naive.topology = topology;
+
+            let mut incremental = naive.clone();
+            incremental.engine = Engine::Incremental;
+
+            let mut rng = Rng::new(seed);
+
+            for _ in 0..40 {
+                naive.step_n(1);
+                incremental.step_n(1);
+
+                // Edits between steps, like painting in the window, must
+                // leave the incremental counts right too.
+                let (x, y) = ((rng.next_u64() % 48) as i32, (rng.next_u64() % 32) as i32);
+
+                match rng.next_u64() % 3 {
+                    0 => {
+                        naive.toggle(x, y);
+                        incremental.toggle(x, y);
+                    }
+                    1 => {
+                        let block = Pattern::builtin("block").unwrap();
+                        naive.stamp(&block, x, y);
+                        incremental.stamp(&block, x, y);
+                    }
+                    _ => {
+                        let region = Region::new(x, y, 4, 3);
+                        naive.clear(&region);
+                        incremental.clear(&region);
+                    }
+                }
+
+                assert_eq!(
+                    naive.state_hash(),
+                    incremental.state_hash(),
+                    "seed {} on a {} diverged at generation {}",
+                    seed,
+                    topology.name(),
+                    naive.generation
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn weighted_rules_agree_across_engines_and_default_to_conway() {
+    let mut conway = Board::seeded(48, 32, 7, 0.35);
+    let mut unit_weights = conway.clone();
+    unit_weights.weighted_rule = Some(WeightedRule::default());
+
+    let mut naive = conway.clone();
+    naive.weighted_rule = Some(WeightedRule {
+        weights: WeightedRule::parse_weights("1 2 1 / 2 . 2 / 1 2 1").unwrap(),
+        birth: WeightedRule::parse_sums("4 6").unwrap(),
+        survive: WeightedRule::parse_sums("4 5 6").unwrap(),
+    });
+
+    let mut incremental = naive.clone();
+    incremental.engine = Engine::Incremental;
+
+    for _ in 0..40 {
+        for board in [&mut conway, &mut unit_weights, &mut naive, &mut incremental] {
+            board.step_n(1);
+        }
+
+        assert_eq!(conway.state_hash(), unit_weights.state_hash());
+        assert_eq!(naive.state_hash(), incremental.state_hash());
+    }
+
+    assert_ne!(conway.state_hash(), naive.state_hash());
+}
+
+#[test]
+fn canonical_hash_ignores_position_but_not_shape() {
+    let glider = "x = 3, y = 3\nbo$2bo$3o!";
+    let mut board = Board::from_rle(glider, 32, 32).expect("glider parses");
+    let start = board.canonical_hash();
+
+    board.step_n(1);
+    assert_ne!(board.canonical_hash(), start);
+
+    board.step_n(3);
+    assert_eq!(board.canonical_hash(), start);
+    assert_ne!(
+        board.state_hash(),
+        Board::from_rle(glider, 32, 32).unwrap().state_hash()
+    );
+}
+
+#[test]
+fn random_patterns_survive_an_rle_round_trip() {
+    let mut rng = Rng::new(42);
+
+    for _ in 0..50 {
+        let mut cells: Vec<(i32, i32)> = (0..40)
+            .map(|_| ((rng.next_u64() % 30) as i32, (rng.next_u64() % 20) as i32))
+            .collect();
+
+        cells.sort_by_key(|(x, y)| (*y, *x));
+        cells.dedup();
+
+        let decoded = rle::decode(&rle::encode(&Pattern::new("random", cells.clone())))
+            .expect("encoded patterns decode");
+        let mut decoded_cells = decoded.cells;
+        decoded_cells.sort_by_key(|(x, y)| (*y, *x));
+
+        let origin = cells.iter().fold((i32::MAX, i32::MAX), |(x, y), cell| {
+            (x.min(cell.0), y.min(cell.1))
+        });
+        let shifted: Vec<(i32, i32)> = cells
+            .iter()
+            .map(|(x, y)| (x - origin.0, y - origin.1))
+            .collect();
+
+        assert!(decoded_cells == cells || decoded_cells == shifted);
+    }
+}
+
+#[test]
+fn oversized_rle_runs_are_rejected_without_allocating_them() {
+    assert_eq!(rle::decode("x = 3, y = 1\n3o!").unwrap().cells.len(), 3);
+    assert!(rle::decode("x = 3, y = 1\n4o!").is_err());
+    assert!(rle::decode("x = 3, y = 1\n$o!").is_err());
+    assert!(rle::decode("x = 3\n3o!").is_err());
+    assert!(rle::decode_body("999999999o!").is_err());
+    assert!(rle::decode_body("2000000000b2000000000o!").is_err());
+    assert!(rle::decode_body("2000000000$2000000000$o!").is_err());
+    assert!(rle::decode_body("99999999999o!").is_err());
+}
+
+#[test]
+fn rotations_keep_the_corner_and_come_full_circle() {
+    let mut rng = Rng::new(7);
+
+    for _ in 0..50 {
+        let cells: Vec<(i32, i32)> = (0..20)
+            .map(|_| {
+                let x = 5 + (rng.next_u64() % 12) as i32;
+                (x, 3 + (rng.next_u64() % 7) as i32)
+            })
+            .collect();
+        let pattern = Pattern::new("random", cells);
+        let sorted = |pattern: Pattern| {
+            let mut cells = pattern.cells;
+            cells.sort_unstable();
+            cells
+        };
+        let quarter = pattern.rotated(90);
+        let (width, height) = pattern.size();
+
+        assert_eq!(quarter.origin(), pattern.origin());
+        assert_eq!(quarter.size(), (height, width));
+        assert_eq!(sorted(quarter.rotated(90)), sorted(pattern.rotated(180)));
+        assert_eq!(
+            sorted(pattern.rotated(180).rotated(180)),
+            sorted(pattern.clone())
+        );
+        assert_eq!(sorted(quarter.rotated(270)), sorted(pattern));
+    }
+}
+
+#[test]
+fn advanced_patterns_step_on_their_own_without_clipping() {
+    let glider = rle::decode("x = 3, y = 3\nbo$2bo$3o!").expect("glider parses");
+    let mut advanced = glider.advanced(Rule::default(), 4).cells;
+    let mut shifted: Vec<(i32, i32)> = glider.cells.iter().map(|(x, y)| (x + 1, y + 1)).collect();
+
+    advanced.sort_unstable();
+    shifted.sort_unstable();
+    assert_eq!(advanced, shifted);
+
+    let acorn = Pattern::builtin("acorn").expect("acorn is built in");
+    let mut board = Board::empty(256, 256);
+    board.stamp(&acorn, 128, 128);
+    board.step_n(60);
+
+    let mut on_board: Vec<(i32, i32)> = board
+        .live_cells()
+        .into_iter()
+        .map(|(x, y)| (x - 128, y - 128))
+        .collect();
+    let mut advanced = acorn.advanced(board.rule, 60).cells;
+
+    on_board.sort_unstable();
+    advanced.sort_unstable();
+    assert_eq!(advanced, on_board);
+}
+
+#[test]
+fn sandboxed_regions_write_back_through_a_replay() {
+    let mut board = Board::seeded(48, 32, 3, 0.35);
+    let region = Region::new(10, 6, 20, 12);
+    let mut sandbox = board.extract(&region);
+
+    sandbox.topology = Topology::Torus;
+    sandbox.step_n(30);
+
+    let mut replay = Replay::new(None);
+    replay.record(0, 0, Input::Clear(region));
+    replay.record(
+        0,
+        0,
+        Input::Paste {
+            cells: sandbox.live_cells(),
+            x: region.x,
+            y: region.y,
+        },
+    );
+
+    let replay = Replay::parse(&replay.to_text()).expect("replays parse");
+    let outside = board.clone();
+    let mut bookmarks = Bookmarks::new();
+
+    for event in replay.events_at(0) {
+        event.input.apply(&mut board, &mut bookmarks).unwrap();
+    }
+
+    for (index, cell) in board.cells.iter().enumerate() {
+        let (x, y) = board.index_to_coordinates(index);
+
+        if region.contains(x, y) {
+            let inside = (y - region.y) as usize * region.width + (x - region.x) as usize;
+            assert_eq!(cell.is_alive(), sandbox.cells[inside].is_alive());
+        } else {
+            assert_eq!(cell.is_alive(), outside.cells[index].is_alive());
+        }
+    }
+}
+
+#[test]
+fn replays_play_back_on_the_board_they_were_recorded_on() {
+    let mut recorded = Board::seeded(40, 30, 5, 0.35);
+    recorded.rule = Rule::parse("B36/S23").unwrap();
+    recorded.topology = Topology::KleinBottle;
+    recorded.engine = Engine::Incremental;
+    recorded.dying_policy = DyingPolicy::Decay;
+
+    let mut replay = Replay::recording(Some(5), &recorded);
+    replay.record(3, 0, Input::Toggle { x: 1, y: 1 });
+
+    let replay = Replay::parse(&replay.to_text()).expect("replays parse");
+    assert_eq!(replay.settings.size, Some((40, 30)));
+
+    // The rule, topology, engine and dying policy are applied on playback.
+    let mut board = Board::seeded(40, 30, 5, 0.35);
+    replay.settings.apply(&mut board).unwrap();
+    assert_eq!(board.rule, recorded.rule);
+    assert_eq!(board.topology, Topology::KleinBottle);
+    assert_eq!(board.engine, Engine::Incremental);
+    assert_eq!(board.dying_policy, DyingPolicy::Decay);
+
+    // A board of another size or with other starting cells can't follow it.
+    let error = replay
+        .settings
+        .apply(&mut Board::seeded(48, 30, 5, 0.35))
+        .unwrap_err();
+    assert!(error.contains("--board 40x30"), "{}", error);
+    assert!(replay
+        .settings
+        .apply(&mut Board::seeded(40, 30, 6, 0.35))
+        .is_err());
+
+    // Replays from before the settings were recorded still play on anything.
+    let old = Replay::parse("seed 5\n3 0 toggle 1 1\n").unwrap();
+    assert!(old.settings.apply(&mut Board::new(8, 8)).is_ok());
+}
+
+#[test]
+fn replays_refuse_inputs_the_frontends_cant_produce() {
+    assert!(Replay::parse("0 0 speed 1\n").is_ok());
+    assert!(Replay::parse("0 0 speed 0\n").is_err());
+    assert!(Replay::parse("0 0 bookmark 9\n").is_ok());
+    assert!(Replay::parse("0 0 bookmark 10\n").is_err());
+    assert!(Replay::parse("0 0 restore 10\n").is_err());
+}
+
+#[test]
+fn spaceship_velocities_keep_their_direction() {
+    let velocity = |rle_text: &str| {
+        let cells = rle::decode(rle_text).unwrap().cells;
+
+        find_periodicity(&cells, Rule::default(), 8)
+            .expect("spaceships repeat")
+            .velocity()
+    };
+
+    assert_eq!(velocity("x = 3, y = 3\nbo$2bo$3o!"), "c/4 diagonal");
+    assert_eq!(
+        velocity("x = 5, y = 4\nbo2bo$o4b$o3bo$4o!"),
+        "c/2 orthogonal"
+    );
+
+    // An oblique ship reports how far it moves along each axis.
+    let oblique = Periodicity {
+        period: 6,
+        dx: -2,
+        dy: 1,
+        heat: 0.0,
+    };
+    assert_eq!(oblique.velocity(), "(2,1)c/6 oblique");
+    assert_eq!(
+        Periodicity {
+            period: 12,
+            dx: 2,
+            dy: 4,
+            ..oblique
+        }
+        .velocity(),
+        "(1,2)c/6 oblique"
+    );
+}
+
+#[test]
+fn shifted_tori_carry_gliders_across_the_seam_shifted() {
+    assert_eq!(Topology::parse("torus+3"), Ok(Topology::ShiftedTorus(3)));
+    assert_eq!(Topology::parse("torus-2"), Ok(Topology::ShiftedTorus(-2)));
+    assert_eq!(Topology::ShiftedTorus(-2).name(), "torus-2");
+    assert!(Topology::parse("torus+").is_err());
+
+    // A glider crosses the 32-row board diagonally in 128 generations: on a
+    // plain torus it comes back where it started, on a shifted one it comes
+    // back moved along by the shift.
+    let glider = rle::decode("x = 3, y = 3\nbo$2bo$3o!").expect("glider parses");
+
+    for shift in [0, 5] {
+        let mut board = Board::empty(32, 32);
+        board.topology = Topology::ShiftedTorus(shift);
+        board.stamp(&glider, 10, 10);
+        board.step_n(128);
+
+        let mut expected = Board::empty(32, 32);
+        expected.stamp(&glider, 10 + shift, 10);
+
+        assert_eq!(board.live_cells(), expected.live_cells());
+    }
+}
+
+#[test]
+fn resource_fields_agree_across_engines_and_starve_births() {
+    let plain = Board::seeded(48, 32, 5, 0.35);
+    let mut naive = plain.clone();
+    naive.resources = Some(ResourceField::new(ResourceRule::default(), 48 * 32));
+
+    let mut incremental = naive.clone();
+    incremental.engine = Engine::Incremental;
+
+    let mut plain = plain;
+
+    for _ in 0..60 {
+        for board in [&mut plain, &mut naive, &mut incremental] {
+            board.step_n(1);
+        }
+
+        assert_eq!(naive.state_hash(), incremental.state_hash());
+    }
+
+    assert_ne!(plain.state_hash(), naive.state_hash());
+}
+
+#[test]
+fn rule_search_samples_distinct_rules_without_b0() {
+    let all = rule_search::rules(None, 0);
+    let sample = rule_search::rules(Some(500), 9);
+
+    assert_eq!(all.len(), RULE_COUNT);
+    assert_eq!(sample, rule_search::rules(Some(500), 9));
+
+    for rules in [&all, &sample] {
+        let mut names: Vec<String> = rules.iter().map(|rule| rule.name()).collect();
+        names.sort();
+        names.dedup();
+
+        assert_eq!(names.len(), rules.len());
+        assert!(rules.iter().all(|rule| !rule.is_born(0)));
+    }
+
+    assert!(all.contains(&Rule::parse("B3/S23").unwrap()));
+}
+
+#[test]
+fn frozen_regions_hold_but_still_count_as_neighbors() {
+    // A blinker frozen mid-flip: its ends would die and the cells beside its
+    // middle would be born.
+    let blinker = Pattern::new("blinker", vec![(0, 0), (0, 1), (0, 2)]);
+    let region = Region::new(5, 4, 1, 3);
+
+    for engine in [Engine::Naive, Engine::Incremental] {
+        let mut board = Board::empty(16, 12);
+        board.engine = engine;
+        board.stamp(&blinker, 5, 4);
+        board.set_frozen(&region, true);
+
+        board.toggle(5, 5);
+        board.stamp(&Pattern::new("dot", vec![(0, 0)]), 5, 3);
+        board.clear(&Region::new(0, 0, 16, 12));
+        assert_eq!(board.live_cells(), vec![(5, 4), (5, 5), (5, 6)]);
+
+        board.step_n(1);
+        assert_eq!(
+            board.live_cells(),
+            vec![(5, 4), (4, 5), (5, 5), (6, 5), (5, 6)]
+        );
+
+        assert!(board.is_frozen(&region));
+        board.set_frozen(&region, false);
+        assert!(!board.is_frozen(&region));
+    }
+}
+
+#[test]
+fn dying_policies_agree_across_engines() {
+    for policy in [DyingPolicy::Dead, DyingPolicy::Alive, DyingPolicy::Decay] {
+        let mut naive = Board::seeded(48, 32, 9, 0.35);
+        naive.dying_policy = policy;
+
+        let mut incremental = naive.clone();
+        incremental.engine = Engine::Incremental;
+
+        for _ in 0..40 {
+            naive.step_n(1);
+            incremental.step_n(1);
+
+            assert_eq!(
+                naive.state_hash(),
+                incremental.state_hash(),
+                "{} policy diverged at generation {}",
+                policy.name(),
+                naive.generation
+            );
+        }
+    }
+}
+
+#[test]
+fn dying_policies_decide_counting_and_rebirth() {
+    // (2, 2) has two live neighbors and a dying one, and the dying cell at
+    // (5, 5) has three live neighbors.
+    let board = |policy| {
+        let mut board = Board::empty(8, 8);
+        board.dying_policy = policy;
+
+        for (x, y) in [(1, 1), (2, 1), (4, 4), (5, 4), (6, 4)] {
+            board.toggle(x, y);
+        }
+
+        for index in [8 + 3, 5 * 8 + 5] {
+            board.cells[index].state = CellState::Dying(3);
+        }
+
+        board.step_n(1);
+        board
+    };
+    let state = |board: &Board, x: usize, y: usize| board.cells[y * 8 + x].state.clone();
+
+    let dead = board(DyingPolicy::Dead);
+    assert!(matches!(state(&dead, 2, 2), CellState::Dead));
+    assert!(matches!(state(&dead, 5, 5), CellState::Alive));
+
+    let alive = board(DyingPolicy::Alive);
+    assert!(matches!(state(&alive, 2, 2), CellState::Alive));
+    assert!(matches!(state(&alive, 5, 5), CellState::Alive));
+
+    let decay = board(DyingPolicy::Decay);
+    assert!(matches!(state(&decay, 2, 2), CellState::Dead));
+    assert!(matches!(state(&decay, 5, 5), CellState::Dying(2)));
+}
+
+#[test]
+fn chunk_index_finds_the_occupied_cells_in_any_rectangle() {
+    let mut rng = Rng::new(11);
+    let mut board = Board::seeded(150, 90, 4, 0.01);
+    board.step_n(3);
+    board.set_frozen(&Region::new(100, 60, 5, 5), true);
+
+    let chunks = ChunkIndex::new(&board);
+
+    for _ in 0..100 {
+        let (x0, x1) = (rng.next_u64() % 160, rng.next_u64() % 160);
+        let (y0, y1) = (rng.next_u64() % 100, rng.next_u64() % 100);
+        let (columns, rows) = (
+            x0.min(x1) as usize..x0.max(x1) as usize,
+            y0.min(y1) as usize..y0.max(y1) as usize,
+        );
+        let mut occupied = chunks.occupied_in(&board, (columns.clone(), rows.clone()));
+        let expected: Vec<usize> = rows
+            .filter(|y| *y < board.height)
+            .flat_map(|y| columns.clone().map(move |x| (x, y)))
+            .filter(|(x, _)| *x < board.width)
+            .map(|(x, y)| y * board.width + x)
+            .filter(|index| {
+                let cell = &board.cells[*index];
+                cell.frozen || !matches!(cell.state, CellState::Dead)
+            })
+            .collect();
+
+        occupied.sort_unstable();
+        assert_eq!(occupied, expected);
+    }
+}
+
+#[test]
+fn predicted_steps_match_real_ones_without_taking_them() {
+    for engine in [Engine::Naive, Engine::Incremental] {
+        let mut board = Board::seeded(40, 30, 6, 0.35);
+        board.engine = engine;
+        board.step_n(5);
+
+        let before = board.alive_mask();
+        let hash = board.state_hash();
+        let (births, deaths) = board.predict_step();
+
+        assert_eq!(board.state_hash(), hash);
+
+        board.step_n(1);
+
+        let after = board.alive_mask();
+        let changed = |alive: bool| -> Vec<usize> {
+            (0..after.len())
+                .filter(|index| before[*index] != alive && after[*index] == alive)
+                .collect()
+        };
+
+        assert_eq!(births, changed(true));
+        assert_eq!(deaths, changed(false));
+    }
+}
+
+#[test]
+fn console_commands_parse_and_replace_the_board() {
+    assert_eq!(
+        ScriptCommand::parse("rule B36/S23"),
+        Ok(ScriptCommand::Input(Input::Rule(
+            Rule::parse("B36/S23").unwrap()
+        )))
+    );
+    assert_eq!(
+        ScriptCommand::parse(" goto 10000 "),
+        Ok(ScriptCommand::Goto(10000))
+    );
+    assert_eq!(
+        ScriptCommand::parse("seed 42"),
+        Ok(ScriptCommand::Seed {
+            seed: 42,
+            density: SOUP_DENSITY
+        })
+    );
+    assert!(ScriptCommand::parse("seed 42 density 1.5").is_err());
+    assert!(ScriptCommand::parse("goto").is_err());
+    assert!(ScriptCommand::parse("bookmark 12").is_err());
+
+    let mut board = Board::seeded(40, 30, 1, 0.5);
+    let mut bookmarks = Bookmarks::new();
+    let hash = board.state_hash();
+
+    assert_eq!(
+        Input::RestoreBookmark(3).apply(&mut board, &mut bookmarks),
+        Ok(())
+    );
+    assert_eq!(board.state_hash(), hash);
+    board.cells[0] = Cell::poison();
+
+    let seed = ScriptCommand::parse("seed 42 density 0.3").unwrap();
+
+    for input in seed.replacement(&board).unwrap() {
+        input.apply(&mut board, &mut bookmarks).unwrap();
+    }
+
+    let mut soup = Board::seeded(40, 30, 42, 0.3);
+    soup.cells[0] = Cell::poison();
+    assert_eq!(board.alive_mask(), soup.alive_mask());
+    assert!(board.cells[0].is_poison());
+
+    for input in ScriptCommand::Load("glider".to_string())
+        .replacement(&board)
+        .unwrap()
+    {
+        input.apply(&mut board, &mut bookmarks).unwrap();
+    }
+
+    assert_eq!(board.population(), 5);
+    assert!(ScriptCommand::Load("no-such-pattern.rle".to_string())
+        .replacement(&board)
+        .is_err());
+}
+
+#[test]
+fn regions_parse_only_with_a_nonzero_size() {
+    assert_eq!(Region::parse("3,-4,5x6"), Ok(Region::new(3, -4, 5, 6)));
+
+    for text in ["0,0,0x5", "0,0,5x0", "0,0,5", "0,0,5xa"] {
+        assert!(Region::parse(text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn predecessors_step_into_their_target_under_the_board_rule() {
+    let mut rng = Rng::new(360);
+
+    for rule in ["B3/S23", "B36/S23", "B2/S"] {
+        for region in [Region::new(4, 3, 4, 3), Region::new(0, 0, 3, 3)] {
+            for _ in 0..10 {
+                let mut board = Board::empty(12, 10);
+
+                board.rule = Rule::parse(rule).unwrap();
+
+                for y in 0..region.height as i32 {
+                    for x in 0..region.width as i32 {
+                        if rng.next_u64().is_multiple_of(3) {
+                            board.toggle(region.x + x, region.y + y);
+                        }
+                    }
+                }
+
+                let target = board.alive_in(&region);
+                let Some(predecessor) = find_predecessor(&board, &region).unwrap() else {
+                    continue;
+                };
+                let mut stepped = Board::empty(12, 10);
+
+                stepped.rule = board.rule;
+
+                for (index, _) in predecessor.iter().enumerate().filter(|(_, alive)| **alive) {
+                    let (x, y) = (index % (region.width + 2), index / (region.width + 2));
+
+                    stepped.toggle(region.x - 1 + x as i32, region.y - 1 + y as i32);
+                }
+
+                stepped.step_n(1);
+                assert_eq!(
+                    stepped.alive_in(&region),
+                    target,
+                    "{} at {:?}",
+                    rule,
+                    region
+                );
+            }
+        }
+    }
+
+    let mut torus = Board::empty(12, 10);
+
+    torus.topology = Topology::Torus;
+    assert!(find_predecessor(&torus, &Region::new(0, 4, 3, 3)).is_err());
+    assert!(find_predecessor(&torus, &Region::new(1, 4, 3, 3)).is_ok());
+    assert!(find_predecessor(&torus, &Region::new(10, 4, 3, 3)).is_err());
+}
@@ -0,0 +1,156 @@
+use game_of_life::chart::population_chart;
+use game_of_life::raster::{fit_cell, render_fit, render_pattern, render_to_image};
+use game_of_life::{png, Board, Config, Pattern, PopulationHistory};
+use std::env;
+use std::fs;
+
+#[test]
+fn render_to_image_scales_cells_and_leaves_dead_cells_transparent() {
+    let mut board = Board::empty(4, 3);
+    board.toggle(1, 2);
+
+    let image = render_to_image(&board, &Config::default(), 3);
+
+    assert_eq!((image.width, image.height), (12, 9));
+    assert_eq!(image.pixel(3, 6), [0, 0, 0, 255]);
+    assert_eq!(image.pixel(5, 8), [0, 0, 0, 255]);
+    assert_eq!(image.pixel(2, 6)[3], 0);
+    assert_eq!(image.pixel(6, 6)[3], 0);
+}
+
+#[test]
+fn render_pattern_crops_to_the_bounding_box() {
+    let glider = Pattern::builtin("glider").expect("glider is built in");
+    let shifted = Pattern::new(
+        "glider",
+        glider.cells.iter().map(|(x, y)| (x + 20, y - 7)).collect(),
+    );
+
+    assert_eq!(
+        render_pattern(&shifted, &Config::default(), 2),
+        render_pattern(&glider, &Config::default(), 2)
+    );
+    assert_eq!(render_pattern(&glider, &Config::default(), 2).width, 6);
+}
+
+#[test]
+fn render_fit_centers_the_board_on_the_background() {
+    let mut board = Board::empty(2, 2);
+    board.toggle(0, 0);
+
+    let image = render_fit(&board, &Config::default(), 10, 6);
+
+    assert_eq!(image.pixel(2, 0), [0, 0, 0, 255]);
+    assert_eq!(image.pixel(4, 2), [0, 0, 0, 255]);
+    assert_eq!(image.pixel(5, 3), [255, 255, 255, 255]);
+    assert_eq!(image.pixel(0, 0), [255, 255, 255, 255]);
+}
+
+#[test]
+fn timelapse_frames_load_back_as_the_rendered_pixels() {
+    let mut board = Board::seeded(30, 20, 4, 0.35);
+    board.set_trail_length(4);
+    board.step_n(3);
+
+    let image = render_fit(&board, &Config::default(), 97, 61);
+    let path = env::temp_dir().join(format!("game-of-life-frame-{}.png", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    png::save(path, &image).unwrap();
+    let loaded = png::load(path);
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(loaded, Ok(image));
+}
+
+#[test]
+fn fit_cell_finds_the_cell_render_fit_draws_at_a_pixel() {
+    let board = Board::empty(7, 5);
+
+    for (width, height) in [(10usize, 6usize), (70, 40), (29, 31)] {
+        for y in 0..height {
+            for x in 0..width {
+                let mut marked = Board::empty(7, 5);
+
+                if let Some((column, row)) = fit_cell(&board, width, height, (x as i32, y as i32)) {
+                    marked.toggle(column, row);
+
+                    let image = render_fit(&marked, &Config::default(), width, height);
+
+                    assert_eq!(
+                        image.pixel(x, y),
+                        [0, 0, 0, 255],
+                        "{}x{} at {},{}",
+                        width,
+                        height,
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+    }
+
+    assert_eq!(fit_cell(&board, 70, 40, (0, 0)), None);
+    assert_eq!(fit_cell(&board, 70, 40, (35, 20)), Some((3, 2)));
+}
+
+#[test]
+fn hatched_themes_draw_dying_cells_as_patterns() {
+    let config = Config::parse("theme = okabe-ito\ntheme.dying_hatch = true").unwrap();
+    let mut board = Board::empty(3, 3);
+    board.toggle(1, 1);
+    board.update_live_neighbor_counts();
+    board.step();
+
+    let image = render_to_image(&board, &config, 8);
+    let covered = (8..16)
+        .flat_map(|y| (8..16).map(move |x| (x, y)))
+        .filter(|(x, y)| image.pixel(*x, *y) == [0xe6, 0x9f, 0x00, 255])
+        .count();
+
+    assert_eq!(image.pixel(8, 8), [0xe6, 0x9f, 0x00, 255]);
+    assert_eq!(image.pixel(10, 8), [255, 255, 255, 255]);
+    assert!(covered > 8 && covered < 64);
+
+    let unhatched = Config::parse("theme = high-contrast\ntheme.dying_hatch = false").unwrap();
+
+    assert!(
+        Config::parse("theme = high-contrast")
+            .unwrap()
+            .theme
+            .dying_hatch
+    );
+    assert!(unhatched.theme.dying_sprites.is_empty());
+}
+
+#[test]
+fn population_charts_plot_every_generation_recorded() {
+    let config = Config::default();
+    let mut board = Board::seeded(40, 40, 2, 0.3);
+    let mut history = PopulationHistory::new();
+
+    for _ in 0..100 {
+        history.record(&board);
+        board.step_n(1);
+    }
+
+    assert_eq!(history.samples().len(), 100);
+    assert_eq!(
+        history.samples()[0],
+        (0, Board::seeded(40, 40, 2, 0.3).population())
+    );
+
+    let chart = population_chart(history.samples(), &config.theme, (400, 300));
+    let ink = (0..300)
+        .flat_map(|y| (0..400).map(move |x| (x, y)))
+        .filter(|(x, y)| chart.pixel(*x, *y) == [0, 0, 0, 255])
+        .count();
+
+    assert_eq!((chart.width, chart.height), (400, 300));
+    assert_eq!(chart.pixel(399, 0), [255, 255, 255, 255]);
+    assert!(ink > 300);
+
+    history.record(&Board::empty(10, 10));
+    assert_eq!(history.samples(), &[(0, 0)]);
+}
@@ -0,0 +1,103 @@
+use game_of_life::{
+    Board, Bookmarks, Checkpoints, Config, GenerationStats, Placement, Session, Smoothing,
+    Workspace,
+};
+use std::fs;
+
+#[test]
+fn workspaces_survive_a_save_and_load() {
+    let directory = std::env::temp_dir().join(format!("workspaces-{}", std::process::id()));
+    let directory = directory.to_string_lossy().to_string();
+    let mut board = Board::seeded(40, 30, 3, 0.3);
+    let mut bookmarks = Bookmarks::new();
+
+    bookmarks.save(4, &board).unwrap();
+    board.step_n(5);
+
+    let workspace = Workspace {
+        board: board.clone(),
+        session: Session {
+            camera: (3, -2, 1.5),
+            fps: 12,
+            smoothing: Smoothing::Fade,
+        },
+        theme: Config::parse("theme = amber").unwrap().theme,
+        bookmarks,
+        stats: vec![GenerationStats::measure(&board, &board.alive_mask())],
+    };
+
+    workspace.save(&directory, "soup-3").unwrap();
+
+    let mut loaded = Workspace::load(&directory, "soup-3").unwrap();
+    let mut restored = Board::empty(1, 1);
+
+    assert_eq!(Workspace::list(&directory).unwrap(), vec!["soup-3"]);
+    assert_eq!(loaded.board.state_hash(), board.state_hash());
+    assert_eq!(loaded.session, workspace.session);
+    assert_eq!(loaded.theme, workspace.theme);
+    assert_eq!(loaded.stats.len(), 1);
+    assert_eq!(loaded.stats[0].population, workspace.stats[0].population);
+
+    loaded.bookmarks.restore(4, &mut restored).unwrap();
+    assert_eq!(
+        restored.live_cells(),
+        Board::seeded(40, 30, 3, 0.3).live_cells()
+    );
+    assert!(Workspace::load(&directory, "../soup-3").is_err());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn long_runs_resume_from_their_last_checkpoint() {
+    let directory = std::env::temp_dir().join(format!("checkpoints-{}", std::process::id()));
+    let directory = directory.to_string_lossy().to_string();
+    let checkpoints = Checkpoints::create(&directory, 20).unwrap();
+    let mut board = Board::seeded(40, 30, 5, 0.35);
+    board.set_trail_length(4);
+
+    for _ in 0..50 {
+        board.step_n(1);
+        checkpoints.save_if_due(&board).unwrap();
+    }
+
+    let mut resumed = Checkpoints::load_latest(&directory).unwrap();
+    assert_eq!(resumed.generation, 40);
+
+    resumed.step_n(10);
+    assert_eq!(resumed.state_hash(), board.state_hash());
+
+    checkpoints.finish(&board).unwrap();
+    assert_eq!(Checkpoints::load_latest(&directory).unwrap().generation, 50);
+
+    fs::write(
+        std::path::Path::new(&directory).join("generation-50.session"),
+        "generation = 50\nboard = 40x30\nfps = 1\n",
+    )
+    .unwrap();
+    assert!(Checkpoints::load_latest(&directory).is_err());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn placements_only_read_a_trailing_position() {
+    let directory = std::env::temp_dir().join(format!("placements-{}", std::process::id()));
+    let path = directory.join("me@home.rle");
+    let path = path.to_string_lossy().to_string();
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&path, "x = 3, y = 3\nbo$2bo$3o!").unwrap();
+
+    let centered = Placement::parse(&path).unwrap();
+    assert_eq!(centered.position, None);
+    assert_eq!(centered.pattern.cells.len(), 5);
+
+    let placed = Placement::parse(&format!("{}@10,-4,90", path)).unwrap();
+    assert_eq!(placed.position, Some((10, -4)));
+    assert_eq!(placed.pattern.size(), (3, 3));
+
+    assert!(Placement::parse(&format!("{}@10,4,45", path)).is_err());
+
+    fs::remove_dir_all(&directory).unwrap();
+}